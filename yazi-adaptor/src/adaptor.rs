@@ -1,17 +1,21 @@
-use std::{env, path::{Path, PathBuf}, sync::atomic::{AtomicBool, Ordering}};
+use std::{env, path::{Path, PathBuf}, sync::atomic::{AtomicBool, AtomicUsize, Ordering}};
 
 use anyhow::{anyhow, Result};
 use ratatui::prelude::Rect;
-use tokio::{fs, sync::mpsc::UnboundedSender};
+use tokio::{sync::mpsc::UnboundedSender, time::sleep};
 use tracing::warn;
 use yazi_config::PREVIEW;
 use yazi_shared::{env_exists, RoCell};
 
 use super::{Iterm2, Kitty, KittyOld};
-use crate::{ueberzug::Ueberzug, Sixel, TMUX};
+use crate::{ueberzug::Ueberzug, Image, Sixel, TMUX};
 
 static IMAGE_SHOWN: AtomicBool = AtomicBool::new(false);
 
+// Bumped on every hide, so that an image or frame still being encoded by then
+// is never placed
+pub(super) static IMAGE_EPOCH: AtomicUsize = AtomicUsize::new(0);
+
 #[allow(clippy::type_complexity)]
 static UEBERZUG: RoCell<Option<UnboundedSender<Option<(PathBuf, Rect)>>>> = RoCell::new();
 
@@ -36,6 +40,8 @@ enum Emulator {
 	Iterm2,
 	WezTerm,
 	Foot,
+	Xterm,
+	Mlterm,
 	BlackBox,
 	VSCode,
 	Hyper,
@@ -55,6 +61,8 @@ impl Adaptor {
 			("ITERM_SESSION_ID", Emulator::Iterm2),
 			("WEZTERM_EXECUTABLE", Emulator::WezTerm),
 			("VSCODE_INJECTION", Emulator::VSCode),
+			("XTERM_VERSION", Emulator::Xterm),
+			("MLTERM", Emulator::Mlterm),
 		];
 		match vars.into_iter().find(|v| env_exists(v.0)) {
 			Some(var) => return var.1,
@@ -75,6 +83,7 @@ impl Adaptor {
 			"xterm-kitty" => return Emulator::Kitty,
 			"foot" => return Emulator::Foot,
 			"foot-extra" => return Emulator::Foot,
+			"mlterm" => return Emulator::Mlterm,
 			_ => warn!("[Adaptor] Unknown TERM: {term}"),
 		}
 		Emulator::Unknown
@@ -88,6 +97,8 @@ impl Adaptor {
 			Emulator::Iterm2 => vec![Self::Iterm2, Self::Sixel],
 			Emulator::WezTerm => vec![Self::Iterm2, Self::Sixel],
 			Emulator::Foot => vec![Self::Sixel],
+			Emulator::Xterm => vec![Self::Sixel],
+			Emulator::Mlterm => vec![Self::Sixel],
 			Emulator::BlackBox => vec![Self::Sixel],
 			Emulator::VSCode => vec![Self::Sixel],
 			Emulator::Hyper => vec![Self::Sixel],
@@ -168,18 +179,14 @@ impl Adaptor {
 		UEBERZUG.init(if self.needs_ueberzug() { Ueberzug::start(self).ok() } else { None });
 	}
 
-	pub async fn image_show(self, mut path: &Path, rect: Rect) -> Result<()> {
-		let cache = PREVIEW.cache(path, 0);
-		if fs::symlink_metadata(&cache).await.is_ok() {
-			path = cache.as_path();
-		}
-
+	pub async fn image_show(self, path: &Path, rect: Rect) -> Result<()> {
 		self.image_hide(rect).ok();
 		IMAGE_SHOWN.store(true, Ordering::Relaxed);
+		let epoch = IMAGE_EPOCH.load(Ordering::Relaxed);
 
 		match self {
-			Self::Kitty => Kitty::image_show(path, rect).await,
-			Self::KittyOld => KittyOld::image_show(path, rect).await,
+			Self::Kitty => Kitty::image_show(path, rect, epoch).await,
+			Self::KittyOld => KittyOld::image_show(path, rect, epoch).await,
 			Self::Iterm2 => Iterm2::image_show(path, rect).await,
 			Self::Sixel => Sixel::image_show(path, rect).await,
 			_ => Ok(if let Some(tx) = &*UEBERZUG {
@@ -188,7 +195,46 @@ impl Adaptor {
 		}
 	}
 
+	// Cycle through the frames of an animated image until the task is aborted,
+	// the first frame is expected to be already shown by `image_show()`
+	pub async fn image_animate(self, path: &Path, rect: Rect) -> Result<()> {
+		if !PREVIEW.animate || !matches!(self, Self::Kitty | Self::Iterm2 | Self::Sixel) {
+			return Ok(());
+		}
+
+		let epoch = IMAGE_EPOCH.load(Ordering::Relaxed);
+		let encode = match self {
+			Self::Kitty => Kitty::frame_encode,
+			Self::Iterm2 => Iterm2::encode_sync,
+			Self::Sixel => Sixel::encode_sync,
+			_ => unreachable!(),
+		};
+
+		// Each frame is encoded once, and sent as is on every loop
+		let frames = Image::frames(path, (rect.width, rect.height), encode).await?;
+		if frames.is_empty() {
+			return Ok(());
+		}
+
+		loop {
+			for (b, delay) in &frames {
+				if !IMAGE_SHOWN.load(Ordering::Relaxed) || IMAGE_EPOCH.load(Ordering::Relaxed) != epoch {
+					return Ok(());
+				}
+
+				match self {
+					Self::Kitty => Kitty::frame_place(b, rect, epoch)?,
+					Self::Iterm2 => Iterm2::frame_place(b, rect)?,
+					Self::Sixel => Sixel::frame_place(b, rect)?,
+					_ => unreachable!(),
+				}
+				sleep(*delay).await;
+			}
+		}
+	}
+
 	pub fn image_hide(self, rect: Rect) -> Result<()> {
+		IMAGE_EPOCH.fetch_add(1, Ordering::Relaxed);
 		if !IMAGE_SHOWN.swap(false, Ordering::Relaxed) {
 			return Ok(());
 		}
@@ -204,6 +250,20 @@ impl Adaptor {
 		}
 	}
 
+	// Deletes all Kitty placements even if no image is known to be shown, to drop
+	// one that raced `image_hide()`. Other protocols draw into the cells, which
+	// the next render overwrites anyway.
+	pub fn image_erase(self) -> Result<()> {
+		IMAGE_EPOCH.fetch_add(1, Ordering::Relaxed);
+		match self {
+			Self::Kitty | Self::KittyOld => {
+				IMAGE_SHOWN.store(false, Ordering::Relaxed);
+				Kitty::image_erase()
+			}
+			_ => Ok(()),
+		}
+	}
+
 	#[inline]
 	pub(super) fn needs_ueberzug(self) -> bool {
 		!matches!(self, Self::Kitty | Self::KittyOld | Self::Iterm2 | Self::Sixel)
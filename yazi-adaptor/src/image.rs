@@ -1,9 +1,8 @@
-use std::{fs::File, io::BufReader, path::{Path, PathBuf}};
+use std::{fs::File, io::{BufReader, ErrorKind, Read}, path::{Path, PathBuf}, time::Duration};
 
 use anyhow::Result;
-use image::{imageops::FilterType, io::Limits, DynamicImage, ImageFormat};
-use yazi_config::{PREVIEW, TASKS};
-use yazi_shared::term::Term;
+use image::{codecs::{gif::GifDecoder, png::PngDecoder}, imageops::FilterType, io::Limits, AnimationDecoder, DynamicImage, Frames, ImageError, ImageFormat};
+use yazi_config::{preview::ImageFilter, PREVIEW, TASKS};
 
 pub struct Image;
 
@@ -23,41 +22,127 @@ impl Image {
 		r
 	}
 
-	pub(super) async fn downscale(path: &Path, size: (u16, u16)) -> Result<DynamicImage> {
-		let (w, h) = Term::ratio()
+	fn filter() -> FilterType {
+		match PREVIEW.image_filter {
+			ImageFilter::Nearest => FilterType::Nearest,
+			ImageFilter::Triangle => FilterType::Triangle,
+			ImageFilter::CatmullRom => FilterType::CatmullRom,
+			ImageFilter::Gaussian => FilterType::Gaussian,
+			ImageFilter::Lanczos3 => FilterType::Lanczos3,
+		}
+	}
+
+	fn max_size(size: (u16, u16)) -> (u32, u32) {
+		PREVIEW
+			.cell_ratio()
 			.map(|(w, h)| {
 				let (w, h) = ((size.0 as f64 * w) as u32, (size.1 as f64 * h) as u32);
 				(w.min(PREVIEW.max_width), h.min(PREVIEW.max_height))
 			})
-			.unwrap_or((PREVIEW.max_width, PREVIEW.max_height));
+			.unwrap_or((PREVIEW.max_width, PREVIEW.max_height))
+	}
+
+	// Whether `e` is from an image that's corrupt, cut off, or in a format that
+	// isn't supported, rather than from being unable to read or show it
+	pub fn is_decode_error(e: &anyhow::Error) -> bool {
+		let io =
+			|e: &std::io::Error| matches!(e.kind(), ErrorKind::UnexpectedEof | ErrorKind::InvalidData);
+		e.chain().any(|e| match e.downcast_ref::<ImageError>() {
+			Some(ImageError::Decoding(_) | ImageError::Unsupported(_)) => true,
+			Some(ImageError::IoError(e)) => io(e),
+			Some(_) => false,
+			None => e.downcast_ref::<std::io::Error>().is_some_and(io),
+		})
+	}
+
+	pub(super) async fn downscale(path: &Path, size: (u16, u16)) -> Result<DynamicImage> {
+		let (w, h) = Self::max_size(size);
 
 		let path = path.to_owned();
 		let img = tokio::task::spawn_blocking(move || {
-			Self::set_limits(image::io::Reader::open(path)?.with_guessed_format()?).decode()
+			let img =
+				Self::set_limits(image::io::Reader::open(&path)?.with_guessed_format()?).decode()?;
+			Ok::<_, anyhow::Error>(Self::orient(img, Self::orientation(&path)))
 		})
 		.await??;
 
 		tokio::task::spawn_blocking(move || {
-			Ok(if img.width() > w || img.height() > h {
-				img.resize(w, h, FilterType::Triangle)
-			} else {
-				img
-			})
+			Ok(if img.width() > w || img.height() > h { img.resize(w, h, Self::filter()) } else { img })
+		})
+		.await?
+	}
+
+	// Decode the frames of an animated GIF or APNG one at a time, downscaled to
+	// fit `size` and turned into what's sent to the terminal by `encode`, up to
+	// `image_alloc` bytes of them; an empty list is returned for still images.
+	pub(super) async fn frames(
+		path: &Path,
+		size: (u16, u16),
+		encode: fn(DynamicImage) -> Result<Vec<u8>>,
+	) -> Result<Vec<(Vec<u8>, Duration)>> {
+		const MAX_FRAMES: usize = 500;
+
+		let (w, h) = Self::max_size(size);
+		let path = path.to_owned();
+		tokio::task::spawn_blocking(move || {
+			let reader = image::io::Reader::open(&path)?.with_guessed_format()?;
+			let frames: Frames = match reader.format() {
+				Some(ImageFormat::Gif) => {
+					GifDecoder::new(BufReader::new(File::open(&path)?))?.into_frames()
+				}
+				Some(ImageFormat::Png) => {
+					let decoder = PngDecoder::new(BufReader::new(File::open(&path)?))?;
+					if !decoder.is_apng() {
+						return Ok(vec![]);
+					}
+					decoder.apng().into_frames()
+				}
+				_ => return Ok(vec![]),
+			};
+
+			let (mut result, mut total) = (vec![], 0);
+			for frame in frames.take(MAX_FRAMES) {
+				let frame = frame?;
+				let (n, d) = frame.delay().numer_denom_ms();
+
+				// Like browsers, treat the near-zero delays of old GIFs as 100ms
+				let ms = if d == 0 || n / d <= 10 { 100 } else { n / d };
+
+				let mut img = DynamicImage::ImageRgba8(frame.into_buffer());
+				if img.width() > w || img.height() > h {
+					img = img.resize(w, h, Self::filter());
+				}
+
+				let b = encode(img)?;
+				total += b.len();
+				if TASKS.image_alloc > 0 && total > TASKS.image_alloc as usize {
+					break;
+				}
+				result.push((b, Duration::from_millis(ms as u64)));
+			}
+			Ok(if result.len() > 1 { result } else { vec![] })
 		})
 		.await?
 	}
 
 	pub async fn precache(path: &Path, cache: PathBuf) -> Result<()> {
+		let len = tokio::fs::metadata(path).await?.len();
+		if PREVIEW.max_image > 0 && len > PREVIEW.max_image {
+			anyhow::bail!("{path:?} is too large to precache");
+		}
+
 		let path = path.to_owned();
 		let mut img = tokio::task::spawn_blocking(move || {
-			Self::set_limits(image::io::Reader::open(path)?.with_guessed_format()?).decode()
+			let img =
+				Self::set_limits(image::io::Reader::open(&path)?.with_guessed_format()?).decode()?;
+			Ok::<_, anyhow::Error>(Self::orient(img, Self::orientation(&path)))
 		})
 		.await??;
 
 		tokio::task::spawn_blocking(move || {
 			let (w, h) = (PREVIEW.max_width, PREVIEW.max_height);
 			if img.width() > w || img.height() > h {
-				img = img.resize(w, h, FilterType::Triangle);
+				img = img.resize(w, h, Self::filter());
 			}
 
 			Ok(match img {
@@ -75,7 +160,7 @@ impl Image {
 		tokio::task::spawn_blocking(move || {
 			let (w, h) = (PREVIEW.max_width, PREVIEW.max_height);
 			if img.width() > w || img.height() > h {
-				img = img.resize(w, h, FilterType::Triangle);
+				img = img.resize(w, h, Self::filter());
 			}
 
 			Ok(match img {
@@ -86,4 +171,97 @@ impl Image {
 		})
 		.await?
 	}
+
+	// Read the EXIF orientation tag of a JPEG, 1 (upright) if it's missing
+	fn orientation(path: &Path) -> u16 {
+		let mut buf = Vec::with_capacity(64 << 10);
+		if File::open(path).and_then(|f| f.take(64 << 10).read_to_end(&mut buf)).is_err() {
+			return 1;
+		}
+		Self::exif_orientation(&buf).unwrap_or(1)
+	}
+
+	fn exif_orientation(b: &[u8]) -> Option<u16> {
+		if !b.starts_with(b"\xff\xd8") {
+			return None;
+		}
+
+		// Find the APP1 segment holding the EXIF data
+		let mut i = 2;
+		let tiff = loop {
+			let (marker, len) =
+				(*b.get(i + 1)?, u16::from_be_bytes([*b.get(i + 2)?, *b.get(i + 3)?]) as usize);
+			if *b.get(i)? != 0xff || marker == 0xda {
+				return None;
+			}
+			if marker == 0xe1 && b.get(i + 4..i + 10)? == b"Exif\0\0" {
+				break b.get(i + 10..i + 2 + len)?;
+			}
+			i += 2 + len;
+		};
+
+		let le = match tiff.get(..2)? {
+			b"II" => true,
+			b"MM" => false,
+			_ => return None,
+		};
+		let u16_at = |o: usize| {
+			let b = [*tiff.get(o)?, *tiff.get(o + 1)?];
+			Some(if le { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+		};
+		let u32_at = |o: usize| {
+			let b = [*tiff.get(o)?, *tiff.get(o + 1)?, *tiff.get(o + 2)?, *tiff.get(o + 3)?];
+			Some(if le { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+		};
+
+		let ifd = u32_at(4)? as usize;
+		for n in 0..u16_at(ifd)? as usize {
+			let entry = ifd + 2 + n * 12;
+			if u16_at(entry)? == 0x0112 {
+				return u16_at(entry + 8).filter(|o| (1..=8).contains(o));
+			}
+		}
+		None
+	}
+
+	fn orient(img: DynamicImage, orientation: u16) -> DynamicImage {
+		match orientation {
+			2 => img.fliph(),
+			3 => img.rotate180(),
+			4 => img.flipv(),
+			5 => img.rotate90().fliph(),
+			6 => img.rotate90(),
+			7 => img.rotate270().fliph(),
+			8 => img.rotate270(),
+			_ => img,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_exif_orientation() {
+		let mut b = b"\xff\xd8\xff\xe1\0\x22Exif\0\0MM\0\x2a\0\0\0\x08\0\x01".to_vec();
+		b.extend(b"\x01\x12\0\x03\0\0\0\x01\0\x06\0\0\0\0\0\0");
+		assert_eq!(Image::exif_orientation(&b), Some(6));
+
+		assert_eq!(Image::exif_orientation(b"\xff\xd8\xff\xdb\0\x02"), None);
+		assert_eq!(Image::exif_orientation(b"\x89PNG"), None);
+	}
+
+	#[test]
+	fn test_is_decode_error() {
+		let cut = image::load_from_memory(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR").unwrap_err();
+		assert!(Image::is_decode_error(&anyhow::Error::from(cut)));
+
+		let eof = std::io::Error::from(ErrorKind::UnexpectedEof);
+		assert!(Image::is_decode_error(&anyhow::Error::from(eof)));
+
+		let missing = std::io::Error::from(ErrorKind::NotFound);
+		assert!(!Image::is_decode_error(&anyhow::Error::from(missing)));
+		assert!(!Image::is_decode_error(&anyhow::anyhow!("terminal went away")));
+	}
 }
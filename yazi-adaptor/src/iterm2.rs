@@ -2,7 +2,7 @@ use std::{io::{stdout, BufWriter, Write}, path::Path};
 
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine};
-use image::{codecs::jpeg::JpegEncoder, DynamicImage};
+use image::{codecs::{jpeg::JpegEncoder, png::PngEncoder}, DynamicImage};
 use ratatui::prelude::Rect;
 use yazi_shared::term::Term;
 
@@ -20,6 +20,10 @@ impl Iterm2 {
 		Term::move_lock(stdout().lock(), (rect.x, rect.y), |stdout| Ok(stdout.write_all(&b)?))
 	}
 
+	pub(super) fn frame_place(b: &[u8], rect: Rect) -> Result<()> {
+		Term::move_lock(stdout().lock(), (rect.x, rect.y), |stdout| Ok(stdout.write_all(b)?))
+	}
+
 	pub(super) fn image_hide(rect: Rect) -> Result<()> {
 		let stdout = BufWriter::new(stdout().lock());
 		let s = " ".repeat(rect.width as usize);
@@ -33,25 +37,31 @@ impl Iterm2 {
 	}
 
 	async fn encode(img: DynamicImage) -> Result<Vec<u8>> {
-		tokio::task::spawn_blocking(move || {
-			let size = (img.width(), img.height());
-
-			let mut jpg = vec![];
-			JpegEncoder::new_with_quality(&mut jpg, 75).encode_image(&img)?;
-
-			let mut buf = vec![];
-			write!(
-				buf,
-				"{}]1337;File=inline=1;size={};width={}px;height={}px;doNotMoveCursor=1:{}\x07{}",
-				START,
-				jpg.len(),
-				size.0,
-				size.1,
-				general_purpose::STANDARD.encode(&jpg),
-				CLOSE
-			)?;
-			Ok(buf)
-		})
-		.await?
+		tokio::task::spawn_blocking(move || Self::encode_sync(img)).await?
+	}
+
+	pub(super) fn encode_sync(img: DynamicImage) -> Result<Vec<u8>> {
+		let size = (img.width(), img.height());
+
+		// JPEG has no alpha channel, so keep transparent images as PNG
+		let mut data = vec![];
+		if img.color().has_alpha() {
+			img.write_with_encoder(PngEncoder::new(&mut data))?;
+		} else {
+			JpegEncoder::new_with_quality(&mut data, 75).encode_image(&img)?;
+		}
+
+		let mut buf = vec![];
+		write!(
+			buf,
+			"{}]1337;File=inline=1;size={};width={}px;height={}px;doNotMoveCursor=1:{}\x07{}",
+			START,
+			data.len(),
+			size.0,
+			size.1,
+			general_purpose::STANDARD.encode(&data),
+			CLOSE
+		)?;
+		Ok(buf)
 	}
 }
@@ -1,4 +1,4 @@
-use std::{io::{stdout, BufWriter, Write}, path::Path};
+use std::{io::{stdout, BufWriter, Write}, path::{Path, PathBuf}, sync::atomic::{AtomicUsize, Ordering}, time::Duration};
 
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine};
@@ -7,7 +7,13 @@ use ratatui::prelude::Rect;
 use yazi_shared::term::Term;
 
 use super::image::Image;
-use crate::{CLOSE, ESCAPE, START};
+use crate::{CLOSE, ESCAPE, IMAGE_EPOCH, SSH, START};
+
+// Pixel data larger than this is written to a temporary file for the terminal
+// to read, rather than inlined as base64
+const FILE_THRESHOLD: usize = 1 << 20;
+
+static FILE_SEQ: AtomicUsize = AtomicUsize::new(0);
 
 static DIACRITICS: [char; 297] = [
 	'\u{0305}',
@@ -312,13 +318,29 @@ static DIACRITICS: [char; 297] = [
 pub(super) struct Kitty;
 
 impl Kitty {
-	pub(super) async fn image_show(path: &Path, rect: Rect) -> Result<()> {
+	pub(super) async fn image_show(path: &Path, rect: Rect, epoch: usize) -> Result<()> {
 		let img = Image::downscale(path, (rect.width, rect.height)).await?;
-		let b = Self::encode(img).await?;
+		let (b, tmp) = Self::encode(img).await?;
+
+		let result = Self::image_hide(rect).and_then(|_| Self::place(&b, rect, epoch));
+		Self::reap(tmp);
+		result
+	}
 
-		Self::image_hide(rect)?;
+	// Frames are sent under the same image id as the still image, each replacing
+	// the one before instead of piling up in the terminal
+	pub(super) fn frame_place(b: &[u8], rect: Rect, epoch: usize) -> Result<()> {
+		Self::place(b, rect, epoch)
+	}
+
+	// The epoch is checked while holding stdout, which hiding also takes, so an
+	// image is either placed before it's hidden or not at all
+	fn place(b: &[u8], rect: Rect, epoch: usize) -> Result<()> {
 		Term::move_lock(stdout().lock(), (rect.x, rect.y), |stdout| {
-			stdout.write_all(&b)?;
+			if IMAGE_EPOCH.load(Ordering::Relaxed) != epoch {
+				return Ok(());
+			}
+			stdout.write_all(b)?;
 
 			let mut buf = String::with_capacity(rect.width as usize * 3 + 20);
 			for y in 0..rect.height {
@@ -353,8 +375,36 @@ impl Kitty {
 		})
 	}
 
-	async fn encode(img: DynamicImage) -> Result<Vec<u8>> {
-		fn output(raw: &[u8], format: u8, size: (u32, u32)) -> Result<Vec<u8>> {
+	pub(super) fn image_erase() -> Result<()> {
+		let mut stdout = stdout().lock();
+		stdout.write_all(format!("{}_Gq=1,a=d,d=A{}\\{}", START, ESCAPE, CLOSE).as_bytes())?;
+		stdout.flush()?;
+		Ok(())
+	}
+
+	async fn encode(img: DynamicImage) -> Result<(Vec<u8>, Option<PathBuf>)> {
+		tokio::task::spawn_blocking(move || Self::encode_sync(img, !*SSH)).await?
+	}
+
+	// Frames are placed over and over, so they're always inlined, as the
+	// terminal deletes a file once it's read
+	pub(super) fn frame_encode(img: DynamicImage) -> Result<Vec<u8>> {
+		Ok(Self::encode_sync(img, false)?.0)
+	}
+
+	fn encode_sync(img: DynamicImage, file: bool) -> Result<(Vec<u8>, Option<PathBuf>)> {
+		fn output(
+			raw: &[u8],
+			format: u8,
+			size: (u32, u32),
+			file: bool,
+		) -> Result<(Vec<u8>, Option<PathBuf>)> {
+			if file && raw.len() > FILE_THRESHOLD {
+				if let Some(tmp) = Kitty::tmp_write(raw) {
+					return Ok((Kitty::direct(&tmp, format, size), Some(tmp)));
+				}
+			}
+
 			let b64 = general_purpose::STANDARD.encode(raw).chars().collect::<Vec<_>>();
 
 			let mut it = b64.chunks(4096).peekable();
@@ -387,15 +437,63 @@ impl Kitty {
 			}
 
 			buf.write_all(CLOSE.as_bytes())?;
-			Ok(buf)
+			Ok((buf, None))
 		}
 
 		let size = (img.width(), img.height());
-		tokio::task::spawn_blocking(move || match img {
-			DynamicImage::ImageRgb8(v) => output(v.as_raw(), 24, size),
-			DynamicImage::ImageRgba8(v) => output(v.as_raw(), 32, size),
-			v => output(v.to_rgb8().as_raw(), 24, size),
-		})
-		.await?
+		match img {
+			DynamicImage::ImageRgb8(v) => output(v.as_raw(), 24, size, file),
+			DynamicImage::ImageRgba8(v) => output(v.as_raw(), 32, size, file),
+			v => output(v.to_rgb8().as_raw(), 24, size, file),
+		}
+	}
+
+	// With `t=t` the terminal deletes the file once it's read, if it's in a
+	// temporary directory and has "tty-graphics-protocol" in its path
+	fn tmp_write(raw: &[u8]) -> Option<PathBuf> {
+		let dir = Path::new("/dev/shm");
+		let dir = if dir.is_dir() { dir.to_owned() } else { std::env::temp_dir() };
+
+		let seq = FILE_SEQ.fetch_add(1, Ordering::Relaxed);
+		let tmp = dir.join(format!("yazi-{}-{seq}.tty-graphics-protocol", std::process::id()));
+
+		// Never follow or reuse what's already there, and keep it from other users
+		let mut opts = std::fs::OpenOptions::new();
+		opts.write(true).create_new(true);
+		#[cfg(unix)]
+		std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+
+		let mut file = opts.open(&tmp).ok()?;
+		if file.write_all(raw).is_err() {
+			std::fs::remove_file(&tmp).ok();
+			return None;
+		}
+		Some(tmp)
+	}
+
+	fn direct(tmp: &Path, format: u8, size: (u32, u32)) -> Vec<u8> {
+		format!(
+			"{}_Gq=1,a=T,i=1,C=1,U=1,f={},s={},v={},t=t;{}{}\\{}{}",
+			*START,
+			format,
+			size.0,
+			size.1,
+			general_purpose::STANDARD.encode(tmp.as_os_str().as_encoded_bytes()),
+			*ESCAPE,
+			*CLOSE,
+			*CLOSE
+		)
+		.into_bytes()
+	}
+
+	// In case the terminal didn't get to read it, e.g. the image was hidden
+	// before being placed
+	fn reap(tmp: Option<PathBuf>) {
+		if let Some(tmp) = tmp {
+			tokio::spawn(async move {
+				tokio::time::sleep(Duration::from_secs(5)).await;
+				tokio::fs::remove_file(tmp).await.ok();
+			});
+		}
 	}
 }
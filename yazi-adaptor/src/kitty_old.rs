@@ -1,4 +1,4 @@
-use std::{io::{stdout, Write}, path::Path};
+use std::{io::{stdout, Write}, path::Path, sync::atomic::Ordering};
 
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine};
@@ -7,17 +7,22 @@ use ratatui::prelude::Rect;
 use yazi_shared::term::Term;
 
 use super::image::Image;
-use crate::{CLOSE, ESCAPE, START};
+use crate::{CLOSE, ESCAPE, IMAGE_EPOCH, START};
 
 pub(super) struct KittyOld;
 
 impl KittyOld {
-	pub(super) async fn image_show(path: &Path, rect: Rect) -> Result<()> {
+	pub(super) async fn image_show(path: &Path, rect: Rect, epoch: usize) -> Result<()> {
 		let img = Image::downscale(path, (rect.width, rect.height)).await?;
 		let b = Self::encode(img).await?;
 
 		Self::image_hide()?;
-		Term::move_lock(stdout().lock(), (rect.x, rect.y), |stdout| Ok(stdout.write_all(&b)?))
+		Term::move_lock(stdout().lock(), (rect.x, rect.y), |stdout| {
+			if IMAGE_EPOCH.load(Ordering::Relaxed) != epoch {
+				return Ok(());
+			}
+			Ok(stdout.write_all(&b)?)
+		})
 	}
 
 	#[inline]
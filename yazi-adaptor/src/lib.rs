@@ -19,6 +19,9 @@ pub use crate::image::*;
 
 pub static ADAPTOR: RoCell<Adaptor> = RoCell::new();
 
+// Images can only be passed by path to a terminal on the same machine
+static SSH: RoCell<bool> = RoCell::new();
+
 // Tmux support
 static TMUX: RoCell<bool> = RoCell::new();
 static ESCAPE: RoCell<&'static str> = RoCell::new();
@@ -30,6 +33,7 @@ pub fn init() {
 	START.init(if *TMUX { "\x1bPtmux;\x1b\x1b" } else { "\x1b" });
 	CLOSE.init(if *TMUX { "\x1b\\" } else { "" });
 	ESCAPE.init(if *TMUX { "\x1b\x1b" } else { "\x1b" });
+	SSH.init(env_exists("SSH_CONNECTION") || env_exists("SSH_CLIENT") || env_exists("SSH_TTY"));
 
 	ADAPTOR.init(Adaptor::detect());
 	ADAPTOR.start();
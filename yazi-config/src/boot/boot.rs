@@ -1,7 +1,7 @@
 use std::{ffi::OsString, fs, path::PathBuf, process};
 
 use clap::Parser;
-use yazi_shared::fs::{current_cwd, expand_path};
+use yazi_shared::{fs::{current_cwd, expand_path}, term::Term};
 
 use super::cli::Args;
 use crate::{Xdg, PREVIEW};
@@ -11,7 +11,8 @@ pub struct Boot {
 	pub cwd:  PathBuf,
 	pub file: Option<OsString>,
 
-	pub state_dir: PathBuf,
+	pub config_dir: PathBuf,
+	pub state_dir:  PathBuf,
 
 	pub cwd_file:     Option<PathBuf>,
 	pub chooser_file: Option<PathBuf>,
@@ -46,11 +47,23 @@ impl Default for Boot {
 			process::exit(0);
 		}
 
+		if args.cell_ratio {
+			match Term::ratio() {
+				Some((w, h)) => println!("Detected cell size: {w:.2}x{h:.2} pixels"),
+				None => println!("The terminal doesn't report its size in pixels"),
+			}
+			if let Some((w, h)) = PREVIEW.cell_ratio {
+				println!("Overridden by `cell_ratio` in yazi.toml: {w:.2}x{h:.2} pixels");
+			}
+			process::exit(0);
+		}
+
 		let (cwd, file) = Self::parse_entry(args.entry);
 		let boot = Self {
 			cwd,
 			file,
 
+			config_dir: Xdg::config_dir().unwrap(),
 			state_dir: Xdg::state_dir().unwrap(),
 
 			cwd_file: args.cwd_file,
@@ -19,6 +19,10 @@ pub(super) struct Args {
 	/// Clear the cache directory
 	#[arg(long, action)]
 	pub clear_cache: bool,
+	/// Print the pixel size of a cell the terminal reports, to calibrate
+	/// `cell_ratio`
+	#[arg(long, action)]
+	pub cell_ratio:  bool,
 
 	/// Print version
 	#[arg(short = 'V', long)]
@@ -0,0 +1,88 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+pub trait NameTransform {
+	fn transform<'a>(&self, name: &'a str) -> Cow<'a, str>;
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameDecoder {
+	PercentDecode,
+	TrimPrefix(String),
+	TrimSuffix(String),
+}
+
+impl NameTransform for NameDecoder {
+	fn transform<'a>(&self, name: &'a str) -> Cow<'a, str> {
+		match self {
+			Self::PercentDecode => percent_decode(name),
+			Self::TrimPrefix(p) => match name.strip_prefix(p.as_str()) {
+				Some(s) if !s.is_empty() => Cow::Borrowed(s),
+				_ => Cow::Borrowed(name),
+			},
+			Self::TrimSuffix(p) => match name.strip_suffix(p.as_str()) {
+				Some(s) if !s.is_empty() => Cow::Borrowed(s),
+				_ => Cow::Borrowed(name),
+			},
+		}
+	}
+}
+
+impl NameTransform for [NameDecoder] {
+	fn transform<'a>(&self, name: &'a str) -> Cow<'a, str> {
+		self.iter().fold(Cow::Borrowed(name), |acc, d| match acc {
+			Cow::Borrowed(s) => d.transform(s),
+			Cow::Owned(s) => Cow::Owned(d.transform(&s).into_owned()),
+		})
+	}
+}
+
+fn percent_decode(s: &str) -> Cow<str> {
+	if !s.contains('%') {
+		return Cow::Borrowed(s);
+	}
+
+	#[inline]
+	fn hex(b: u8) -> Option<u8> { (b as char).to_digit(16).map(|d| d as u8) }
+
+	let b = s.as_bytes();
+	let mut out = Vec::with_capacity(b.len());
+	let mut i = 0;
+	while i < b.len() {
+		if b[i] == b'%' && i + 2 < b.len() {
+			if let (Some(h), Some(l)) = (hex(b[i + 1]), hex(b[i + 2])) {
+				out.push(h << 4 | l);
+				i += 3;
+				continue;
+			}
+		}
+		out.push(b[i]);
+		i += 1;
+	}
+
+	// Keep the original name if the decoded bytes aren't valid UTF-8
+	String::from_utf8(out).map(Cow::Owned).unwrap_or(Cow::Borrowed(s))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_percent_decode() {
+		assert_eq!(percent_decode("a%20b.txt"), "a b.txt");
+		assert_eq!(percent_decode("%E4%BD%A0%E5%A5%BD"), "你好");
+		assert_eq!(percent_decode("100%"), "100%");
+		assert_eq!(percent_decode("%zz%2"), "%zz%2");
+		assert_eq!(percent_decode("%FF"), "%FF");
+	}
+
+	#[test]
+	fn test_chain() {
+		let decoders = [NameDecoder::TrimPrefix("[dl] ".to_owned()), NameDecoder::PercentDecode];
+		assert_eq!(decoders.transform("[dl] a%2Bb"), "a+b");
+		assert_eq!(decoders.transform("[dl] "), "[dl] ");
+	}
+}
@@ -54,12 +54,23 @@ impl ManagerLayout {
 	#[inline]
 	pub fn preview_height(&self) -> usize { self.preview_rect().height as usize }
 
+	// Rows for the files of a folder preview, the last one is the summary
+	#[inline]
+	pub fn preview_folder_height(&self) -> usize {
+		let h = self.preview_height();
+		if h < 2 {
+			h
+		} else {
+			h - 1
+		}
+	}
+
 	pub fn image_rect(&self) -> Rect {
 		let mut rect = self.preview_rect();
 		if PREVIEW.max_width == 0 || PREVIEW.max_height == 0 {
 			return rect;
 		}
-		if let Some((w, h)) = Term::ratio() {
+		if let Some((w, h)) = PREVIEW.cell_ratio() {
 			rect.width = rect.width.min((PREVIEW.max_width as f64 / w).ceil() as u16);
 			rect.height = rect.height.min((PREVIEW.max_height as f64 / h).ceil() as u16);
 		}
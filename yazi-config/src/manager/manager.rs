@@ -1,7 +1,9 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use super::{ManagerLayout, SortBy};
+use super::{ManagerLayout, NameDecoder, NameTransform, SortBy};
 use crate::{validation::check_validation, MERGED_YAZI};
 
 #[derive(Debug, Deserialize, Serialize, Validate)]
@@ -19,6 +21,12 @@ pub struct Manager {
 	pub linemode:     String,
 	pub show_hidden:  bool,
 	pub show_symlink: bool,
+	pub hide_ignored: bool,
+
+	// Directory sizes
+	pub size_allocated: bool,
+
+	pub name_transforms: Vec<NameDecoder>,
 }
 
 impl Default for Manager {
@@ -34,3 +42,10 @@ impl Default for Manager {
 		manager
 	}
 }
+
+impl Manager {
+	#[inline]
+	pub fn display_name<'a>(&self, name: &'a str) -> Cow<'a, str> {
+		self.name_transforms.transform(name)
+	}
+}
@@ -1,7 +1,9 @@
+mod display;
 mod layout;
 mod manager;
 mod sorting;
 
+pub use display::*;
 pub use layout::*;
 pub use manager::*;
 pub use sorting::*;
@@ -24,21 +24,21 @@ impl Open {
 		M: AsRef<str>,
 	{
 		let is_folder = Some(mime.as_ref() == MIME_DIR);
-		self.rules.iter().find_map(|rule| {
-			if rule.mime.as_ref().is_some_and(|m| m.matches(&mime))
-				|| rule.name.as_ref().is_some_and(|n| n.match_path(&path, is_folder))
-			{
-				let openers = rule
-					.use_
-					.iter()
-					.filter_map(|use_| self.openers.get(use_))
-					.flatten()
-					.collect::<IndexSet<_>>();
 
-				if openers.is_empty() { None } else { Some(openers) }
-			} else {
-				None
-			}
+		// Filename rules win over mime rules, first match wins within each
+		let by_name =
+			self.rules.iter().filter(|r| r.name.as_ref().is_some_and(|n| n.match_path(&path, is_folder)));
+		let by_mime = self.rules.iter().filter(|r| r.mime.as_ref().is_some_and(|m| m.matches(&mime)));
+
+		by_name.chain(by_mime).find_map(|rule| {
+			let openers = rule
+				.use_
+				.iter()
+				.filter_map(|use_| self.openers.get(use_))
+				.flatten()
+				.collect::<IndexSet<_>>();
+
+			if openers.is_empty() { None } else { Some(openers) }
 		})
 	}
 
@@ -82,3 +82,32 @@ impl<'de> Deserialize<'de> for Open {
 		Ok(Self { rules: outer.open.rules, openers })
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_name_before_mime() {
+		let open: Open = toml::from_str(
+			r#"
+			[opener]
+			edit = [{ exec = "edit" }]
+			make = [{ exec = "make" }]
+			[open]
+			rules = [
+				{ mime = "text/*", use = "edit" },
+				{ name = "Makefile", use = "make" },
+				{ name = "*.lock", use = "missing" },
+			]
+			"#,
+		)
+		.unwrap();
+
+		let first = |p: &str, m: &str| open.openers(p, m).map(|o| o[0].exec.clone());
+		assert_eq!(first("/a/Makefile", "text/x-makefile").as_deref(), Some("make"));
+		assert_eq!(first("/a/main.c", "text/x-c").as_deref(), Some("edit"));
+		assert_eq!(first("/a/Cargo.lock", "text/plain").as_deref(), Some("edit"));
+		assert_eq!(first("/a/image.png", "image/png"), None);
+	}
+}
@@ -1,13 +1,19 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Deserializer};
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Opener {
-	pub exec:   String,
-	pub block:  bool,
-	pub orphan: bool,
-	pub desc:   String,
-	pub for_:   Option<String>,
-	pub spread: bool,
+	pub exec:       String,
+	pub block:      bool,
+	pub orphan:     bool,
+	pub desc:       String,
+	pub for_:       Option<String>,
+	pub spread:     bool,
+	// At most this many files are spread over one process, the rest go to more
+	pub max_spread: Option<usize>,
+	pub env:        BTreeMap<String, String>,
+	pub cwd:        Option<String>,
 }
 
 impl Opener {
@@ -32,14 +38,18 @@ impl<'de> Deserialize<'de> for Opener {
 	{
 		#[derive(Deserialize)]
 		pub struct Shadow {
-			exec:   String,
+			exec:       String,
 			#[serde(default)]
-			block:  bool,
+			block:      bool,
 			#[serde(default)]
-			orphan: bool,
-			desc:   Option<String>,
+			orphan:     bool,
+			desc:       Option<String>,
 			#[serde(rename = "for")]
-			for_:   Option<String>,
+			for_:       Option<String>,
+			#[serde(default)]
+			env:        BTreeMap<String, String>,
+			cwd:        Option<String>,
+			max_spread: Option<usize>,
 
 			// TODO: remove this when v0.1.6 is released --
 			display_name: Option<String>,
@@ -50,6 +60,9 @@ impl<'de> Deserialize<'de> for Opener {
 		if shadow.exec.is_empty() {
 			return Err(serde::de::Error::custom("`exec` cannot be empty"));
 		}
+		if shadow.max_spread == Some(0) {
+			return Err(serde::de::Error::custom("`max_spread` must be at least 1"));
+		}
 
 		// TODO: remove this when v0.1.6 is released --
 		if shadow.display_name.is_some() {
@@ -75,6 +88,9 @@ impl<'de> Deserialize<'de> for Opener {
 			desc,
 			for_: shadow.for_,
 			spread,
+			max_spread: shadow.max_spread,
+			env: shadow.env,
+			cwd: shadow.cwd,
 		})
 	}
 }
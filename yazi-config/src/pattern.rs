@@ -32,6 +32,10 @@ impl Pattern {
 		};
 		is_folder.map_or(true, |f| f == self.is_folder) && s.is_some_and(|s| self.matches(s))
 	}
+
+	// Matching anything, like `*` or `*/`
+	#[inline]
+	pub fn is_wildcard(&self) -> bool { matches!(self.inner.as_str(), "*" | "**") }
 }
 
 impl TryFrom<&str> for Pattern {
@@ -30,6 +30,11 @@ pub struct Input {
 	pub delete_origin: Origin,
 	pub delete_offset: Offset,
 
+	// empty trash
+	pub empty_trash_title:  String,
+	pub empty_trash_origin: Origin,
+	pub empty_trash_offset: Offset,
+
 	// find
 	pub find_title:  [String; 2],
 	pub find_origin: Origin,
@@ -54,6 +59,21 @@ pub struct Input {
 	pub quit_title:  String,
 	pub quit_origin: Origin,
 	pub quit_offset: Offset,
+
+	// escalate
+	pub escalate_title:  String,
+	pub escalate_origin: Origin,
+	pub escalate_offset: Offset,
+
+	// conflict
+	pub conflict_title:  String,
+	pub conflict_origin: Origin,
+	pub conflict_offset: Offset,
+
+	// compress
+	pub compress_title:  String,
+	pub compress_origin: Origin,
+	pub compress_offset: Offset,
 }
 
 impl Default for Input {
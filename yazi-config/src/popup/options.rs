@@ -1,6 +1,11 @@
+use std::path::PathBuf;
+
 use super::{Offset, Position};
 use crate::{INPUT, SELECT};
 
+// Returns why the value can't be submitted, if it can't
+pub type InputValidator = Box<dyn Fn(&str) -> Option<String> + Send>;
+
 #[derive(Default)]
 pub struct InputCfg {
 	pub title:      String,
@@ -9,6 +14,10 @@ pub struct InputCfg {
 	pub realtime:   bool,
 	pub completion: bool,
 	pub highlight:  bool,
+	pub history:    Option<&'static str>,
+	pub path:       bool,
+	pub validator:  Option<InputValidator>,
+	pub taken:      Option<(PathBuf, Option<String>)>,
 }
 
 #[derive(Default)]
@@ -25,6 +34,8 @@ impl InputCfg {
 			title: INPUT.cd_title.to_owned(),
 			position: Position::new(INPUT.cd_origin, INPUT.cd_offset),
 			completion: true,
+			history: Some("cd"),
+			path: true,
 			..Default::default()
 		}
 	}
@@ -34,6 +45,8 @@ impl InputCfg {
 		Self {
 			title: INPUT.create_title.to_owned(),
 			position: Position::new(INPUT.create_origin, INPUT.create_offset),
+			history: Some("create"),
+			path: true,
 			..Default::default()
 		}
 	}
@@ -43,6 +56,8 @@ impl InputCfg {
 		Self {
 			title: INPUT.rename_title.to_owned(),
 			position: Position::new(INPUT.rename_origin, INPUT.rename_offset),
+			history: Some("rename"),
+			path: true,
 			..Default::default()
 		}
 	}
@@ -58,8 +73,8 @@ impl InputCfg {
 	}
 
 	#[inline]
-	pub fn delete(n: usize) -> Self {
-		let title = INPUT.delete_title.replace("{n}", &n.to_string());
+	pub fn delete(n: usize, size: &str) -> Self {
+		let title = INPUT.delete_title.replace("{n}", &n.to_string()).replace("{size}", size);
 		Self {
 			title: title.replace("{s}", if n > 1 { "s" } else { "" }),
 			position: Position::new(INPUT.delete_origin, INPUT.delete_offset),
@@ -67,12 +82,22 @@ impl InputCfg {
 		}
 	}
 
+	#[inline]
+	pub fn empty_trash() -> Self {
+		Self {
+			title: INPUT.empty_trash_title.to_owned(),
+			position: Position::new(INPUT.empty_trash_origin, INPUT.empty_trash_offset),
+			..Default::default()
+		}
+	}
+
 	#[inline]
 	pub fn find(prev: bool) -> Self {
 		Self {
 			title: INPUT.find_title[prev as usize].to_owned(),
 			position: Position::new(INPUT.find_origin, INPUT.find_offset),
 			realtime: true,
+			history: Some("find"),
 			..Default::default()
 		}
 	}
@@ -82,6 +107,7 @@ impl InputCfg {
 		Self {
 			title: INPUT.search_title.to_owned(),
 			position: Position::new(INPUT.search_origin, INPUT.search_offset),
+			history: Some("search"),
 			..Default::default()
 		}
 	}
@@ -92,6 +118,8 @@ impl InputCfg {
 			title: INPUT.shell_title[block as usize].to_owned(),
 			position: Position::new(INPUT.shell_origin, INPUT.shell_offset),
 			highlight: true,
+			history: Some("shell"),
+			path: true,
 			..Default::default()
 		}
 	}
@@ -115,11 +143,55 @@ impl InputCfg {
 		}
 	}
 
+	#[inline]
+	pub fn escalate(n: usize, cmd: &str) -> Self {
+		let title = INPUT.escalate_title.replace("{n}", &n.to_string()).replace("{cmd}", cmd);
+		Self {
+			title: title.replace("{s}", if n > 1 { "s" } else { "" }),
+			position: Position::new(INPUT.escalate_origin, INPUT.escalate_offset),
+			..Default::default()
+		}
+	}
+
+	#[inline]
+	pub fn conflict(name: &str) -> Self {
+		Self {
+			title: INPUT.conflict_title.replace("{name}", name),
+			position: Position::new(INPUT.conflict_origin, INPUT.conflict_offset),
+			..Default::default()
+		}
+	}
+
+	#[inline]
+	pub fn compress() -> Self {
+		Self {
+			title: INPUT.compress_title.to_owned(),
+			position: Position::new(INPUT.compress_origin, INPUT.compress_offset),
+			history: Some("compress"),
+			path: true,
+			..Default::default()
+		}
+	}
+
 	#[inline]
 	pub fn with_value(mut self, value: impl Into<String>) -> Self {
 		self.value = value.into();
 		self
 	}
+
+	#[inline]
+	pub fn with_validator(mut self, f: impl Fn(&str) -> Option<String> + Send + 'static) -> Self {
+		self.validator = Some(Box::new(f));
+		self
+	}
+
+	// Refuses names of existing entries in `dir`, other than `except`, which
+	// are looked up in the background rather than by the validator
+	#[inline]
+	pub fn with_taken(mut self, dir: PathBuf, except: Option<String>) -> Self {
+		self.taken = Some((dir, except));
+		self
+	}
 }
 
 impl SelectCfg {
@@ -140,4 +212,17 @@ impl SelectCfg {
 			}),
 		}
 	}
+
+	#[inline]
+	pub fn review(title: String, items: Vec<String>) -> Self {
+		let max_height = Self::max_height(items.len());
+		Self {
+			title,
+			items,
+			position: Position::new(SELECT.open_origin, Offset {
+				height: max_height,
+				..SELECT.open_offset
+			}),
+		}
+	}
 }
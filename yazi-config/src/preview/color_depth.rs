@@ -0,0 +1,53 @@
+use std::{env, str::FromStr};
+
+use anyhow::bail;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub enum ColorDepth {
+	Truecolor,
+	Palette256,
+	Palette16,
+}
+
+impl ColorDepth {
+	// Guessed from `$COLORTERM` and `$TERM`, full color unless they say otherwise
+	pub fn detect() -> Self {
+		if env::var("COLORTERM").is_ok_and(|s| s == "truecolor" || s == "24bit") {
+			return Self::Truecolor;
+		}
+
+		let term = env::var("TERM").unwrap_or_default();
+		if term.contains("256color") {
+			Self::Palette256
+		} else if matches!(term.as_str(), "linux" | "screen" | "ansi" | "dumb" | "xterm-color")
+			|| term.starts_with("vt")
+			|| term.ends_with("-16color")
+		{
+			Self::Palette16
+		} else {
+			Self::Truecolor
+		}
+	}
+}
+
+impl FromStr for ColorDepth {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"auto" => Self::detect(),
+			"truecolor" => Self::Truecolor,
+			"256" => Self::Palette256,
+			"16" => Self::Palette16,
+			_ => bail!("invalid color_depth value: {s}"),
+		})
+	}
+}
+
+impl TryFrom<String> for ColorDepth {
+	type Error = anyhow::Error;
+
+	fn try_from(s: String) -> Result<Self, Self::Error> { Self::from_str(&s) }
+}
@@ -0,0 +1,36 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub enum ImageFilter {
+	Nearest,
+	#[default]
+	Triangle,
+	CatmullRom,
+	Gaussian,
+	Lanczos3,
+}
+
+impl FromStr for ImageFilter {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"nearest" => Self::Nearest,
+			"triangle" => Self::Triangle,
+			"catmull-rom" => Self::CatmullRom,
+			"gaussian" => Self::Gaussian,
+			"lanczos3" => Self::Lanczos3,
+			_ => bail!("invalid image_filter value: {s}"),
+		})
+	}
+}
+
+impl TryFrom<String> for ImageFilter {
+	type Error = anyhow::Error;
+
+	fn try_from(s: String) -> Result<Self, Self::Error> { Self::from_str(&s) }
+}
@@ -1,3 +1,9 @@
+mod color_depth;
+mod filter;
 mod preview;
+mod previewer;
 
+pub use color_depth::*;
+pub use filter::*;
 pub use preview::*;
+pub use previewer::*;
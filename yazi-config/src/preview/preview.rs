@@ -2,20 +2,35 @@ use std::{path::{Path, PathBuf}, time::{self, SystemTime}};
 
 use md5::{Digest, Md5};
 use serde::Deserialize;
-use yazi_shared::fs::expand_path;
+use yazi_shared::{fs::expand_path, term::Term, MIME_DIR};
 
+use super::{ColorDepth, ImageFilter, Previewer};
 use crate::{xdg::Xdg, MERGED_YAZI};
 
 #[derive(Debug)]
 pub struct Preview {
-	pub tab_size:   u8,
-	pub max_width:  u32,
-	pub max_height: u32,
+	pub tab_size:     u8,
+	pub line_numbers: bool,
+	pub wrap:         bool,
+	pub color_depth:  ColorDepth,
+	pub max_width:    u32,
+	pub max_height:   u32,
+	pub max_image:    u64,
+	pub image_filter: ImageFilter,
+	pub cell_ratio:   Option<(f64, f64)>,
+	pub animate:      bool,
+	pub show_exif:    bool,
+	pub video_info:   bool,
+	pub video_seek:   u8,
+
+	pub pdf_as_text: bool,
 
 	pub cache_dir: PathBuf,
 
 	pub ueberzug_scale:  f32,
 	pub ueberzug_offset: (f32, f32, f32, f32),
+
+	pub previewers: Vec<Previewer>,
 }
 
 impl Default for Preview {
@@ -26,14 +41,28 @@ impl Default for Preview {
 		}
 		#[derive(Deserialize)]
 		struct Shadow {
-			tab_size:   u8,
-			max_width:  u32,
-			max_height: u32,
+			tab_size:     u8,
+			line_numbers: bool,
+			wrap:         bool,
+			color_depth:  ColorDepth,
+			max_width:    u32,
+			max_height:   u32,
+			max_image:    u64,
+			image_filter: ImageFilter,
+			cell_ratio:   (f64, f64),
+			animate:      bool,
+			show_exif:    bool,
+			video_info:   bool,
+			video_seek:   u8,
+
+			pdf_as_text: bool,
 
 			cache_dir: Option<String>,
 
 			ueberzug_scale:  f32,
 			ueberzug_offset: (f32, f32, f32, f32),
+
+			previewers: Vec<Previewer>,
 		}
 
 		let preview = toml::from_str::<Outer>(&MERGED_YAZI).unwrap().preview;
@@ -43,23 +72,75 @@ impl Default for Preview {
 
 		Preview {
 			tab_size: preview.tab_size,
+			line_numbers: preview.line_numbers,
+			wrap: preview.wrap,
+			color_depth: preview.color_depth,
 			max_width: preview.max_width,
 			max_height: preview.max_height,
+			max_image: preview.max_image,
+			image_filter: preview.image_filter,
+			cell_ratio: Some(preview.cell_ratio).filter(|&(w, h)| w > 0.0 && h > 0.0),
+			animate: preview.animate,
+			show_exif: preview.show_exif,
+			video_info: preview.video_info,
+			video_seek: preview.video_seek.min(95),
+
+			pdf_as_text: preview.pdf_as_text,
 
 			cache_dir,
 
 			ueberzug_scale: preview.ueberzug_scale,
 			ueberzug_offset: preview.ueberzug_offset,
+
+			previewers: preview.previewers,
 		}
 	}
 }
 
 impl Preview {
+	// The command of the first previewer matching by name, or else by mime type
+	pub fn previewer(&self, path: &Path, mime: &str) -> Option<&str> {
+		let is_folder = Some(mime == MIME_DIR);
+		let by_name = self
+			.previewers
+			.iter()
+			.find(|p| p.name.as_ref().is_some_and(|n| n.match_path(path, is_folder)));
+		let by_mime =
+			|| self.previewers.iter().find(|p| p.mime.as_ref().is_some_and(|m| m.matches(mime)));
+		by_name.or_else(by_mime).map(|p| p.exec.as_str())
+	}
+
+	// Pixels per cell, as configured, or as reported by the terminal
+	#[inline]
+	pub fn cell_ratio(&self) -> Option<(f64, f64)> { self.cell_ratio.or_else(Term::ratio) }
+
 	#[inline]
-	pub fn cache(&self, path: &Path, skip: usize) -> PathBuf {
-		self
-			.cache_dir
-			.join(format!("{:x}", Md5::new_with_prefix(format!("{:?}///{}", path, skip)).finalize()))
+	pub fn cache(&self, path: &Path, mtime: Option<SystemTime>, skip: usize) -> PathBuf {
+		self.cache_dir.join(Self::cache_name(path, mtime, skip, ""))
+	}
+
+	#[inline]
+	pub fn cache_with(
+		&self,
+		path: &Path,
+		mtime: Option<SystemTime>,
+		skip: usize,
+		salt: &str,
+	) -> PathBuf {
+		self.cache_dir.join(Self::cache_name(path, mtime, skip, salt))
+	}
+
+	// The modification time of the source is part of the name, so that editing
+	// a file in place doesn't keep showing the stale cache. Entries of the old
+	// versions are left behind, the default `cache_dir` is in the temporary
+	// directory for the OS to clean up.
+	fn cache_name(path: &Path, mtime: Option<SystemTime>, skip: usize, salt: &str) -> String {
+		let key = if salt.is_empty() {
+			format!("{:?}///{}///{:?}", path, skip, mtime)
+		} else {
+			format!("{:?}///{}///{}///{:?}", path, skip, salt, mtime)
+		};
+		format!("{:x}", Md5::new_with_prefix(key).finalize())
 	}
 
 	#[inline]
@@ -68,3 +149,20 @@ impl Preview {
 		self.cache_dir.join(format!("{prefix}-{}", nanos / 1000))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_cache_name() {
+		let path = Path::new("/foo");
+		let (t1, t2) = (Some(SystemTime::UNIX_EPOCH), Some(SystemTime::now()));
+
+		let before = Preview::cache_name(path, t1, 0, "");
+		assert_eq!(before, Preview::cache_name(path, t1, 0, ""));
+		assert_ne!(before, Preview::cache_name(path, t1, 1, ""));
+		assert_ne!(before, Preview::cache_name(path, t1, 0, "salt"));
+		assert_ne!(before, Preview::cache_name(path, t2, 0, ""));
+	}
+}
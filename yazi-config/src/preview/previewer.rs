@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+use crate::pattern::Pattern;
+
+// A user command whose output previews the files it matches
+#[derive(Debug, Deserialize)]
+pub struct Previewer {
+	pub(super) name: Option<Pattern>,
+	pub(super) mime: Option<Pattern>,
+	pub exec:        String,
+}
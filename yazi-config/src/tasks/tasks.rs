@@ -12,10 +12,30 @@ pub struct Tasks {
 	#[validate(range(min = 1, message = "Cannot be less than 1"))]
 	pub bizarre_retry: u8,
 
+	#[validate(range(min = 1, message = "Cannot be less than 1"))]
+	pub max_transfers:     u8,
+	#[validate(range(min = 1, message = "Cannot be less than 1"))]
+	pub max_precaches:     u8,
+	#[validate(range(min = 100, message = "Cannot be less than 100"))]
+	pub progress_interval: u64,
+
+	pub paste_retry:   u8,
+	pub delete_retry:  u8,
+	pub retry_backoff: u64,
+
 	pub image_alloc: u32,
 	pub image_bound: [u16; 2],
 
+	pub precache_battery: bool,
+	#[validate(range(min = 0.0, message = "Cannot be negative"))]
+	pub precache_load:    f32,
+
 	pub suppress_preload: bool,
+	pub preserve_attrs:   bool,
+	pub notify:           bool,
+
+	pub escalate:  String,
+	pub audit_log: String,
 }
 
 impl Default for Tasks {
@@ -1,6 +1,7 @@
-use std::fmt;
+use std::{borrow::Cow, fmt, path::Path};
 
 use serde::{de::{self, Visitor}, Deserializer};
+use yazi_shared::{fs::GitStatus, MimeKind};
 
 use crate::Pattern;
 
@@ -11,6 +12,54 @@ pub struct Icon {
 }
 
 impl Icon {
+	// The icon of the first rule matching `path`, or for a file whose name says
+	// nothing, that of a typical file of its sniffed `mime` kind, and only then
+	// that of a catch-all rule like `*`. A `git` status is marked after it.
+	pub fn resolve<'a>(
+		icons: &'a [Icon],
+		path: &Path,
+		is_dir: bool,
+		mime: Option<MimeKind>,
+		git: Option<GitStatus>,
+	) -> Option<Cow<'a, str>> {
+		let find = |p: &Path, wildcard: bool| {
+			icons.iter().find(|&x| x.name.is_wildcard() == wildcard && x.name.match_path(p, Some(is_dir)))
+		};
+
+		let icon = find(path, false)
+			.or_else(|| {
+				let typical = mime.filter(|_| !is_dir).and_then(Self::typical)?;
+				find(Path::new(typical), false)
+			})
+			.or_else(|| find(path, true))?;
+		Some(match git {
+			Some(g) => Cow::Owned(format!("{}{}", icon.display, g.marker())),
+			None => Cow::Borrowed(&icon.display),
+		})
+	}
+
+	// A file name the icon rules are likely to know for `kind`
+	fn typical(kind: MimeKind) -> Option<&'static str> {
+		Some(match kind {
+			MimeKind::Archive => "a.zip",
+			MimeKind::Compressed => "a.gz",
+			MimeKind::Image => "a.png",
+			MimeKind::Svg => "a.svg",
+			MimeKind::Video => "a.mp4",
+			MimeKind::Audio => "a.mp3",
+			MimeKind::Font => "a.ttf",
+			MimeKind::JSON => "a.json",
+			MimeKind::PDF => "a.pdf",
+			MimeKind::Text => "a.txt",
+			MimeKind::Markdown => "a.md",
+			MimeKind::Notebook => "a.ipynb",
+			MimeKind::Table => "a.csv",
+			MimeKind::Document => "a.docx",
+			MimeKind::Binary => "a.bin",
+			MimeKind::Empty | MimeKind::Others => return None,
+		})
+	}
+
 	pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Icon>, D::Error>
 	where
 		D: Deserializer<'de>,
@@ -43,3 +92,50 @@ impl Icon {
 		deserializer.deserialize_map(IconVisitor)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn icons(rules: &[(&str, &str)]) -> Vec<Icon> {
+		rules
+			.iter()
+			.map(|&(k, v)| Icon { name: Pattern::try_from(k).unwrap(), display: v.to_owned() })
+			.collect()
+	}
+
+	#[test]
+	fn test_resolve() {
+		let icons = icons(&[("Makefile", "M"), ("*.png", "I"), ("*.txt", "T"), ("*/", "D")]);
+		let resolve = |p: &str, dir, mime, git| {
+			Icon::resolve(&icons, Path::new(p), dir, mime, git).map(|s| s.into_owned())
+		};
+
+		assert_eq!(resolve("a.png", false, None, None).as_deref(), Some("I"));
+		assert_eq!(resolve("a.png", false, Some(MimeKind::Text), None).as_deref(), Some("I"));
+		assert_eq!(resolve("photo", false, Some(MimeKind::Image), None).as_deref(), Some("I"));
+		assert_eq!(resolve("photo", false, None, None), None);
+		assert_eq!(resolve("Makefile", false, Some(MimeKind::Text), None).as_deref(), Some("M"));
+		assert_eq!(resolve("photos", true, Some(MimeKind::Image), None).as_deref(), Some("D"));
+		assert_eq!(resolve("blob", false, Some(MimeKind::Others), None), None);
+
+		let git = Some(GitStatus::Modified);
+		assert_eq!(resolve("a.txt", false, None, git).as_deref(), Some("TM"));
+	}
+
+	#[test]
+	fn test_resolve_catch_all() {
+		// Placed first so it'd win if it weren't kept for last
+		let icons = icons(&[("*", "F"), ("*/", "D"), ("*.png", "I"), ("photos/", "P")]);
+		let resolve = |p: &str, dir, mime| {
+			Icon::resolve(&icons, Path::new(p), dir, mime, None).map(|s| s.into_owned())
+		};
+
+		assert_eq!(resolve("a.png", false, None).as_deref(), Some("I"));
+		assert_eq!(resolve("photo", false, Some(MimeKind::Image)).as_deref(), Some("I"));
+		assert_eq!(resolve("photo", false, Some(MimeKind::Others)).as_deref(), Some("F"));
+		assert_eq!(resolve("photo", false, None).as_deref(), Some("F"));
+		assert_eq!(resolve("photos", true, None).as_deref(), Some("P"));
+		assert_eq!(resolve("docs", true, Some(MimeKind::Image)).as_deref(), Some("D"));
+	}
+}
@@ -72,6 +72,7 @@ pub struct Input {
 	pub title:    Style,
 	pub value:    Style,
 	pub selected: Style,
+	pub error:    Style,
 }
 
 #[derive(Deserialize, Serialize)]
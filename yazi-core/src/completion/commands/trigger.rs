@@ -1,7 +1,7 @@
-use std::{mem, path::{MAIN_SEPARATOR, MAIN_SEPARATOR_STR}};
+use std::{mem, path::{Path, MAIN_SEPARATOR, MAIN_SEPARATOR_STR}};
 
-use tokio::fs;
-use yazi_shared::{emit, event::Exec, Layer};
+use tokio::{fs, io};
+use yazi_shared::{emit, event::Exec, fs::expand_path, Layer};
 
 use crate::completion::Completion;
 
@@ -48,20 +48,7 @@ impl Completion {
 
 		let ticket = self.ticket;
 		tokio::spawn(async move {
-			let mut dir = fs::read_dir(&parent).await?;
-			let mut cache = Vec::new();
-			while let Ok(Some(f)) = dir.next_entry().await {
-				let Ok(meta) = f.metadata().await else {
-					continue;
-				};
-
-				cache.push(format!(
-					"{}{}",
-					f.file_name().to_string_lossy(),
-					if meta.is_dir() { MAIN_SEPARATOR_STR } else { "" },
-				));
-			}
-
+			let cache = Self::list(&expand_path(&parent)).await?;
 			if !cache.is_empty() {
 				emit!(Call(
 					Exec::call("show", cache)
@@ -79,6 +66,28 @@ impl Completion {
 		mem::replace(&mut self.visible, false)
 	}
 
+	// Names of the entries in `dir`, with a trailing separator for directories,
+	// and for symlinks to them
+	pub(crate) async fn list(dir: &Path) -> io::Result<Vec<String>> {
+		let mut it = fs::read_dir(dir).await?;
+		let mut names = Vec::new();
+		while let Ok(Some(f)) = it.next_entry().await {
+			let Ok(mut meta) = f.metadata().await else {
+				continue;
+			};
+			if meta.is_symlink() {
+				meta = fs::metadata(f.path()).await.unwrap_or(meta);
+			}
+
+			names.push(format!(
+				"{}{}",
+				f.file_name().to_string_lossy(),
+				if meta.is_dir() { MAIN_SEPARATOR_STR } else { "" },
+			));
+		}
+		Ok(names)
+	}
+
 	#[inline]
 	fn split_path(s: &str) -> (String, String) {
 		match s.rsplit_once(MAIN_SEPARATOR) {
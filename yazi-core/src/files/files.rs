@@ -3,9 +3,11 @@ use std::{collections::{BTreeMap, BTreeSet}, mem, ops::Deref, sync::atomic::Orde
 use anyhow::Result;
 use tokio::{fs, select, sync::mpsc::{self, UnboundedReceiver}};
 use yazi_config::{manager::SortBy, MANAGER};
-use yazi_shared::fs::{File, Url, FILES_TICKET};
+use yazi_shared::fs::{ChaKind, File, GitStatus, Url, FILES_TICKET};
 
-use super::FilesSorter;
+use super::{FilesFilter, FilesSorter, Ignore};
+
+const READ_DIR_BATCH: usize = 256;
 
 pub struct Files {
 	items:              Vec<File>,
@@ -13,11 +15,16 @@ pub struct Files {
 	ticket:             u64,
 	pub(crate) version: u64,
 
-	pub sizes: BTreeMap<Url, u64>,
-	selected:  BTreeSet<Url>,
+	pub sizes:         BTreeMap<Url, u64>,
+	pub git:           BTreeMap<Url, GitStatus>,
+	pub(crate) sizing: BTreeSet<Url>,
+	selected:          BTreeSet<Url>,
 
 	sorter:      FilesSorter,
 	show_hidden: bool,
+
+	filter:   Option<FilesFilter>,
+	filtered: Vec<File>,
 }
 
 impl Default for Files {
@@ -29,10 +36,15 @@ impl Default for Files {
 			version: Default::default(),
 
 			sizes:    Default::default(),
+			git:      Default::default(),
+			sizing:   Default::default(),
 			selected: Default::default(),
 
 			sorter:      Default::default(),
 			show_hidden: MANAGER.show_hidden,
+
+			filter:   Default::default(),
+			filtered: Default::default(),
 		}
 	}
 }
@@ -48,15 +60,45 @@ impl Files {
 		let mut it = fs::read_dir(url).await?;
 		let (tx, rx) = mpsc::unbounded_channel();
 
+		let ignore = if MANAGER.hide_ignored { Ignore::load(url).await } else { None };
 		tokio::spawn(async move {
-			while let Ok(Some(item)) = it.next_entry().await {
-				select! {
-					_ = tx.closed() => break,
-					result = item.metadata() => {
-						if let Ok(meta) = result {
-							tx.send(File::from_meta(Url::from(item.path()), meta).await).ok();
+			let mut done = false;
+			while !done && !tx.is_closed() {
+				let (mut files, mut urls) = (Vec::with_capacity(READ_DIR_BATCH), vec![]);
+				while files.len() + urls.len() < READ_DIR_BATCH {
+					let item = match it.next_entry().await {
+						Ok(Some(item)) => item,
+						_ => {
+							done = true;
+							break;
 						}
+					};
+
+					// The entry's metadata is enough for all but symlinks, which need their target
+					let url = Url::from(item.path());
+					match item.metadata().await.ok().filter(|m| !m.is_symlink()) {
+						Some(meta) => files.extend(File::from_meta_sync(url, meta)),
+						None => urls.push(url),
+					}
+				}
+
+				if !urls.is_empty() {
+					select! {
+						_ = tx.closed() => break,
+						linked = File::from_many(urls) => files.extend(linked),
+					}
+				}
+
+				let files = select! {
+					_ = tx.closed() => break,
+					files = File::with_births(files) => files,
+				};
+				for mut file in files {
+					// Ignored entries are treated as hidden, so they follow `show_hidden`
+					if ignore.as_ref().is_some_and(|i| i.is_ignored(&file.url, file.is_dir())) {
+						file.cha.kind |= ChaKind::HIDDEN;
 					}
+					tx.send(file).ok();
 				}
 			}
 		});
@@ -133,6 +175,8 @@ impl Files {
 		self.ticket = FILES_TICKET.fetch_add(1, Ordering::Relaxed);
 		self.sorter.sort(&mut items, &self.sizes);
 		self.items = items;
+		self.filtered.clear();
+		self.apply_filter();
 		self.version += 1;
 		true
 	}
@@ -152,6 +196,7 @@ impl Files {
 			}
 
 			self.sorter.sort(&mut self.items, &self.sizes);
+			self.apply_filter();
 			self.version += 1;
 			return true;
 		}
@@ -168,6 +213,19 @@ impl Files {
 	}
 
 	pub fn update_size(&mut self, items: BTreeMap<Url, u64>) -> bool {
+		for url in items.keys() {
+			self.sizing.remove(url);
+		}
+		self.sizes.extend(items);
+		if self.sorter.by == SortBy::Size {
+			self.sorter.sort(&mut self.items, &self.sizes);
+			self.version += 1;
+		}
+		true
+	}
+
+	pub fn update_sizing(&mut self, items: BTreeMap<Url, u64>) -> bool {
+		self.sizing.extend(items.keys().cloned());
 		self.sizes.extend(items);
 		if self.sorter.by == SortBy::Size {
 			self.sorter.sort(&mut self.items, &self.sizes);
@@ -176,6 +234,14 @@ impl Files {
 		true
 	}
 
+	pub fn update_git(&mut self, items: BTreeMap<Url, GitStatus>) -> bool {
+		if self.git == items {
+			return false;
+		}
+		self.git = items;
+		true
+	}
+
 	pub fn update_creating(&mut self, mut todo: BTreeMap<Url, File>) -> bool {
 		if !self.show_hidden {
 			todo.retain(|_, f| !f.is_hidden());
@@ -188,6 +254,7 @@ impl Files {
 
 		self.items.extend(todo.into_values());
 		self.sorter.sort(&mut self.items, &self.sizes);
+		self.apply_filter();
 		self.version += 1;
 		true
 	}
@@ -221,6 +288,10 @@ impl Files {
 			go!(self.hidden);
 			b |= !removed.is_empty();
 		}
+
+		if !todo.is_empty() {
+			go!(self.filtered);
+		}
 		b
 	}
 
@@ -246,6 +317,7 @@ impl Files {
 		let old = todo.len();
 		go!(self.items);
 		go!(self.hidden);
+		go!(self.filtered);
 
 		if old != todo.len() {
 			self.version += 1;
@@ -270,6 +342,22 @@ impl Files {
 	#[inline]
 	pub fn position(&self, url: &Url) -> Option<usize> { self.iter().position(|f| &f.url == url) }
 
+	// Counts of directories and files, and their total size, if the size of
+	// every directory is already known
+	pub fn summary(&self) -> (usize, usize, Option<u64>) {
+		let (mut dirs, mut total) = (0, Some(0));
+		for f in self.iter() {
+			let size = if f.is_dir() {
+				dirs += 1;
+				self.sizes.get(&f.url).filter(|_| !self.sizing.contains(&f.url)).copied()
+			} else {
+				Some(f.len)
+			};
+			total = total.zip(size).map(|(a, b)| a + b);
+		}
+		(dirs, self.len() - dirs, total)
+	}
+
 	// --- Selected
 	pub fn selected(&self, pending: &BTreeSet<usize>, unset: bool) -> Vec<&File> {
 		if self.selected.is_empty() && (unset || pending.is_empty()) {
@@ -339,7 +427,57 @@ impl Files {
 		}
 
 		self.show_hidden = state;
+		self.apply_filter();
+		self.version += 1;
+		true
+	}
+
+	#[inline]
+	pub fn filter(&self) -> Option<&FilesFilter> { self.filter.as_ref() }
+
+	pub fn set_filter(&mut self, filter: Option<FilesFilter>) -> bool {
+		if filter.is_none() && self.filter.is_none() {
+			return false;
+		}
+
+		self.filter = filter;
+		self.apply_filter();
+		self.version += 1;
+		true
+	}
+
+	// The kind filter goes by the sniffed mime types once they're known
+	pub fn learn_mimes(&mut self, mimes: &BTreeMap<Url, String>) -> bool {
+		if !self.filter.as_mut().is_some_and(|f| f.learn(mimes)) {
+			return false;
+		}
+
+		self.apply_filter();
 		self.version += 1;
 		true
 	}
+
+	// Moves what the filter no longer keeps out of the items, and back what it does
+	fn apply_filter(&mut self) {
+		if self.filter.is_none() && self.filtered.is_empty() {
+			return;
+		}
+
+		let returned = !self.filtered.is_empty();
+		for f in mem::take(&mut self.filtered) {
+			if !self.show_hidden && f.is_hidden() {
+				self.hidden.push(f);
+			} else {
+				self.items.push(f);
+			}
+		}
+
+		if let Some(filter) = &self.filter {
+			let items = mem::take(&mut self.items);
+			(self.items, self.filtered) = items.into_iter().partition(|f| filter.matches(f));
+		}
+		if returned {
+			self.sorter.sort(&mut self.items, &self.sizes);
+		}
+	}
 }
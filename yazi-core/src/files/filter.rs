@@ -0,0 +1,133 @@
+use std::{collections::HashMap, path::Path};
+
+use yazi_shared::{fs::{File, Url}, MimeKind};
+
+// Keeps the entries whose name contains `name`, smart-case, and files of the
+// `kind`; directories are kept regardless of their kind, to stay navigable.
+#[derive(Debug, Default)]
+pub struct FilesFilter {
+	name:      Option<String>,
+	sensitive: bool,
+	kind:      Option<MimeKind>,
+
+	// Kinds of the sniffed mime types, over guesses from the extension
+	kinds: HashMap<Url, MimeKind>,
+}
+
+impl FilesFilter {
+	pub fn new(name: Option<&str>, kind: Option<MimeKind>) -> Option<Self> {
+		let name = name.filter(|s| !s.is_empty());
+		if name.is_none() && kind.is_none() {
+			return None;
+		}
+
+		let sensitive = name.is_some_and(|s| s.chars().any(char::is_uppercase));
+		Some(Self {
+			name: name.map(|s| if sensitive { s.to_owned() } else { s.to_lowercase() }),
+			sensitive,
+			kind,
+			kinds: Default::default(),
+		})
+	}
+
+	pub fn matches(&self, file: &File) -> bool {
+		if let Some(name) = &self.name {
+			let Some(s) = file.url.file_name().map(|s| s.to_string_lossy()) else {
+				return false;
+			};
+			if !(if self.sensitive { s.contains(name) } else { s.to_lowercase().contains(name) }) {
+				return false;
+			}
+		}
+
+		let Some(kind) = self.kind else {
+			return true;
+		};
+		if file.is_dir() {
+			return true;
+		}
+
+		// Unknown ones stay until their mime type tells
+		match self.kinds.get(&file.url) {
+			Some(&k) => k == kind,
+			None => Self::guess(&file.url).map_or(true, |k| k == kind),
+		}
+	}
+
+	// Learns the kinds of `mimes`, returns whether any of them changed
+	pub fn learn<'a>(&mut self, mimes: impl IntoIterator<Item = (&'a Url, &'a String)>) -> bool {
+		if self.kind.is_none() {
+			return false;
+		}
+
+		let mut b = false;
+		for (url, mime) in mimes {
+			let kind = MimeKind::new(mime);
+			b |=
+				self.kinds.insert(url.clone(), kind).map_or(Self::guess(url) != Some(kind), |k| k != kind);
+		}
+		b
+	}
+
+	#[inline]
+	fn guess(path: &Path) -> Option<MimeKind> {
+		path.extension().and_then(|e| e.to_str()).and_then(MimeKind::from_ext)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use super::*;
+
+	fn file(path: &str, dir: bool) -> File {
+		let mut f = File::from_dummy(Url::from(path));
+		if dir {
+			f.cha.kind |= yazi_shared::fs::ChaKind::DIR;
+		}
+		f
+	}
+
+	#[test]
+	fn test_matches() {
+		let filter = FilesFilter::new(Some("shot"), None).unwrap();
+		assert!(filter.matches(&file("/a/Screenshot.png", false)));
+		assert!(!filter.matches(&file("/a/photo.png", false)));
+
+		let filter = FilesFilter::new(Some("Shot"), None).unwrap();
+		assert!(!filter.matches(&file("/a/screenshot.png", false)));
+		assert!(FilesFilter::new(Some(""), None).is_none());
+
+		let mut filter = FilesFilter::new(None, Some(MimeKind::Video)).unwrap();
+		assert!(filter.matches(&file("/a/clip.mkv", false)));
+		assert!(!filter.matches(&file("/a/photo.png", false)));
+		assert!(filter.matches(&file("/a/Pictures", true)));
+		assert!(filter.matches(&file("/a/unknown", false)));
+
+		let mimes = BTreeMap::from([
+			(Url::from("/a/unknown"), "image/png".to_owned()),
+			(Url::from("/a/clip.mkv"), "video/x-matroska".to_owned()),
+		]);
+		assert!(filter.learn(&mimes));
+		assert!(!filter.learn(&mimes));
+		assert!(!filter.matches(&file("/a/unknown", false)));
+		assert!(filter.matches(&file("/a/clip.mkv", false)));
+	}
+
+	#[test]
+	fn test_learn_seeded() {
+		let mimes = HashMap::from([
+			(Url::from("/a/unknown"), "video/mp4".to_owned()),
+			(Url::from("/b/other"), "video/mp4".to_owned()),
+		]);
+
+		let mut filter = FilesFilter::new(None, Some(MimeKind::Video)).unwrap();
+		assert!(filter.learn(mimes.iter().filter(|(u, _)| u.parent() == Some(Path::new("/a")))));
+		assert!(filter.matches(&file("/a/unknown", false)));
+		assert!(filter.kinds.get(&Url::from("/b/other")).is_none());
+
+		let mut filter = FilesFilter::new(Some("x"), None).unwrap();
+		assert!(!filter.learn(&mimes));
+	}
+}
@@ -0,0 +1,153 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc, time::SystemTime};
+
+use glob::{MatchOptions, Pattern};
+use parking_lot::Mutex;
+use tokio::fs;
+
+#[allow(clippy::type_complexity)]
+static CACHE: Mutex<Option<HashMap<PathBuf, (Option<SystemTime>, Arc<Vec<Rule>>)>>> =
+	Mutex::new(None);
+
+struct Rule {
+	pattern:  Pattern,
+	negated:  bool,
+	dir_only: bool,
+	anchored: bool,
+}
+
+impl Rule {
+	fn parse(line: &str) -> Option<Self> {
+		let line = line.trim_end();
+		if line.is_empty() || line.starts_with('#') {
+			return None;
+		}
+
+		let (negated, line) = match line.strip_prefix('!') {
+			Some(s) => (true, s),
+			None => (false, line.strip_prefix('\\').unwrap_or(line)),
+		};
+		let (dir_only, line) = match line.strip_suffix('/') {
+			Some(s) => (true, s),
+			None => (false, line),
+		};
+
+		// A slash at the beginning or in the middle anchors the pattern to the
+		// `.gitignore`
+		let anchored = line.contains('/');
+		let pattern = Pattern::new(line.trim_start_matches('/')).ok()?;
+		Some(Self { pattern, negated, dir_only, anchored })
+	}
+
+	fn matches(&self, rel: &str, name: &str, is_dir: bool) -> bool {
+		if self.dir_only && !is_dir {
+			return false;
+		}
+
+		let opt = MatchOptions { require_literal_separator: true, ..Default::default() };
+		if self.anchored {
+			self.pattern.matches_with(rel, opt)
+		} else {
+			self.pattern.matches_with(name, opt)
+		}
+	}
+}
+
+// The `.gitignore` rules that apply to the entries of a directory, from the
+// repository root down to the directory itself
+pub(super) struct Ignore {
+	layers: Vec<(PathBuf, Arc<Vec<Rule>>)>,
+}
+
+impl Ignore {
+	pub(super) async fn load(dir: &Path) -> Option<Self> {
+		let mut layers = vec![];
+		for d in dir.ancestors() {
+			if let Some(rules) = Self::rules(&d.join(".gitignore")).await {
+				layers.push((d.to_owned(), rules));
+			}
+			if fs::symlink_metadata(d.join(".git")).await.is_ok() {
+				layers.reverse();
+				return Some(Self { layers }).filter(|s| !s.layers.is_empty());
+			}
+		}
+		None
+	}
+
+	async fn rules(path: &Path) -> Option<Arc<Vec<Rule>>> {
+		let mtime = fs::metadata(path).await.ok()?.modified().ok();
+		if let Some((m, rules)) = CACHE.lock().get_or_insert_with(Default::default).get(path) {
+			if *m == mtime {
+				return Some(rules.clone());
+			}
+		}
+
+		let content = fs::read_to_string(path).await.ok()?;
+		let rules = Arc::new(content.lines().filter_map(Rule::parse).collect::<Vec<_>>());
+		CACHE
+			.lock()
+			.get_or_insert_with(Default::default)
+			.insert(path.to_owned(), (mtime, rules.clone()));
+		Some(rules)
+	}
+
+	// Like git, nothing inside an ignored directory can be re-included
+	pub(super) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+		let Some(root) = self.layers.first().map(|(d, _)| d) else {
+			return false;
+		};
+
+		let mut parents = path.ancestors().skip(1).take_while(|p| p.starts_with(root) && *p != root);
+		parents.any(|p| self.matched(p, true)) || self.matched(path, is_dir)
+	}
+
+	fn matched(&self, path: &Path, is_dir: bool) -> bool {
+		let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+
+		let mut ignored = false;
+		for (dir, rules) in &self.layers {
+			let Ok(rel) = path.strip_prefix(dir) else {
+				continue;
+			};
+
+			let rel = rel.to_string_lossy();
+			for rule in rules.iter() {
+				if rule.matches(&rel, &name, is_dir) {
+					ignored = !rule.negated;
+				}
+			}
+		}
+		ignored
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_rules() {
+		let ignore = Ignore {
+			layers: vec![(
+				PathBuf::from("/repo"),
+				Arc::new(
+					["target/", "*.log", "!keep.log", "/docs/*.html"]
+						.iter()
+						.filter_map(|l| Rule::parse(l))
+						.collect(),
+				),
+			)],
+		};
+
+		assert!(ignore.is_ignored(Path::new("/repo/target"), true));
+		assert!(!ignore.is_ignored(Path::new("/repo/target"), false));
+		assert!(ignore.is_ignored(Path::new("/repo/a/b.log"), false));
+		assert!(!ignore.is_ignored(Path::new("/repo/keep.log"), false));
+		assert!(ignore.is_ignored(Path::new("/repo/docs/index.html"), false));
+		assert!(!ignore.is_ignored(Path::new("/repo/src/docs/index.html"), false));
+
+		assert!(ignore.is_ignored(Path::new("/repo/target/debug"), true));
+		assert!(ignore.is_ignored(Path::new("/repo/target/keep.log"), false));
+		assert!(ignore.is_ignored(Path::new("/repo/a/target/b/c.rs"), false));
+		assert!(!ignore.is_ignored(Path::new("/repo/a/c.rs"), false));
+	}
+}
@@ -1,5 +1,9 @@
 mod files;
+mod filter;
+mod ignore;
 mod sorter;
 
 pub use files::*;
+pub use filter::*;
+use ignore::*;
 pub use sorter::*;
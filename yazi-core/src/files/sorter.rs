@@ -22,11 +22,16 @@ impl FilesSorter {
 				return self.cmp(&*a.url, &*b.url, self.promote(a, b));
 			}
 
-			self.cmp(
+			let ord = self.cmp(
 				a.url.as_os_str().to_ascii_uppercase(),
 				b.url.as_os_str().to_ascii_uppercase(),
 				self.promote(a, b),
-			)
+			);
+			if ord == Ordering::Equal {
+				self.cmp(&*a.url, &*b.url, ord)
+			} else {
+				ord
+			}
 		};
 
 		match self.by {
@@ -77,7 +82,8 @@ impl FilesSorter {
 				return promote;
 			}
 
-			let ordering = natsort(&entities[a].0, &entities[b].0, !self.sensitive);
+			let ordering = natsort(&entities[a].0, &entities[b].0, !self.sensitive)
+				.then_with(|| entities[a].1.url.cmp(&entities[b].1.url));
 			if self.reverse { ordering.reverse() } else { ordering }
 		});
 
@@ -103,3 +109,59 @@ impl FilesSorter {
 		if self.dir_first { b.is_dir().cmp(&a.is_dir()) } else { Ordering::Equal }
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use yazi_shared::fs::ChaKind;
+
+	use super::*;
+
+	fn file(name: &str, dir: bool, len: u64) -> File {
+		let mut f = File::from_dummy(Url::from(format!("/a/{name}")));
+		if dir {
+			f.cha.kind |= ChaKind::DIR;
+		}
+		f.cha.len = len;
+		f
+	}
+
+	fn sorted(sorter: FilesSorter, items: &[File]) -> Vec<String> {
+		let mut items = items.to_vec();
+		sorter.sort(&mut items, &BTreeMap::new());
+		items.iter().map(|f| f.name().unwrap().to_string_lossy().into_owned()).collect()
+	}
+
+	#[test]
+	fn test_dir_first() {
+		let items =
+			[file("b", false, 30), file("D", true, 10), file("a", false, 20), file("c", true, 40)];
+
+		let by_name = FilesSorter { by: SortBy::Alphabetical, dir_first: true, ..Default::default() };
+		assert_eq!(sorted(by_name, &items), ["c", "D", "a", "b"]);
+		assert_eq!(sorted(FilesSorter { reverse: true, ..by_name }, &items), ["D", "c", "b", "a"]);
+		assert_eq!(sorted(FilesSorter { dir_first: false, ..by_name }, &items), ["a", "b", "c", "D"]);
+
+		let by_size = FilesSorter { by: SortBy::Size, dir_first: true, ..Default::default() };
+		assert_eq!(sorted(by_size, &items), ["D", "c", "a", "b"]);
+		assert_eq!(sorted(FilesSorter { reverse: true, ..by_size }, &items), ["c", "D", "b", "a"]);
+
+		let natural = FilesSorter { by: SortBy::Natural, dir_first: true, ..Default::default() };
+		assert_eq!(sorted(FilesSorter { reverse: true, ..natural }, &items), ["D", "c", "b", "a"]);
+	}
+
+	#[test]
+	fn test_stable_ties() {
+		let items =
+			[file("b", false, 10), file("A", false, 10), file("a", false, 10), file("B", false, 10)];
+
+		for by in [SortBy::Modified, SortBy::Created, SortBy::Extension, SortBy::Natural, SortBy::Size]
+		{
+			let sorter = FilesSorter { by, ..Default::default() };
+			let mut shuffled = items.to_vec();
+			for _ in 0..items.len() {
+				shuffled.rotate_left(1);
+				assert_eq!(sorted(sorter, &shuffled), ["A", "a", "B", "b"]);
+			}
+		}
+	}
+}
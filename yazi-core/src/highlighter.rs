@@ -1,22 +1,66 @@
-use std::{mem, path::{Path, PathBuf}, sync::{atomic::{AtomicUsize, Ordering}, OnceLock}};
+use std::{io::SeekFrom, mem, path::{Path, PathBuf}, sync::{atomic::{AtomicUsize, Ordering}, OnceLock}, time::SystemTime};
 
 use anyhow::{anyhow, Result};
-use syntect::{dumps::from_uncompressed_data, easy::HighlightLines, highlighting::{Theme, ThemeSet}, parsing::{SyntaxReference, SyntaxSet}, util::as_24_bit_terminal_escaped};
-use tokio::{fs::File, io::{AsyncBufReadExt, BufReader}};
-use yazi_config::THEME;
-use yazi_shared::PeekError;
+use parking_lot::Mutex;
+use syntect::{dumps::{from_dump_file, from_uncompressed_data, from_uncompressed_dump_file}, highlighting::{self, HighlightIterator, HighlightState, Style, Theme, ThemeSet}, parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet}, util::as_24_bit_terminal_escaped};
+use tokio::{fs::{self, File}, io::{self, AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, BufReader}};
+use tracing::warn;
+use yazi_config::{preview::ColorDepth, BOOT, PREVIEW, THEME};
+use yazi_shared::{term::Term, Charset, PeekError};
 
 static INCR: AtomicUsize = AtomicUsize::new(0);
 static SYNTECT_SYNTAX: OnceLock<SyntaxSet> = OnceLock::new();
 static SYNTECT_THEME: OnceLock<Theme> = OnceLock::new();
 
+// The user's syntax sets that were merged, with their mtime and size
+static SYNTECT_STAMP: OnceLock<String> = OnceLock::new();
+
+// Longer lines are split, so that a file without any newline can't be read
+// to the end just to fill the first one
+const LINE_CAP: u64 = 256 << 10;
+
+// Lines between two saved parser states
+const CHECKPOINT_INTERVAL: usize = 200;
+
+// Parser states of the file previewed last, so that scrolling deep into it
+// resumes from the nearest one instead of parsing everything before it again
+static CHECKPOINTS: Mutex<Option<Checkpoints>> = Mutex::new(None);
+
+type CheckpointKey = (PathBuf, Option<SystemTime>, u64, String);
+
+struct Checkpoints {
+	key:    CheckpointKey,
+	points: Vec<Checkpoint>,
+}
+
+#[derive(Clone)]
+struct Checkpoint {
+	// The state before parsing this 0-based line, which starts at `offset`
+	line:      usize,
+	offset:    u64,
+	parse:     ParseState,
+	highlight: HighlightState,
+}
+
+// SAFETY: `ParseState` is only `!Send` because of the Oniguruma capture regions
+// it may hold, which own their buffers and aren't tied to the thread that made
+// them
+unsafe impl Send for Checkpoint {}
+
 pub struct Highlighter {
-	path: PathBuf,
+	path:    PathBuf,
+	charset: Charset,
 }
 
 impl Highlighter {
 	#[inline]
-	pub fn new(path: PathBuf) -> Self { Self { path } }
+	pub fn new(path: PathBuf) -> Self { Self { path, charset: Charset::Utf8 } }
+
+	#[inline]
+	pub fn with_charset(mut self, charset: Charset) -> Self {
+		self.charset = charset;
+		self
+	}
 
 	pub fn init() -> (&'static Theme, &'static SyntaxSet) {
 		#[inline]
@@ -26,14 +70,72 @@ impl Highlighter {
 		}
 
 		let theme = SYNTECT_THEME.get_or_init(|| {
-			from_file().unwrap_or_else(|_| ThemeSet::load_defaults().themes["base16-ocean.dark"].clone())
+			from_file().unwrap_or_else(|_| {
+				let name =
+					if Term::light_background() { "base16-ocean.light" } else { "base16-ocean.dark" };
+				ThemeSet::load_defaults().themes[name].clone()
+			})
+		});
+		let syntaxes = SYNTECT_SYNTAX.get_or_init(|| {
+			Self::user_syntaxes(from_uncompressed_data(yazi_prebuild::syntaxes()).unwrap())
 		});
-		let syntaxes =
-			SYNTECT_SYNTAX.get_or_init(|| from_uncompressed_data(yazi_prebuild::syntaxes()).unwrap());
 
 		(theme, syntaxes)
 	}
 
+	// Merges the syntax sets under `<config>/syntaxes` into the bundled one, the
+	// user's taking precedence. They're dumps made with syntect, since parsing
+	// `.sublime-syntax` needs its `yaml-load` feature, which isn't built in. In a
+	// checkout of syntect 5, this packs a directory of them into `out.packdump`:
+	//
+	//   cargo run --example gendata -- synpack <dir> out.packdump _.packdump
+	//
+	// Both compressed dumps and the uncompressed ones `gendata` writes are read.
+	fn user_syntaxes(bundled: SyntaxSet) -> SyntaxSet {
+		let Ok(it) = std::fs::read_dir(BOOT.config_dir.join("syntaxes")) else {
+			return bundled;
+		};
+
+		let mut paths: Vec<_> = it.flatten().map(|e| e.path()).collect();
+		paths.sort_unstable();
+
+		let (mut extra, mut stamp) = (vec![], String::new());
+		for path in paths {
+			match path.extension().and_then(|e| e.to_str()) {
+				Some("packdump") => match from_dump_file::<SyntaxSet, _>(&path)
+					.or_else(|_| from_uncompressed_dump_file(&path))
+				{
+					Ok(set) => {
+						let meta = std::fs::metadata(&path).ok();
+						stamp += &format!("{path:?} {:?};", meta.map(|m| (m.modified().ok(), m.len())));
+						extra.push(set.into_builder());
+					}
+					Err(e) => warn!("Skipping the invalid syntax set {path:?}: {e}"),
+				},
+				Some("sublime-syntax") => {
+					warn!("Skipping {path:?}, syntax definitions have to be packed into a .packdump")
+				}
+				_ => {}
+			}
+		}
+		SYNTECT_STAMP.set(stamp).ok();
+		if extra.is_empty() {
+			return bundled;
+		}
+
+		let mut builder = bundled.into_builder();
+		for def in extra.iter().flat_map(|b| b.syntaxes()) {
+			builder.add(def.clone());
+		}
+		builder.build()
+	}
+
+	// Identifies the user's syntax sets, for caches of what they highlighted
+	pub fn syntaxes_stamp() -> &'static str {
+		Self::init();
+		SYNTECT_STAMP.get().map_or("", |s| s.as_str())
+	}
+
 	async fn find_syntax(path: &Path) -> Result<&'static SyntaxReference> {
 		let (_, syntaxes) = Self::init();
 		let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
@@ -52,19 +154,37 @@ impl Highlighter {
 		syntaxes.find_syntax_by_first_line(&line).ok_or_else(|| anyhow!("No syntax found"))
 	}
 
-	pub async fn highlight(&self, skip: usize, limit: usize) -> Result<String, PeekError> {
-		let mut reader = BufReader::new(File::open(&self.path).await?).lines();
+	// Also tells whether there are more lines after this page
+	pub async fn highlight(&self, skip: usize, limit: usize) -> Result<(String, bool), PeekError> {
+		let mut reader = LineReader::open(&self.path, self.charset).await?;
 
 		let syntax = Self::find_syntax(&self.path).await;
 		let mut plain = syntax.is_err();
 
-		let mut before = Vec::with_capacity(if plain { 0 } else { skip });
+		let key = match &syntax {
+			Ok(s) => Some(Self::checkpoint_key(&self.path, s).await),
+			Err(_) => None,
+		};
+		let start = key.as_ref().and_then(|k| Self::checkpoint(k, skip));
+
+		let (base, offset) = start.as_ref().map_or((0, 0), |c| (c.line, c.offset));
+		if start.is_some() {
+			reader.seek(base, offset).await?;
+		}
+
+		let (mut i, mut pos, mut more) = (base, offset, false);
+
+		let mut before = Vec::with_capacity(if plain { 0 } else { skip - i });
 		let mut after = Vec::with_capacity(limit);
+		let mut offsets = vec![];
 
-		let mut i = 0;
-		while let Some(mut line) = reader.next_line().await? {
-			i += 1;
+		while let Some((mut line, len)) = reader.next().await? {
+			if !plain && i < skip && i > base && i % CHECKPOINT_INTERVAL == 0 {
+				offsets.push((i, pos));
+			}
+			(i, pos) = (i + 1, pos + len);
 			if i > skip + limit {
+				more = true;
 				break;
 			}
 
@@ -87,45 +207,268 @@ impl Highlighter {
 		}
 
 		if plain {
-			Ok(after.join(""))
-		} else {
-			Self::highlight_with(before, after, syntax.unwrap()).await
+			return Ok((after.join(""), more));
 		}
+
+		let (text, points) =
+			Self::highlight_from(start, offsets, before, after, syntax.unwrap()).await?;
+		Self::save_checkpoints(key.unwrap(), points);
+		Ok((text, more))
 	}
 
+	pub async fn highlight_code(
+		lang: &str,
+		before: Vec<String>,
+		after: Vec<String>,
+	) -> Result<String, PeekError> {
+		let (_, syntaxes) = Self::init();
+		match syntaxes.find_syntax_by_token(lang) {
+			Some(syntax) if !lang.is_empty() => Self::highlight_with(before, after, syntax).await,
+			_ => Ok(after.join("")),
+		}
+	}
+
+	#[inline]
 	async fn highlight_with(
 		before: Vec<String>,
 		after: Vec<String>,
 		syntax: &'static SyntaxReference,
 	) -> Result<String, PeekError> {
+		Ok(Self::highlight_from(None, vec![], before, after, syntax).await?.0)
+	}
+
+	// Highlights `after`, with the state `before` leaves; parsing resumes from
+	// `start`, and the states at the lines in `offsets` are saved along the way
+	async fn highlight_from(
+		start: Option<Checkpoint>,
+		offsets: Vec<(usize, u64)>,
+		before: Vec<String>,
+		after: Vec<String>,
+		syntax: &'static SyntaxReference,
+	) -> Result<(String, Vec<Checkpoint>), PeekError> {
 		let ticket = INCR.load(Ordering::Relaxed);
 
 		tokio::task::spawn_blocking(move || {
 			let (theme, syntaxes) = Self::init();
-			let mut h = HighlightLines::new(syntax, theme);
+			let highlighter = highlighting::Highlighter::new(theme);
+
+			let base = start.as_ref().map_or(0, |c| c.line);
+			let (mut parse, mut highlight) = match start {
+				Some(c) => (c.parse, c.highlight),
+				None => (ParseState::new(syntax), HighlightState::new(&highlighter, ScopeStack::new())),
+			};
+
+			let (mut offsets, mut points) = (offsets.into_iter().peekable(), vec![]);
 			let mut result = String::new();
 
-			for line in before {
+			for (i, line) in before.iter().chain(&after).enumerate() {
 				if ticket != INCR.load(Ordering::Relaxed) {
 					return Err("Highlighting cancelled".into());
 				}
-				h.highlight_line(&line, syntaxes).map_err(|e| anyhow!(e))?;
-			}
-			for line in after {
-				if ticket != INCR.load(Ordering::Relaxed) {
-					return Err("Highlighting cancelled".into());
+
+				if let Some((_, offset)) = offsets.next_if(|&(l, _)| l == base + i) {
+					points.push(Checkpoint {
+						line: base + i,
+						offset,
+						parse: parse.clone(),
+						highlight: highlight.clone(),
+					});
 				}
 
-				let regions = h.highlight_line(&line, syntaxes).map_err(|e| anyhow!(e))?;
-				result.push_str(&as_24_bit_terminal_escaped(&regions, false));
+				let ops = parse.parse_line(line, syntaxes).map_err(|e| anyhow!(e))?;
+				if i >= before.len() {
+					let regions: Vec<_> =
+						HighlightIterator::new(&mut highlight, &ops, line, &highlighter).collect();
+					result.push_str(&Self::escape(&regions));
+				} else {
+					HighlightIterator::new(&mut highlight, &ops, line, &highlighter).for_each(drop);
+				}
 			}
 
 			result.push_str("\x1b[0m");
-			Ok(result)
+			Ok((result, points))
 		})
 		.await?
 	}
 
+	async fn checkpoint_key(path: &Path, syntax: &SyntaxReference) -> CheckpointKey {
+		let meta = fs::metadata(path).await.ok();
+		(
+			path.to_owned(),
+			meta.as_ref().and_then(|m| m.modified().ok()),
+			meta.map_or(0, |m| m.len()),
+			syntax.name.clone(),
+		)
+	}
+
+	// The last saved state at or before line `skip`
+	fn checkpoint(key: &CheckpointKey, skip: usize) -> Option<Checkpoint> {
+		let cache = CHECKPOINTS.lock();
+		let cache = cache.as_ref().filter(|c| c.key == *key)?;
+		cache.points.iter().rev().find(|c| c.line <= skip).cloned()
+	}
+
+	fn save_checkpoints(key: CheckpointKey, points: Vec<Checkpoint>) {
+		let mut cache = CHECKPOINTS.lock();
+		match cache.as_mut() {
+			Some(c) if c.key == key => {
+				c.points.extend(points);
+				c.points.sort_unstable_by_key(|c| c.line);
+				c.points.dedup_by_key(|c| c.line);
+			}
+			_ => *cache = Some(Checkpoints { key, points }),
+		}
+	}
+
 	#[inline]
 	pub fn abort() { INCR.fetch_add(1, Ordering::Relaxed); }
+
+	// Foreground escapes for the regions, in the closest colors the terminal has
+	pub fn escape(regions: &[(Style, &str)]) -> String {
+		if PREVIEW.color_depth == ColorDepth::Truecolor {
+			return as_24_bit_terminal_escaped(regions, false);
+		}
+
+		let mut s = String::new();
+		for (style, text) in regions {
+			let c = style.foreground;
+			match PREVIEW.color_depth {
+				ColorDepth::Palette256 => s.push_str(&format!("\x1b[38;5;{}m", palette_256(c.r, c.g, c.b))),
+				_ => match palette_16(c.r, c.g, c.b) {
+					n @ 0..=7 => s.push_str(&format!("\x1b[{}m", 30 + n)),
+					n => s.push_str(&format!("\x1b[{}m", 90 + n - 8)),
+				},
+			}
+			s.push_str(text);
+		}
+		s
+	}
+}
+
+// The nearest of the 6x6x6 color cube and the grayscale ramp of xterm
+fn palette_256(r: u8, g: u8, b: u8) -> u8 {
+	const STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+	let step = |v: u8| {
+		if v < 48 {
+			0
+		} else if v < 115 {
+			1
+		} else {
+			(v - 35) / 40
+		}
+	};
+
+	let (ri, gi, bi) = (step(r), step(g), step(b));
+	let cube = (STEPS[ri as usize], STEPS[gi as usize], STEPS[bi as usize]);
+
+	let avg = (r as u16 + g as u16 + b as u16) / 3;
+	let level = if avg > 238 { 23 } else { avg.saturating_sub(3) / 10 } as u8;
+	let gray = 8 + 10 * level;
+
+	if distance((r, g, b), (gray, gray, gray)) < distance((r, g, b), cube) {
+		232 + level
+	} else {
+		16 + 36 * ri + 6 * gi + bi
+	}
+}
+
+// The nearest of the 16 ANSI colors, as xterm draws them by default
+fn palette_16(r: u8, g: u8, b: u8) -> u8 {
+	const COLORS: [(u8, u8, u8); 16] = [
+		(0, 0, 0),
+		(205, 0, 0),
+		(0, 205, 0),
+		(205, 205, 0),
+		(0, 0, 238),
+		(205, 0, 205),
+		(0, 205, 205),
+		(229, 229, 229),
+		(127, 127, 127),
+		(255, 0, 0),
+		(0, 255, 0),
+		(255, 255, 0),
+		(92, 92, 255),
+		(255, 0, 255),
+		(0, 255, 255),
+		(255, 255, 255),
+	];
+	(0..16).min_by_key(|&i| distance((r, g, b), COLORS[i as usize])).unwrap()
+}
+
+#[inline]
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+	let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+	d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+enum LineReader {
+	Bytes(BufReader<File>, Charset),
+	Whole(std::vec::IntoIter<String>),
+}
+
+impl LineReader {
+	async fn open(path: &Path, charset: Charset) -> io::Result<Self> {
+		if !matches!(charset, Charset::Utf16Le | Charset::Utf16Be) {
+			return Ok(Self::Bytes(BufReader::new(File::open(path).await?), charset));
+		}
+
+		// UTF-16 can't be split on the `\n` byte, so decode it at once
+		let mut buf = vec![];
+		File::open(path).await?.take(8 << 20).read_to_end(&mut buf).await?;
+		let lines: Vec<_> = charset.decode(&buf).lines().map(ToOwned::to_owned).collect();
+		Ok(Self::Whole(lines.into_iter()))
+	}
+
+	// Moves to the 0-based `line`, which starts at byte `offset`
+	async fn seek(&mut self, line: usize, offset: u64) -> io::Result<()> {
+		match self {
+			Self::Bytes(reader, _) => _ = reader.seek(SeekFrom::Start(offset)).await?,
+			Self::Whole(it) => _ = it.nth(line.wrapping_sub(1)),
+		}
+		Ok(())
+	}
+
+	// The next line, and how many bytes it took up with its line ending
+	async fn next(&mut self) -> io::Result<Option<(String, u64)>> {
+		match self {
+			Self::Bytes(reader, charset) => {
+				let mut buf = vec![];
+				let len = (&mut *reader).take(LINE_CAP).read_until(b'\n', &mut buf).await?;
+				if len == 0 {
+					return Ok(None);
+				}
+				if buf.ends_with(b"\n") {
+					buf.pop();
+					if buf.ends_with(b"\r") {
+						buf.pop();
+					}
+				}
+				Ok(Some((charset.decode(&buf).into_owned(), len as u64)))
+			}
+			Self::Whole(it) => Ok(it.next().map(|s| (s, 0))),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_palette_256() {
+		assert_eq!(palette_256(0, 0, 0), 16);
+		assert_eq!(palette_256(255, 255, 255), 231);
+		assert_eq!(palette_256(255, 0, 0), 196);
+		assert_eq!(palette_256(95, 135, 175), 67);
+		assert_eq!(palette_256(128, 128, 128), 244);
+	}
+
+	#[test]
+	fn test_palette_16() {
+		assert_eq!(palette_16(0, 0, 0), 0);
+		assert_eq!(palette_16(200, 10, 10), 1);
+		assert_eq!(palette_16(250, 250, 250), 15);
+		assert_eq!(palette_16(100, 100, 250), 12);
+		assert_eq!(palette_16(130, 130, 130), 8);
+	}
 }
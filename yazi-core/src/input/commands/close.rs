@@ -16,6 +16,9 @@ impl From<bool> for Opt {
 impl Input {
 	pub fn close(&mut self, opt: impl Into<Opt>) -> bool {
 		let opt = opt.into() as Opt;
+		if opt.submit && self.error.is_some() {
+			return false;
+		}
 
 		if self.completion {
 			Completion::_close();
@@ -23,11 +26,17 @@ impl Input {
 
 		if let Some(cb) = self.callback.take() {
 			let value = self.snap_mut().value.clone();
+			if opt.submit && self.history.push(&value) {
+				self.history.save();
+			}
 			_ = cb.send(if opt.submit { Ok(value) } else { Err(InputError::Canceled(value)) });
 		}
 
 		self.ticket = self.ticket.wrapping_add(1);
 		self.visible = false;
+		self.validator = None;
+		self.taken = None;
+		self.error = None;
 		true
 	}
 }
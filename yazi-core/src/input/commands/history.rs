@@ -0,0 +1,31 @@
+use yazi_shared::event::Exec;
+
+use crate::input::{op::InputOp, Input};
+
+pub struct Opt {
+	step: isize,
+}
+
+impl From<&Exec> for Opt {
+	fn from(e: &Exec) -> Self {
+		Self { step: e.args.first().and_then(|s| s.parse().ok()).unwrap_or(0) }
+	}
+}
+
+impl Input {
+	pub fn history(&mut self, opt: impl Into<Opt>) -> bool {
+		let opt = opt.into() as Opt;
+		let Some(value) = self.history.step(opt.step, &self.snaps.current().value) else {
+			return false;
+		};
+
+		let snap = self.snap_mut();
+		snap.op = InputOp::None;
+		(snap.value, snap.offset, snap.cursor) = (value, 0, 0);
+
+		self.move_(self.snap().count() as isize);
+		self.snaps.tag(self.limit());
+		self.flush_value();
+		true
+	}
+}
@@ -5,12 +5,15 @@ mod complete;
 mod delete;
 mod escape;
 mod forward;
+mod history;
 mod insert;
 mod kill;
 mod move_;
 mod paste;
 mod redo;
 mod show;
+mod tab;
+mod taken;
 mod type_;
 mod undo;
 mod visual;
@@ -39,12 +39,18 @@ impl Input {
 		self.callback = Some(opt.tx);
 		self.realtime = opt.cfg.realtime;
 		self.completion = opt.cfg.completion;
+		self.path = opt.cfg.path;
+		self.tab = Default::default();
+		self.validator = opt.cfg.validator;
+		self.taken = opt.cfg.taken;
 
 		// Shell
 		self.highlight = opt.cfg.highlight;
 
 		// Reset snaps
 		self.snaps.reset(opt.cfg.value, self.limit());
+		self.history.reset(opt.cfg.history);
+		self.validate();
 		true
 	}
 }
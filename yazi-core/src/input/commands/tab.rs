@@ -0,0 +1,87 @@
+use yazi_shared::{emit, event::Exec, Layer};
+
+use crate::input::{op::InputOp, Input, InputTab};
+
+pub struct Opt {
+	listed: Option<Listed>,
+}
+
+// Candidates listed in the background, for the text before the cursor back then
+struct Listed {
+	before:      String,
+	base:        String,
+	cands:       Vec<String>,
+	insensitive: bool,
+}
+
+impl From<&Exec> for Opt {
+	fn from(e: &Exec) -> Self { Self { listed: e.take_data() } }
+}
+
+impl Input {
+	pub fn tab(&mut self, opt: impl Into<Opt>) -> bool {
+		if !self.path {
+			return false;
+		}
+
+		let replacement = if let Some(listed) = opt.into().listed {
+			let Some(replacement) = self.tab_listed(listed) else {
+				return false;
+			};
+			replacement
+		} else if self.tab.value == self.snap().value && !self.tab.cands.is_empty() {
+			let idx = self.tab.idx.map_or(0, |i| (i + 1) % self.tab.cands.len());
+			self.tab.idx = Some(idx);
+			self.tab.cands[idx].clone()
+		} else {
+			let before = self.partition()[0].to_owned();
+			tokio::spawn(async move {
+				let (base, cands, insensitive) = InputTab::list(&before).await;
+				if !cands.is_empty() {
+					let listed = Listed { before, base, cands, insensitive };
+					emit!(Call(Exec::call("tab", vec![]).with_data(listed).vec(), Layer::Input));
+				}
+			});
+			return false;
+		};
+
+		let head = format!("{}{replacement}", self.tab.base);
+		let value = format!("{head}{}", self.tab.after);
+
+		let snap = self.snap_mut();
+		snap.op = InputOp::None;
+		(snap.value, snap.offset, snap.cursor) = (value, 0, 0);
+
+		self.move_(head.chars().count() as isize);
+		self.tab.value = self.snap().value.clone();
+		self.snaps.tag(self.limit());
+		self.flush_value();
+		true
+	}
+
+	// Settles on what to insert, unless the text has changed since it was listed
+	fn tab_listed(&mut self, listed: Listed) -> Option<String> {
+		let Listed { before, base, mut cands, insensitive } = listed;
+		let [now, after] = self.partition();
+		if now != before {
+			return None;
+		}
+
+		let child = before[base.len()..].chars().count();
+		let (replacement, idx) = match cands.len() {
+			0 => return None,
+			1 => (cands.pop().unwrap(), None),
+			_ => {
+				let prefix = InputTab::common_prefix(&cands, insensitive);
+				if prefix.chars().count() > child {
+					(prefix, None)
+				} else {
+					(cands[0].clone(), Some(0))
+				}
+			}
+		};
+
+		self.tab = InputTab { cands, idx, base, after: after.to_owned(), value: String::new() };
+		Some(replacement)
+	}
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use tokio::{fs, time::sleep};
+use yazi_shared::{emit, event::Exec, Layer};
+
+use crate::input::Input;
+
+// Typing faster than this doesn't stat every keystroke
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+pub struct Opt<'a> {
+	value:  &'a str,
+	ticket: usize,
+}
+
+impl<'a> From<&'a Exec> for Opt<'a> {
+	fn from(e: &'a Exec) -> Self {
+		Self {
+			value:  e.args.first().map(|s| s.as_str()).unwrap_or_default(),
+			ticket: e.named.get("ticket").and_then(|s| s.parse().ok()).unwrap_or(0),
+		}
+	}
+}
+
+impl Input {
+	// Looks up whether the value names an existing entry in the background,
+	// reporting it through `taken` if it still is the value
+	pub(in crate::input) fn check_taken(&mut self) {
+		self.taken_ticket = self.taken_ticket.wrapping_add(1);
+		let Some((dir, except)) = self.taken.clone() else {
+			return;
+		};
+
+		let value = self.snap().value.clone();
+		if except.as_ref() == Some(&value) {
+			return;
+		}
+
+		let ticket = self.taken_ticket;
+		tokio::spawn(async move {
+			sleep(DEBOUNCE).await;
+			if fs::symlink_metadata(dir.join(&value)).await.is_ok() {
+				emit!(Call(Exec::call("taken", vec![value]).with("ticket", ticket).vec(), Layer::Input));
+			}
+		});
+	}
+
+	pub fn taken<'a>(&mut self, opt: impl Into<Opt<'a>>) -> bool {
+		let opt = opt.into() as Opt;
+		if opt.ticket != self.taken_ticket || opt.value != self.snap().value || self.error.is_some() {
+			return false;
+		}
+
+		self.error = Some("Already exists".to_owned());
+		true
+	}
+}
@@ -0,0 +1,116 @@
+use std::collections::{HashMap, VecDeque};
+
+use yazi_config::BOOT;
+
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Default)]
+pub(super) struct InputHistory {
+	kind:  Option<&'static str>,
+	idx:   usize,
+	draft: String,
+
+	loaded:  bool,
+	entries: HashMap<String, VecDeque<String>>,
+}
+
+impl InputHistory {
+	pub(super) fn reset(&mut self, kind: Option<&'static str>) {
+		if kind.is_some() && !self.loaded {
+			self.loaded = true;
+			self.entries = std::fs::read(BOOT.state_dir.join("input_history.json"))
+				.ok()
+				.and_then(|b| serde_json::from_slice(&b).ok())
+				.unwrap_or_default();
+		}
+
+		self.kind = kind;
+		self.idx = 0;
+		self.draft.clear();
+	}
+
+	// Walks `step` entries into the past (negative) or back to the present
+	// (positive), `current` is kept as the draft when leaving it.
+	pub(super) fn step(&mut self, step: isize, current: &str) -> Option<String> {
+		let list = self.entries.get(self.kind?)?;
+		let idx = if step < 0 {
+			list.len().min(self.idx + step.unsigned_abs())
+		} else {
+			self.idx.saturating_sub(step as usize)
+		};
+		if idx == self.idx {
+			return None;
+		}
+
+		if self.idx == 0 {
+			self.draft = current.to_owned();
+		}
+		self.idx = idx;
+		Some(if idx == 0 { self.draft.clone() } else { list[list.len() - idx].clone() })
+	}
+
+	pub(super) fn push(&mut self, value: &str) -> bool {
+		let Some(kind) = self.kind else {
+			return false;
+		};
+		if value.is_empty() {
+			return false;
+		}
+
+		let list = self.entries.entry(kind.to_owned()).or_default();
+		if list.back().is_some_and(|s| s == value) {
+			return false;
+		}
+
+		list.push_back(value.to_owned());
+		if list.len() > HISTORY_LIMIT {
+			list.pop_front();
+		}
+		true
+	}
+
+	pub(super) fn save(&self) {
+		if let Ok(s) = serde_json::to_string(&self.entries) {
+			std::fs::write(BOOT.state_dir.join("input_history.json"), s).ok();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn history(values: &[&str]) -> InputHistory {
+		let mut h = InputHistory { kind: Some("cd"), loaded: true, ..Default::default() };
+		values.iter().for_each(|v| _ = h.push(v));
+		h
+	}
+
+	#[test]
+	fn test_push() {
+		let mut h = history(&["a", "b"]);
+		assert!(!h.push("b"));
+		assert!(!h.push(""));
+		assert!(h.push("a"));
+		assert_eq!(h.entries["cd"], ["a", "b", "a"]);
+
+		(0..HISTORY_LIMIT).for_each(|i| _ = h.push(&i.to_string()));
+		assert_eq!(h.entries["cd"].len(), HISTORY_LIMIT);
+		assert_eq!(h.entries["cd"][0], "0");
+	}
+
+	#[test]
+	fn test_step() {
+		let mut h = history(&["a", "b", "c"]);
+		assert_eq!(h.step(1, "draft"), None);
+		assert_eq!(h.step(-1, "draft").as_deref(), Some("c"));
+		assert_eq!(h.step(-1, "c").as_deref(), Some("b"));
+		assert_eq!(h.step(-5, "b").as_deref(), Some("a"));
+		assert_eq!(h.step(-1, "a"), None);
+		assert_eq!(h.step(1, "a").as_deref(), Some("b"));
+		assert_eq!(h.step(5, "b").as_deref(), Some("draft"));
+
+		h.kind = Some("rename");
+		assert_eq!(h.step(-1, ""), None);
+	}
+}
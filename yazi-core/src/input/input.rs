@@ -1,26 +1,33 @@
-use std::ops::Range;
+use std::{ops::Range, path::PathBuf};
 
 use tokio::sync::mpsc::UnboundedSender;
 use unicode_width::UnicodeWidthStr;
-use yazi_config::{popup::Position, INPUT};
+use yazi_config::{popup::{InputValidator, Position}, INPUT};
 use yazi_scheduler::external;
 use yazi_shared::InputError;
 
-use super::{mode::InputMode, op::InputOp, InputSnap, InputSnaps};
+use super::{mode::InputMode, op::InputOp, InputHistory, InputSnap, InputSnaps, InputTab};
 
 #[derive(Default)]
 pub struct Input {
-	pub(super) snaps: InputSnaps,
-	pub ticket:       usize,
-	pub visible:      bool,
+	pub(super) snaps:   InputSnaps,
+	pub(super) history: InputHistory,
+	pub ticket:         usize,
+	pub visible:        bool,
 
 	pub title:    String,
 	pub position: Position,
 
 	// Typing
-	pub(super) callback:   Option<UnboundedSender<Result<String, InputError>>>,
-	pub(super) realtime:   bool,
-	pub(super) completion: bool,
+	pub(super) callback:     Option<UnboundedSender<Result<String, InputError>>>,
+	pub(super) realtime:     bool,
+	pub(super) completion:   bool,
+	pub(super) path:         bool,
+	pub(super) tab:          InputTab,
+	pub(super) validator:    Option<InputValidator>,
+	pub(super) taken:        Option<(PathBuf, Option<String>)>,
+	pub(super) taken_ticket: usize,
+	pub error:               Option<String>,
 
 	// Shell
 	pub(super) highlight: bool,
@@ -89,6 +96,7 @@ impl Input {
 	#[inline]
 	pub(super) fn flush_value(&mut self) {
 		self.ticket = self.ticket.wrapping_add(1);
+		self.validate();
 
 		if self.realtime {
 			let value = self.snap().value.clone();
@@ -102,6 +110,16 @@ impl Input {
 	}
 }
 
+impl Input {
+	#[inline]
+	pub(super) fn validate(&mut self) {
+		self.error = self.validator.as_ref().and_then(|f| f(&self.snaps.current().value));
+		if self.error.is_none() {
+			self.check_taken();
+		}
+	}
+}
+
 impl Input {
 	#[inline]
 	pub fn value(&self) -> &str { self.snap().slice(self.snap().window(self.limit())) }
@@ -1,13 +1,17 @@
 mod commands;
+mod history;
 mod input;
 mod mode;
 mod op;
 mod shell;
 mod snap;
 mod snaps;
+mod tab;
 
+use history::*;
 pub use input::*;
 pub use mode::*;
 use op::*;
 use snap::*;
 use snaps::*;
+use tab::*;
@@ -1,5 +1,5 @@
 use anyhow::{bail, Result};
-use syntect::{easy::HighlightLines, util::as_24_bit_terminal_escaped};
+use syntect::easy::HighlightLines;
 
 use super::Input;
 use crate::Highlighter;
@@ -14,7 +14,7 @@ impl Input {
 		if let Some(syntax) = syntaxes.find_syntax_by_name("Bourne Again Shell (bash)") {
 			let mut h = HighlightLines::new(syntax, theme);
 			let regions = h.highlight_line(self.value(), syntaxes)?;
-			return Ok(as_24_bit_terminal_escaped(&regions, false));
+			return Ok(Highlighter::escape(&regions));
 		}
 
 		bail!("Failed to find syntax")
@@ -0,0 +1,118 @@
+use std::path::{Path, MAIN_SEPARATOR};
+
+use tokio::fs;
+use yazi_shared::fs::expand_path;
+
+use crate::completion::Completion;
+
+// Candidates of the last completion, kept as long as the value stays what the
+// completion produced, so that pressing Tab again cycles through them.
+#[derive(Default)]
+pub(super) struct InputTab {
+	pub(super) cands: Vec<String>,
+	pub(super) idx:   Option<usize>,
+	pub(super) base:  String,
+	pub(super) after: String,
+	pub(super) value: String,
+}
+
+impl InputTab {
+	// Lists the entries in the directory of `word` starting with its last component
+	pub(super) async fn list(word: &str) -> (String, Vec<String>, bool) {
+		let (base, child) = match word.rsplit_once(MAIN_SEPARATOR) {
+			Some((p, c)) => (format!("{p}{MAIN_SEPARATOR}"), c),
+			None => (String::new(), word),
+		};
+
+		let dir = if base.is_empty() { Path::new(".").to_owned() } else { expand_path(&base) };
+		let Ok(names) = Completion::list(&dir).await else {
+			return (base, vec![], false);
+		};
+
+		let insensitive = Self::insensitive(&dir, &names).await;
+		(base, Self::matches(names, child, insensitive), insensitive)
+	}
+
+	pub(super) fn matches(names: Vec<String>, child: &str, insensitive: bool) -> Vec<String> {
+		let mut cands: Vec<_> = names
+			.into_iter()
+			.filter(|n| {
+				if !insensitive {
+					return n.starts_with(child);
+				}
+				let mut it = n.chars();
+				child.chars().all(|c| it.next().is_some_and(|n| n.to_lowercase().eq(c.to_lowercase())))
+			})
+			.collect();
+
+		cands.sort_unstable();
+		cands
+	}
+
+	pub(super) fn common_prefix(cands: &[String], insensitive: bool) -> String {
+		let Some(first) = cands.first() else {
+			return String::new();
+		};
+
+		let mut len = first.len();
+		for cand in &cands[1..] {
+			len = first
+				.char_indices()
+				.zip(cand.chars())
+				.take_while(|&((_, a), b)| a == b || insensitive && a.to_lowercase().eq(b.to_lowercase()))
+				.map(|((i, a), _)| i + a.len_utf8())
+				.last()
+				.unwrap_or(0)
+				.min(len);
+		}
+		first[..len].to_owned()
+	}
+
+	// Whether the filesystem of `dir` folds case, by looking up an entry with its
+	// case swapped, that doesn't exist under that name itself
+	async fn insensitive(dir: &Path, names: &[String]) -> bool {
+		let Some(name) = names.iter().find(|n| n.chars().any(|c| c.is_alphabetic())) else {
+			return cfg!(any(windows, target_os = "macos"));
+		};
+
+		let name = name.trim_end_matches(MAIN_SEPARATOR);
+		let swapped: String = name
+			.chars()
+			.flat_map(|c| {
+				if c.is_uppercase() {
+					c.to_lowercase().collect::<Vec<_>>()
+				} else {
+					c.to_uppercase().collect()
+				}
+			})
+			.collect();
+
+		!names.iter().any(|n| n.trim_end_matches(MAIN_SEPARATOR) == swapped)
+			&& fs::symlink_metadata(dir.join(swapped)).await.is_ok()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn names(v: &[&str]) -> Vec<String> { v.iter().map(|s| s.to_string()).collect() }
+
+	#[test]
+	fn test_matches() {
+		let all = names(&["foo", "Foobar/", "bar", "food"]);
+		assert_eq!(InputTab::matches(all.clone(), "foo", false), ["foo", "food"]);
+		assert_eq!(InputTab::matches(all.clone(), "foo", true), ["Foobar/", "foo", "food"]);
+		assert_eq!(InputTab::matches(all, "", false).len(), 4);
+	}
+
+	#[test]
+	fn test_common_prefix() {
+		assert_eq!(InputTab::common_prefix(&[], false), "");
+		assert_eq!(InputTab::common_prefix(&names(&["foo/"]), false), "foo/");
+		assert_eq!(InputTab::common_prefix(&names(&["food", "foobar"]), false), "foo");
+		assert_eq!(InputTab::common_prefix(&names(&["Foobar", "food"]), false), "");
+		assert_eq!(InputTab::common_prefix(&names(&["Foobar", "food"]), true), "Foo");
+		assert_eq!(InputTab::common_prefix(&names(&["日本語", "日本"]), false), "日本");
+	}
+}
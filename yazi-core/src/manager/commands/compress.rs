@@ -0,0 +1,26 @@
+use yazi_shared::{event::Exec, fs::expand_path};
+
+use crate::{manager::Manager, tasks::Tasks};
+
+pub struct Opt {
+	to:          Option<String>,
+	dereference: bool,
+}
+
+impl From<&Exec> for Opt {
+	fn from(e: &Exec) -> Self {
+		Self {
+			to:          e.named.get("to").cloned(),
+			dereference: e.named.contains_key("dereference"),
+		}
+	}
+}
+
+impl Manager {
+	pub fn compress(&mut self, opt: impl Into<Opt>, tasks: &Tasks) -> bool {
+		let opt = opt.into() as Opt;
+		let sources = self.selected().into_iter().map(|f| f.url()).collect();
+		let to = opt.to.map(|s| self.cwd().join(expand_path(s)));
+		tasks.file_compress(sources, to, opt.dereference)
+	}
+}
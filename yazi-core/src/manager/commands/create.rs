@@ -2,7 +2,7 @@ use std::path::{PathBuf, MAIN_SEPARATOR};
 
 use tokio::fs;
 use yazi_config::popup::InputCfg;
-use yazi_shared::{emit, event::Exec, fs::{File, FilesOp}, fs::Url};
+use yazi_shared::{emit, event::Exec, fs::{name_error, File, FilesOp, Url}};
 
 use crate::{input::Input, manager::Manager};
 
@@ -19,7 +19,14 @@ impl Manager {
 		let opt = opt.into() as Opt;
 		let cwd = self.cwd().to_owned();
 		tokio::spawn(async move {
-			let mut result = Input::_show(InputCfg::create());
+			let mut cfg = InputCfg::create().with_validator(|name| {
+				name_error(name.trim_end_matches(MAIN_SEPARATOR), true).map(|e| e.to_owned())
+			});
+			if !opt.force {
+				cfg = cfg.with_taken(cwd.to_path_buf(), None);
+			}
+
+			let mut result = Input::_show(cfg);
 			let Some(Ok(name)) = result.recv().await else {
 				return Ok(());
 			};
@@ -0,0 +1,14 @@
+use yazi_shared::event::Exec;
+
+use crate::{manager::Manager, tasks::Tasks};
+
+pub struct Opt;
+
+impl From<&Exec> for Opt {
+	fn from(_: &Exec) -> Self { Self }
+}
+
+impl Manager {
+	#[inline]
+	pub fn empty_trash(&mut self, _: impl Into<Opt>, tasks: &Tasks) -> bool { tasks.empty_trash() }
+}
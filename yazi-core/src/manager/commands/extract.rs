@@ -0,0 +1,23 @@
+use yazi_shared::{event::Exec, fs::expand_path};
+
+use crate::{manager::Manager, tasks::Tasks};
+
+pub struct Opt {
+	to:   Option<String>,
+	here: bool,
+}
+
+impl From<&Exec> for Opt {
+	fn from(e: &Exec) -> Self {
+		Self { to: e.named.get("to").cloned(), here: e.named.contains_key("here") }
+	}
+}
+
+impl Manager {
+	pub fn extract(&mut self, opt: impl Into<Opt>, tasks: &Tasks) -> bool {
+		let opt = opt.into() as Opt;
+		let archives = self.selected().into_iter().map(|f| f.url()).collect();
+		let dest = opt.to.map_or_else(|| self.cwd().clone(), |s| self.cwd().join(expand_path(s)));
+		tasks.file_extract(archives, &dest, opt.here)
+	}
+}
@@ -0,0 +1,41 @@
+use yazi_shared::{event::Exec, MimeKind};
+
+use crate::{files::FilesFilter, manager::Manager};
+
+pub struct Opt<'a> {
+	query: Option<&'a str>,
+	kind:  Option<MimeKind>,
+}
+
+impl<'a> From<&'a Exec> for Opt<'a> {
+	fn from(e: &'a Exec) -> Self {
+		Self {
+			query: e.args.first().map(|s| s.as_str()),
+			kind:  e.named.get("kind").and_then(|s| s.parse().ok()),
+		}
+	}
+}
+
+impl Manager {
+	// Without a query or a kind, the filter is cleared
+	pub fn filter<'a>(&mut self, opt: impl Into<Opt<'a>>) -> bool {
+		let opt = opt.into() as Opt;
+		let tab = self.tabs.active_mut();
+		let hovered = tab.current.hovered().map(|h| h.url());
+
+		// Start from the mime types already sniffed in this folder
+		let mut filter = FilesFilter::new(opt.query, opt.kind);
+		if let Some(f) = &mut filter {
+			let cwd = &tab.current.cwd;
+			f.learn(self.mimetype.iter().filter(|(u, _)| u.parent() == Some(cwd.as_path())));
+		}
+
+		if !tab.current.files.set_filter(filter) {
+			return false;
+		}
+
+		tab.current.repos(hovered);
+		Self::_hover(None);
+		true
+	}
+}
@@ -3,13 +3,20 @@ use yazi_shared::event::Exec;
 use crate::{manager::Manager, tasks::Tasks};
 
 pub struct Opt {
-	relative: bool,
-	force:    bool,
+	relative:    bool,
+	dereference: bool,
+	force:       bool,
+	dry_run:     bool,
 }
 
 impl From<&Exec> for Opt {
 	fn from(e: &Exec) -> Self {
-		Self { relative: e.named.contains_key("relative"), force: e.named.contains_key("force") }
+		Self {
+			relative:    e.named.contains_key("relative"),
+			dereference: e.named.contains_key("dereference"),
+			force:       e.named.contains_key("force"),
+			dry_run:     e.named.contains_key("dry-run"),
+		}
 	}
 }
 
@@ -17,6 +24,6 @@ impl Manager {
 	pub fn link(&mut self, opt: impl Into<Opt>, tasks: &Tasks) -> bool {
 		let opt = opt.into() as Opt;
 		let (cut, ref src) = self.yanked;
-		!cut && tasks.file_link(src, self.cwd(), opt.relative, opt.force)
+		!cut && tasks.file_link(src, self.cwd(), opt.relative, opt.dereference, opt.force, opt.dry_run)
 	}
 }
@@ -1,5 +1,9 @@
 mod close;
+mod compress;
 mod create;
+mod empty_trash;
+mod extract;
+mod filter;
 mod hover;
 mod link;
 mod open;
@@ -9,6 +13,7 @@ mod quit;
 mod refresh;
 mod remove;
 mod rename;
+mod restore;
 mod suspend;
 mod tab_close;
 mod tab_create;
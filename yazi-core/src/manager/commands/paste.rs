@@ -3,11 +3,19 @@ use yazi_shared::event::Exec;
 use crate::{manager::Manager, tasks::Tasks};
 
 pub struct Opt {
-	force: bool,
+	force:   bool,
+	verify:  bool,
+	dry_run: bool,
 }
 
 impl From<&Exec> for Opt {
-	fn from(e: &Exec) -> Self { Self { force: e.named.contains_key("force") } }
+	fn from(e: &Exec) -> Self {
+		Self {
+			force:   e.named.contains_key("force"),
+			verify:  e.named.contains_key("verify"),
+			dry_run: e.named.contains_key("dry-run"),
+		}
+	}
 }
 
 impl Manager {
@@ -16,6 +24,10 @@ impl Manager {
 		let (cut, ref src) = self.yanked;
 
 		let opt = opt.into() as Opt;
-		if cut { tasks.file_cut(src, dest, opt.force) } else { tasks.file_copy(src, dest, opt.force) }
+		if cut {
+			tasks.file_cut(src, dest, opt.force, opt.verify, opt.dry_run)
+		} else {
+			tasks.file_copy(src, dest, opt.force, opt.verify, opt.dry_run)
+		}
 	}
 }
@@ -69,8 +69,12 @@ impl Manager {
 
 	fn peek_folder(&mut self, opt: Opt, url: Url) -> bool {
 		let folder = self.active().history.get(&url);
+		// Starting where the folder is scrolled to, but with its hovered file in view
+		let limit = MANAGER.layout.preview_folder_height();
 		let (skip, bound) = folder
-			.map(|f| (f.offset, f.files.len().saturating_sub(MANAGER.layout.folder_height())))
+			.map(|f| {
+				(f.offset.max((f.cursor + 1).saturating_sub(limit)), f.files.len().saturating_sub(limit))
+			})
 			.unwrap_or_default();
 
 		let in_chunks = folder.is_none();
@@ -5,6 +5,7 @@ use crate::{manager::Manager, tasks::Tasks};
 pub struct Opt {
 	force:       bool,
 	permanently: bool,
+	dry_run:     bool,
 }
 
 impl From<&Exec> for Opt {
@@ -12,6 +13,7 @@ impl From<&Exec> for Opt {
 		Self {
 			force:       e.named.contains_key("force"),
 			permanently: e.named.contains_key("permanently"),
+			dry_run:     e.named.contains_key("dry-run"),
 		}
 	}
 }
@@ -20,6 +22,6 @@ impl Manager {
 	pub fn remove(&mut self, opt: impl Into<Opt>, tasks: &Tasks) -> bool {
 		let opt = opt.into() as Opt;
 		let targets = self.selected().into_iter().map(|f| f.url()).collect();
-		tasks.file_remove(targets, opt.force, opt.permanently)
+		tasks.file_remove(targets, opt.force, opt.permanently, opt.dry_run)
 	}
 }
@@ -1,10 +1,10 @@
-use std::{collections::BTreeMap, ffi::OsStr, io::{stdout, BufWriter, Write}, path::PathBuf};
+use std::{collections::{BTreeMap, HashMap, HashSet}, ffi::OsStr, io::{stdout, BufWriter, Write}, path::{Path, PathBuf}};
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{bail, Result};
 use tokio::{fs::{self, OpenOptions}, io::{stdin, AsyncReadExt, AsyncWriteExt}};
 use yazi_config::{popup::InputCfg, OPEN, PREVIEW};
 use yazi_scheduler::{external::{self, ShellOpt}, BLOCKER};
-use yazi_shared::{emit, event::Exec, fs::{max_common_root, File, FilesOp, Url}, term::Term, Defer};
+use yazi_shared::{emit, event::Exec, fs::{expand_path, max_common_root, name_error, File, FilesOp, Url}, term::Term, Defer};
 
 use crate::{input::Input, manager::Manager, Ctx};
 
@@ -39,8 +39,19 @@ impl Manager {
 
 		let opt = opt.into() as Opt;
 		tokio::spawn(async move {
-			let mut result =
-				Input::_show(InputCfg::rename().with_value(hovered.file_name().unwrap().to_string_lossy()));
+			let (dir, old) = (
+				hovered.parent().unwrap().to_owned(),
+				hovered.file_name().unwrap().to_string_lossy().into_owned(),
+			);
+
+			let mut cfg = InputCfg::rename()
+				.with_value(&old)
+				.with_validator(|name| name_error(name, false).map(|e| e.to_owned()));
+			if !opt.force {
+				cfg = cfg.with_taken(dir, Some(old));
+			}
+
+			let mut result = Input::_show(cfg);
 
 			let Some(Ok(name)) = result.recv().await else {
 				return;
@@ -97,6 +108,8 @@ impl Manager {
 				args:   vec![tmp.to_owned().into()],
 				piped:  false,
 				orphan: false,
+				env:    opener.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+				cwd:    opener.cwd.as_ref().map(expand_path),
 			})?;
 			child.wait().await?;
 
@@ -120,15 +133,38 @@ impl Manager {
 			return Ok(());
 		}
 
+		let mut exists = HashSet::new();
+		for (_, n) in &todo {
+			if fs::symlink_metadata(root.join(n)).await.is_ok() {
+				exists.insert(n.clone());
+			}
+		}
+		let (ok, conflicts) = Self::bulk_rename_check(todo, &exists);
+
 		{
 			let mut stdout = BufWriter::new(stdout().lock());
-			for (o, n) in &todo {
+			for (o, n) in &ok {
 				writeln!(stdout, "{} -> {}", o.display(), n.display())?;
 			}
-			write!(stdout, "Continue to rename? (y/N): ")?;
+			if !conflicts.is_empty() {
+				writeln!(stdout, "\nSkipped for conflicts:")?;
+				for (o, n, e) in &conflicts {
+					writeln!(stdout, "{} -> {}: {e}", o.display(), n.display())?;
+				}
+			}
+			if ok.is_empty() {
+				writeln!(stdout, "\nNothing to rename, press ENTER to exit")?;
+			} else {
+				write!(stdout, "Continue to rename? (y/N): ")?;
+			}
 			stdout.flush()?;
 		}
 
+		if ok.is_empty() {
+			stdin().read_exact(&mut [0]).await?;
+			return Ok(());
+		}
+
 		let mut buf = [0; 10];
 		stdin().read(&mut buf).await.ok();
 		if buf[0] != b'y' && buf[0] != b'Y' {
@@ -136,13 +172,10 @@ impl Manager {
 		}
 
 		let mut failed = Vec::new();
-		for (o, n) in todo {
-			if fs::symlink_metadata(&n).await.is_ok() {
-				failed.push((o, n, anyhow!("Destination already exists")));
-				continue;
-			}
+		let order = Self::bulk_rename_order(ok, |p| std::fs::symlink_metadata(root.join(p)).is_ok());
+		for (o, n) in order {
 			if let Err(e) = fs::rename(root.join(&o), root.join(&n)).await {
-				failed.push((o, n, e.into()));
+				failed.push((o, n, e));
 			}
 		}
 		if failed.is_empty() {
@@ -163,4 +196,132 @@ impl Manager {
 		stdin().read_exact(&mut [0]).await?;
 		Ok(())
 	}
+
+	// An edited name may only land on a file that is itself being renamed away.
+	// Repeated until nothing changes, since a skipped rename leaves its source in
+	// place for the others.
+	#[allow(clippy::type_complexity)]
+	fn bulk_rename_check(
+		todo: Vec<(PathBuf, PathBuf)>,
+		exists: &HashSet<PathBuf>,
+	) -> (Vec<(PathBuf, PathBuf)>, Vec<(PathBuf, PathBuf, &'static str)>) {
+		let mut dests = HashMap::new();
+		for (_, n) in &todo {
+			*dests.entry(n.clone()).or_insert(0) += 1;
+		}
+
+		let (mut ok, mut conflicts) = (vec![], vec![]);
+		for (o, n) in todo {
+			if dests[&n] > 1 {
+				conflicts.push((o, n, "Several files renamed to the same name"));
+			} else {
+				ok.push((o, n));
+			}
+		}
+
+		loop {
+			let sources: HashSet<_> = ok.iter().map(|(o, _): &(PathBuf, PathBuf)| o.clone()).collect();
+			let (kept, taken): (Vec<_>, Vec<_>) =
+				ok.into_iter().partition(|(_, n)| sources.contains(n) || !exists.contains(n));
+
+			ok = kept;
+			if taken.is_empty() {
+				break;
+			}
+			conflicts.extend(taken.into_iter().map(|(o, n)| (o, n, "Destination already exists")));
+		}
+		(ok, conflicts)
+	}
+
+	// Orders the renames so none overwrites a file that has yet to move. Cycles,
+	// like swapping two names, are broken by parking one file under a temporary
+	// name, one that neither exists nor is part of the renames.
+	fn bulk_rename_order(
+		todo: Vec<(PathBuf, PathBuf)>,
+		exists: impl Fn(&Path) -> bool,
+	) -> Vec<(PathBuf, PathBuf)> {
+		let names: HashSet<_> = todo.iter().flat_map(|(o, n)| [o.clone(), n.clone()]).collect();
+		let mut pending: Vec<_> = todo;
+		let mut ordered = Vec::with_capacity(pending.len());
+
+		let mut parked = 0;
+		while !pending.is_empty() {
+			let sources: HashSet<_> = pending.iter().map(|(o, _)| o.clone()).collect();
+			let (ready, blocked): (Vec<_>, Vec<_>) =
+				pending.into_iter().partition(|(_, n)| !sources.contains(n));
+
+			pending = blocked;
+			if !ready.is_empty() {
+				ordered.extend(ready);
+				continue;
+			}
+
+			// Everything left is a cycle, free up one of its names
+			let (o, n) = pending.remove(0);
+			let tmp = loop {
+				let mut name = o.file_name().unwrap_or_default().to_owned();
+				name.push(format!(".yazi-bulk-{parked}"));
+				parked += 1;
+
+				let tmp = o.with_file_name(name);
+				if !names.contains(&tmp) && !exists(&tmp) {
+					break tmp;
+				}
+			};
+
+			ordered.push((o, tmp.clone()));
+			pending.push((tmp, n));
+		}
+		ordered
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn pairs(v: &[(&str, &str)]) -> Vec<(PathBuf, PathBuf)> {
+		v.iter().map(|&(o, n)| (PathBuf::from(o), PathBuf::from(n))).collect()
+	}
+
+	#[test]
+	fn test_bulk_rename_check() {
+		let exists: HashSet<_> = ["b", "c"].into_iter().map(PathBuf::from).collect();
+
+		// `b -> c` is skipped for `c`, which keeps `b` in place for `a -> b`
+		let (ok, conflicts) =
+			Manager::bulk_rename_check(pairs(&[("a", "b"), ("b", "c"), ("d", "e")]), &exists);
+		assert_eq!(ok, pairs(&[("d", "e")]));
+		assert_eq!(conflicts.len(), 2);
+
+		let (ok, conflicts) = Manager::bulk_rename_check(pairs(&[("a", "b"), ("b", "d")]), &exists);
+		assert_eq!(ok, pairs(&[("a", "b"), ("b", "d")]));
+		assert!(conflicts.is_empty());
+
+		let (ok, conflicts) = Manager::bulk_rename_check(pairs(&[("a", "x"), ("d", "x")]), &exists);
+		assert!(ok.is_empty());
+		assert_eq!(conflicts.len(), 2);
+	}
+
+	#[test]
+	fn test_bulk_rename_order() {
+		// A chain runs back to front
+		assert_eq!(
+			Manager::bulk_rename_order(pairs(&[("a", "b"), ("b", "c")]), |_| false),
+			pairs(&[("b", "c"), ("a", "b")])
+		);
+
+		// A swap goes through a temporary name
+		assert_eq!(
+			Manager::bulk_rename_order(pairs(&[("a", "b"), ("b", "a")]), |_| false),
+			pairs(&[("a", "a.yazi-bulk-0"), ("b", "a"), ("a.yazi-bulk-0", "b")])
+		);
+
+		// Not onto a name that's taken already
+		assert_eq!(
+			Manager::bulk_rename_order(pairs(&[("a", "b"), ("b", "a")]), |p| p
+				== Path::new("a.yazi-bulk-0")),
+			pairs(&[("a", "a.yazi-bulk-1"), ("b", "a"), ("a.yazi-bulk-1", "b")])
+		);
+	}
 }
@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 
-use yazi_shared::{fs::{File, FilesOp}, fs::Url};
+use yazi_shared::fs::{File, FilesOp, Url};
 
 use super::{Tabs, Watcher};
 use crate::{tab::{Folder, Tab}, tasks::Tasks};
@@ -72,9 +72,15 @@ impl Manager {
 			return false;
 		}
 
-		tasks.precache_image(&mimes);
-		tasks.precache_video(&mimes);
-		tasks.precache_pdf(&mimes);
+		let current = self.current();
+		tasks.precache_image(&mimes, current);
+		tasks.precache_video(&mimes, current);
+		tasks.precache_pdf(&mimes, current);
+
+		let hovered = self.hovered().map(|h| h.url());
+		if self.current_mut().files.learn_mimes(&mimes) {
+			self.current_mut().repos(hovered);
+		}
 
 		self.mimetype.extend(mimes);
 		true
@@ -0,0 +1,64 @@
+use std::{fmt::Write, io::SeekFrom, path::Path};
+
+use tokio::{fs::File, io::{AsyncReadExt, AsyncSeekExt}};
+use yazi_shared::PeekError;
+
+const BYTES_PER_ROW: usize = 16;
+
+pub(super) struct Hex;
+
+impl Hex {
+	pub(super) async fn render(path: &Path, skip: usize, limit: usize) -> Result<String, PeekError> {
+		let mut file = File::open(path).await?;
+
+		let rows = (file.metadata().await?.len() as usize).div_ceil(BYTES_PER_ROW);
+		if skip > 0 && skip + limit > rows {
+			return Err(PeekError::Exceed(rows.saturating_sub(limit)));
+		}
+
+		file.seek(SeekFrom::Start((skip * BYTES_PER_ROW) as u64)).await?;
+		let mut buf = Vec::with_capacity(limit * BYTES_PER_ROW);
+		file.take((limit * BYTES_PER_ROW) as u64).read_to_end(&mut buf).await?;
+
+		Ok(
+			buf
+				.chunks(BYTES_PER_ROW)
+				.enumerate()
+				.map(|(i, chunk)| Self::row((skip + i) * BYTES_PER_ROW, chunk))
+				.collect::<Vec<_>>()
+				.join("\n"),
+		)
+	}
+
+	fn row(offset: usize, chunk: &[u8]) -> String {
+		let mut s = format!("\x1b[34m{offset:08x}\x1b[39m  ");
+		for i in 0..BYTES_PER_ROW {
+			match chunk.get(i) {
+				Some(b) => _ = write!(s, "{b:02x} "),
+				None => s.push_str("   "),
+			}
+			if i == BYTES_PER_ROW / 2 - 1 {
+				s.push(' ');
+			}
+		}
+
+		s.push(' ');
+		s.extend(
+			chunk.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }),
+		);
+		s
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_row() {
+		assert_eq!(
+			Hex::row(0x10, b"ELF\x7f\x00abc"),
+			"\x1b[34m00000010\x1b[39m  45 4c 46 7f 00 61 62 63                           ELF..abc"
+		);
+	}
+}
@@ -0,0 +1,168 @@
+use std::{path::Path, sync::OnceLock};
+
+use regex::{Captures, Regex};
+use tokio::{fs::File, io::{AsyncBufReadExt, BufReader}};
+use yazi_config::MANAGER;
+use yazi_shared::PeekError;
+
+use crate::Highlighter;
+
+static INLINE: OnceLock<Regex> = OnceLock::new();
+
+pub(super) struct Markdown;
+
+impl Markdown {
+	pub(super) async fn render(path: &Path, skip: usize, limit: usize) -> Result<String, PeekError> {
+		let mut reader = BufReader::new(File::open(path).await?).lines();
+
+		// Rendered lines map 1:1 to source lines, so `skip` works the same as for text
+		let mut lines = Vec::with_capacity(limit);
+		let mut fence: Option<(String, Vec<String>, Vec<String>)> = None;
+
+		let mut i = 0;
+		while let Some(line) = reader.next_line().await? {
+			i += 1;
+			if i > skip + limit {
+				break;
+			}
+
+			let visible = i > skip;
+			let trimmed = line.trim_start();
+			if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+				if let Some((lang, before, after)) = fence.take() {
+					Self::flush(&mut lines, &lang, before, after).await?;
+				} else {
+					fence = Some((trimmed[3..].trim().to_owned(), vec![], vec![]));
+				}
+				if visible {
+					lines.push(format!("\x1b[2m{line}\x1b[22m"));
+				}
+				continue;
+			}
+
+			if let Some((_, before, after)) = &mut fence {
+				if visible {
+					after.push(line + "\n")
+				} else {
+					before.push(line + "\n")
+				}
+			} else if visible {
+				lines.push(Self::line(&line));
+			}
+		}
+
+		if let Some((lang, before, after)) = fence {
+			Self::flush(&mut lines, &lang, before, after).await?;
+		}
+
+		if skip > 0 && i < skip + limit {
+			return Err(PeekError::Exceed(i.saturating_sub(limit)));
+		}
+		Ok(lines.join("\n"))
+	}
+
+	async fn flush(
+		lines: &mut Vec<String>,
+		lang: &str,
+		before: Vec<String>,
+		after: Vec<String>,
+	) -> Result<(), PeekError> {
+		if after.is_empty() {
+			return Ok(());
+		}
+
+		let code = Highlighter::highlight_code(lang, before, after).await?;
+		lines.push(match code.strip_suffix("\n\x1b[0m") {
+			Some(s) => format!("{s}\x1b[0m"),
+			None => code.trim_end_matches('\n').to_owned(),
+		});
+		Ok(())
+	}
+
+	pub(super) fn line(s: &str) -> String {
+		let trimmed = s.trim_start();
+		let indent = &s[..s.len() - trimmed.len()];
+
+		let level = trimmed.bytes().take_while(|&b| b == b'#').count();
+		if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+			let text = Self::inline(trimmed[level..].trim());
+			return if level == 1 {
+				format!("\x1b[1;4m{text}\x1b[0m")
+			} else {
+				format!("\x1b[1m{text}\x1b[0m")
+			};
+		}
+
+		if Self::is_rule(trimmed) {
+			return "─".repeat(MANAGER.layout.preview_rect().width as usize);
+		}
+
+		if let Some(rest) = trimmed.strip_prefix('>') {
+			return format!(
+				"{indent}\x1b[2m│\x1b[22m \x1b[3m{}\x1b[23m",
+				Self::inline(rest.trim_start())
+			);
+		}
+
+		for marker in ["- ", "* ", "+ "] {
+			if let Some(rest) = trimmed.strip_prefix(marker) {
+				return format!("{indent}• {}", Self::inline(rest));
+			}
+		}
+
+		Self::inline(s)
+	}
+
+	fn inline(s: &str) -> String {
+		let re = INLINE.get_or_init(|| {
+			Regex::new(r"`([^`]+)`|\*\*(.+?)\*\*|__(.+?)__|\*([^*\s](?:[^*]*[^*\s])?)\*").unwrap()
+		});
+
+		re.replace_all(s, |caps: &Captures| {
+			if let Some(m) = caps.get(1) {
+				format!("\x1b[36m{}\x1b[39m", m.as_str())
+			} else if let Some(m) = caps.get(2).or_else(|| caps.get(3)) {
+				format!("\x1b[1m{}\x1b[22m", m.as_str())
+			} else {
+				format!("\x1b[3m{}\x1b[23m", &caps[4])
+			}
+		})
+		.into_owned()
+	}
+
+	fn is_rule(s: &str) -> bool {
+		let mut chars = s.chars().filter(|c| !c.is_whitespace());
+		let Some(first) = chars.next().filter(|c| matches!(c, '-' | '*' | '_')) else {
+			return false;
+		};
+
+		let mut n = 1;
+		for c in chars {
+			if c != first {
+				return false;
+			}
+			n += 1;
+		}
+		n >= 3
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_inline() {
+		assert_eq!(Markdown::inline("a **b** c"), "a \x1b[1mb\x1b[22m c");
+		assert_eq!(Markdown::inline("`**x**` *y*"), "\x1b[36m**x**\x1b[39m \x1b[3my\x1b[23m");
+		assert_eq!(Markdown::inline("2 * 3 * 4"), "2 * 3 * 4");
+	}
+
+	#[test]
+	fn test_rule() {
+		assert!(Markdown::is_rule("---"));
+		assert!(Markdown::is_rule("* * *"));
+		assert!(!Markdown::is_rule("--"));
+		assert!(!Markdown::is_rule("-*-"));
+	}
+}
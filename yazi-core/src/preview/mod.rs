@@ -1,7 +1,17 @@
+mod hex;
+mod markdown;
+mod notebook;
 mod preview;
 mod provider;
+mod sgr;
+mod table;
 
+use hex::*;
+use markdown::*;
+use notebook::*;
 pub use preview::*;
 use provider::*;
+use sgr::*;
+use table::*;
 
 pub static COLLISION: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
@@ -0,0 +1,110 @@
+use std::path::Path;
+
+use serde_json::Value;
+use tokio::fs;
+use yazi_shared::PeekError;
+
+use super::Markdown;
+use crate::Highlighter;
+
+pub(super) struct Notebook;
+
+impl Notebook {
+	pub(super) async fn render(path: &Path, skip: usize, limit: usize) -> Result<String, PeekError> {
+		let nb: Value = serde_json::from_slice(&fs::read(path).await?).map_err(|e| e.to_string())?;
+		let Some(cells) = nb.get("cells").and_then(|c| c.as_array()) else {
+			return Err("not a notebook".into());
+		};
+
+		let meta = &nb["metadata"];
+		let lang = meta["language_info"]["name"]
+			.as_str()
+			.or_else(|| meta["kernelspec"]["language"].as_str())
+			.unwrap_or("python");
+
+		// Cells are rendered one after another, until there are enough lines to fill
+		// the page
+		let mut lines = vec![];
+		for cell in cells {
+			if lines.len() >= skip + limit {
+				break;
+			}
+
+			let source = Self::text(&cell["source"]);
+			match cell["cell_type"].as_str() {
+				Some("code") => {
+					let n = cell["execution_count"].as_u64().map(|n| n.to_string()).unwrap_or_default();
+					lines.push(format!("\x1b[2mIn [{n}]:\x1b[22m"));
+
+					let code: Vec<_> = source.lines().map(|l| format!("{l}\n")).collect();
+					let code = Highlighter::highlight_code(lang, vec![], code).await?;
+					lines.extend(code.trim_end_matches("\x1b[0m").lines().map(|l| format!("{l}\x1b[0m")));
+
+					for output in cell["outputs"].as_array().into_iter().flatten() {
+						Self::output(&mut lines, output);
+					}
+				}
+				Some("markdown") => lines.extend(source.lines().map(Markdown::line)),
+				_ => lines.extend(source.lines().map(|l| format!("\x1b[2m{l}\x1b[22m"))),
+			}
+			lines.push(String::new());
+		}
+
+		if skip > 0 && lines.len() < skip + limit {
+			return Err(PeekError::Exceed(lines.len().saturating_sub(limit)));
+		}
+		Ok(lines.into_iter().skip(skip).take(limit).collect::<Vec<_>>().join("\n"))
+	}
+
+	fn output(lines: &mut Vec<String>, output: &Value) {
+		let text = match output["output_type"].as_str() {
+			Some("stream") => Self::text(&output["text"]),
+			Some("error") => {
+				let name = output["ename"].as_str().unwrap_or_default();
+				format!("{name}: {}", output["evalue"].as_str().unwrap_or_default())
+			}
+			_ => {
+				let data = &output["data"];
+				if data.as_object().is_some_and(|d| d.keys().any(|k| k.starts_with("image/"))) {
+					return lines.push("\x1b[2m[image output]\x1b[22m".to_owned());
+				}
+				Self::text(&data["text/plain"])
+			}
+		};
+		lines.extend(text.lines().map(|l| format!("\x1b[2m{l}\x1b[22m")));
+	}
+
+	// Multiline strings are stored either as a string or as a list of lines
+	fn text(v: &Value) -> String {
+		match v {
+			Value::String(s) => s.clone(),
+			Value::Array(a) => a.iter().filter_map(|s| s.as_str()).collect(),
+			_ => String::new(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	#[test]
+	fn test_text() {
+		assert_eq!(Notebook::text(&json!("a\nb")), "a\nb");
+		assert_eq!(Notebook::text(&json!(["a\n", "b"])), "a\nb");
+		assert_eq!(Notebook::text(&json!(null)), "");
+	}
+
+	#[test]
+	fn test_output() {
+		let mut lines = vec![];
+		Notebook::output(&mut lines, &json!({ "output_type": "stream", "text": ["1\n", "2\n"] }));
+		Notebook::output(
+			&mut lines,
+			&json!({ "output_type": "display_data", "data": { "image/png": "iVBOR", "text/plain": "<Figure>" } }),
+		);
+		assert_eq!(lines, ["\x1b[2m1\x1b[22m", "\x1b[2m2\x1b[22m", "\x1b[2m[image output]\x1b[22m"]);
+	}
+}
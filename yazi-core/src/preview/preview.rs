@@ -3,7 +3,7 @@ use std::{mem, time::Duration};
 use tokio::{pin, task::JoinHandle};
 use tokio_stream::{wrappers::UnboundedReceiverStream, StreamExt};
 use yazi_adaptor::ADAPTOR;
-use yazi_config::MANAGER;
+use yazi_config::{MANAGER, PREVIEW};
 use yazi_shared::{emit, event::{PreviewData, PreviewLock}, fs::{Cha, FilesOp, Url}, MimeKind, PeekError};
 
 use super::Provider;
@@ -24,12 +24,33 @@ impl Preview {
 		}
 
 		self.abort();
-		let (url, kind, skip) = (url.clone(), MimeKind::new(mime), self.skip);
+		if let Some(note) = Self::special(&cha) {
+			let data = Provider::note(note);
+			emit!(Preview(PreviewLock { url: url.clone(), cha: Some(cha), skip: 0, data }));
+			return;
+		}
+
+		let (url, kind, skip) = (url.clone(), Self::kind(url, mime), self.skip);
+		let exec = PREVIEW.previewer(&url, mime);
 
 		self.handle = Some(tokio::spawn(async move {
-			match Provider::auto(kind, &url, skip).await {
+			let result = match exec {
+				Some(exec) => Provider::custom(&url, exec, skip).await,
+				None => Provider::auto(kind, &url, cha.modified, skip).await,
+			};
+			match result {
 				Ok(data) => {
-					emit!(Preview(PreviewLock { url, cha: Some(cha), skip, data }));
+					let rect = match &data {
+						PreviewData::Image(exif) if kind == MimeKind::Image && exec.is_none() => {
+							Some(Provider::image_rect(exif.as_deref()))
+						}
+						_ => None,
+					};
+
+					emit!(Preview(PreviewLock { url: url.clone(), cha: Some(cha), skip, data }));
+					if let Some(rect) = rect {
+						ADAPTOR.image_animate(&url, rect).await.ok();
+					}
 				}
 				Err(PeekError::Exceed(max)) => {
 					Manager::_peek_upper_bound(max, &url);
@@ -41,6 +62,9 @@ impl Preview {
 
 	pub fn go_folder(&mut self, url: Url, in_chunks: bool) {
 		self.abort();
+		if self.lock.as_ref().is_some_and(|l| l.is_image()) {
+			ADAPTOR.image_erase().ok();
+		}
 		self.lock = Some(PreviewLock {
 			url:  url.clone(),
 			cha:  None,
@@ -70,8 +94,42 @@ impl Preview {
 		}));
 	}
 
+	// Reading these would block, or never end
+	fn special(cha: &Cha) -> Option<&'static str> {
+		Some(if cha.is_fifo() {
+			"FIFO (named pipe), not previewed"
+		} else if cha.is_socket() {
+			"Socket, not previewed"
+		} else if cha.is_block_device() {
+			"Block device, not previewed"
+		} else if cha.is_char_device() {
+			"Character device, not previewed"
+		} else {
+			return None;
+		})
+	}
+
+	fn kind(url: &Url, mime: &str) -> MimeKind {
+		let kind = MimeKind::new(mime);
+		if kind != MimeKind::Text && kind != MimeKind::JSON {
+			return kind;
+		}
+
+		match url.extension().and_then(|e| e.to_str()) {
+			Some("md" | "markdown") if kind == MimeKind::Text => MimeKind::Markdown,
+			Some("csv" | "tsv") if kind == MimeKind::Text => MimeKind::Table,
+			Some("ipynb") => MimeKind::Notebook,
+			_ => kind,
+		}
+	}
+
 	pub fn arrow(&mut self, step: isize, mime: &str, upper: Option<usize>) {
-		let size = Provider::step_size(MimeKind::new(mime), step.unsigned_abs());
+		// User previewers always scroll by lines
+		let kind = match &self.lock {
+			Some(l) if PREVIEW.previewer(&l.url, mime).is_some() => MimeKind::Text,
+			_ => MimeKind::new(mime),
+		};
+		let size = Provider::step_size(kind, step.unsigned_abs());
 		self.skip = if step < 0 { self.skip.saturating_sub(size) } else { self.skip + size };
 
 		if let Some(upper) = upper {
@@ -89,6 +147,7 @@ impl Preview {
 	#[inline]
 	pub fn reset(&mut self) -> bool {
 		self.abort();
+		ADAPTOR.image_erase().ok();
 		self.lock.take().map(|l| l.is_image()) == Some(false)
 	}
 
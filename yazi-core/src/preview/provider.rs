@@ -1,62 +1,296 @@
-use std::path::Path;
+use std::{collections::HashSet, fmt::Write, mem, path::Path, time::SystemTime};
 
-use tokio::fs;
-use yazi_adaptor::ADAPTOR;
-use yazi_config::{MANAGER, PREVIEW};
+use ratatui::layout::Rect;
+use tokio::{fs, io::AsyncReadExt};
+use unicode_width::UnicodeWidthChar;
+use yazi_adaptor::{Image, ADAPTOR};
+use yazi_config::{MANAGER, PREVIEW, THEME};
 use yazi_scheduler::external;
-use yazi_shared::{event::PreviewData, MimeKind, PeekError};
+use yazi_shared::{event::PreviewData, fs::format_xattrs, mime_from_magic, readable_size, term::Term, Charset, MimeKind, PeekError};
 
+use super::{Hex, Markdown, Notebook, Sgr, Table};
 use crate::Highlighter;
 
+// Compressed files are previewed by at most this much of their content
+const DECOMPRESS_CAP: usize = 1 << 20;
+
 pub(super) struct Provider;
 
 impl Provider {
 	pub(super) async fn auto(
 		kind: MimeKind,
 		path: &Path,
+		mtime: Option<SystemTime>,
 		skip: usize,
 	) -> Result<PreviewData, PeekError> {
 		match kind {
-			MimeKind::Empty => Err("Empty file".into()),
+			MimeKind::Empty => Ok(Self::note("0 bytes")),
 			MimeKind::Archive => Provider::archive(path, skip).await.map(PreviewData::Text),
-			MimeKind::Image => Provider::image(path).await,
-			MimeKind::Video => Provider::video(path, skip).await,
+			MimeKind::Compressed => Provider::compressed(path, mtime, skip).await.map(PreviewData::Text),
+			MimeKind::Image => Provider::photo(path, mtime).await,
+			MimeKind::Svg => Provider::svg(path, mtime, skip).await,
+			MimeKind::Video => Provider::video(path, mtime, skip).await,
+			MimeKind::Audio => Provider::audio(path, mtime).await,
+			MimeKind::Font => Provider::font(path, mtime, skip).await,
 			MimeKind::JSON => Provider::json(path, skip).await.map(PreviewData::Text),
-			MimeKind::PDF => Provider::pdf(path, skip).await,
+			MimeKind::PDF => Provider::pdf(path, mtime, skip).await,
 			MimeKind::Text => Provider::highlight(path, skip).await.map(PreviewData::Text),
-			MimeKind::Others => Err("Unsupported mimetype".into()),
+			MimeKind::Markdown => Provider::markdown(path, skip).await.map(PreviewData::Text),
+			MimeKind::Notebook => Provider::notebook(path, skip).await.map(PreviewData::Text),
+			MimeKind::Table => Provider::table(path, skip).await.map(PreviewData::Text),
+			MimeKind::Document => Provider::document(path, mtime, skip).await.map(PreviewData::Text),
+			MimeKind::Binary => Provider::hex(path, skip).await.map(PreviewData::Text),
+			MimeKind::Others => Provider::hex(path, skip).await.map(PreviewData::Text),
+		}
+	}
+
+	// Output of a user previewer, shown as is, escape sequences included
+	pub(super) async fn custom(
+		path: &Path,
+		exec: &str,
+		skip: usize,
+	) -> Result<PreviewData, PeekError> {
+		let rect = MANAGER.layout.preview_rect();
+		let limit = MANAGER.layout.preview_height();
+
+		let lines = match external::previewer(exec, path, (rect.width, rect.height), skip + limit).await
+		{
+			Ok(l) => l,
+			Err(e) => return Ok(Self::note(&format!("Previewer `{exec}` failed: {e}"))),
+		};
+		if skip > 0 && lines.len() < skip + limit {
+			return Err(PeekError::Exceed(lines.len().saturating_sub(limit)));
 		}
+
+		let s = lines.into_iter().skip(skip).collect::<Vec<_>>().join("\n");
+		Ok(PreviewData::Text(s.replace('\t', &" ".repeat(PREVIEW.tab_size as usize)) + "\x1b[0m"))
 	}
 
 	pub(super) fn step_size(kind: MimeKind, step: usize) -> usize {
 		match kind {
 			MimeKind::Empty => 0,
 			MimeKind::Archive => step * MANAGER.layout.preview_height() / 10,
+			MimeKind::Compressed => step * MANAGER.layout.preview_height() / 10,
 			MimeKind::Image => 0,
+			MimeKind::Svg => step * MANAGER.layout.preview_height() / 10,
 			MimeKind::Video => step,
+			MimeKind::Audio => 0,
+			MimeKind::Font => 0,
 			MimeKind::JSON => step * MANAGER.layout.preview_height() / 10,
 			MimeKind::PDF => 1,
 			MimeKind::Text => step * MANAGER.layout.preview_height() / 10,
+			MimeKind::Markdown => step * MANAGER.layout.preview_height() / 10,
+			MimeKind::Notebook => step * MANAGER.layout.preview_height() / 10,
+			MimeKind::Table => step * MANAGER.layout.preview_height() / 10,
+			MimeKind::Document => step * MANAGER.layout.preview_height() / 10,
+			MimeKind::Binary => step * MANAGER.layout.preview_height() / 10,
 			MimeKind::Others => step * MANAGER.layout.preview_height() / 10,
 		}
 	}
 
 	pub(super) async fn image(path: &Path) -> Result<PreviewData, PeekError> {
 		ADAPTOR.image_show(path, MANAGER.layout.image_rect()).await?;
-		Ok(PreviewData::Image)
+		Ok(PreviewData::Image(None))
 	}
 
-	pub(super) async fn video(path: &Path, skip: usize) -> Result<PreviewData, PeekError> {
-		let cache = PREVIEW.cache(path, skip);
-		if fs::symlink_metadata(&cache).await.is_err() {
-			external::ffmpegthumbnailer(path, &cache, skip).await?;
+	pub(super) async fn photo(
+		path: &Path,
+		mtime: Option<SystemTime>,
+	) -> Result<PreviewData, PeekError> {
+		// Files still being downloaded are often empty or cut off halfway
+		if Self::zero_bytes(path).await {
+			return Ok(Self::note("0 bytes"));
+		}
+		if let Some(len) = Self::oversized(path).await {
+			return Ok(Self::note(&format!("{}, too large to preview", readable_size(len))));
+		}
+
+		let exif = if PREVIEW.show_exif {
+			external::exiftool(path).await.ok().filter(|s| !s.is_empty())
+		} else {
+			None
+		};
+		// Precached ones are downscaled already
+		let cache = PREVIEW.cache(path, mtime, 0);
+		let path = if fs::symlink_metadata(&cache).await.is_ok() { cache.as_path() } else { path };
+		match ADAPTOR.image_show(path, Self::image_rect(exif.as_deref())).await {
+			Ok(()) => Ok(PreviewData::Image(exif)),
+			Err(e) if Image::is_decode_error(&e) => Ok(Self::note("Cannot decode image")),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	#[inline]
+	async fn zero_bytes(path: &Path) -> bool { fs::metadata(path).await.is_ok_and(|m| m.len() == 0) }
+
+	// The size of an image over `max_image`, which isn't worth decoding
+	async fn oversized(path: &Path) -> Option<u64> {
+		let len = fs::metadata(path).await.ok()?.len();
+		Some(len).filter(|&l| PREVIEW.max_image > 0 && l > PREVIEW.max_image)
+	}
+
+	#[inline]
+	pub(super) fn note(s: &str) -> PreviewData { PreviewData::Text(format!("\x1b[2m{s}\x1b[22m")) }
+
+	// Leave room for the EXIF text at the bottom of the preview
+	pub(super) fn image_rect(exif: Option<&str>) -> Rect {
+		let mut rect = MANAGER.layout.image_rect();
+		if let Some(s) = exif {
+			let rest = MANAGER.layout.preview_rect().bottom().saturating_sub(rect.bottom());
+			let need = (s.lines().count() as u16 + 1).saturating_sub(rest);
+			rect.height = rect.height.saturating_sub(need);
+		}
+		rect
+	}
+
+	pub(super) async fn svg(
+		path: &Path,
+		mtime: Option<SystemTime>,
+		skip: usize,
+	) -> Result<PreviewData, PeekError> {
+		let cache = PREVIEW.cache(path, mtime, 0);
+		if fs::symlink_metadata(&cache).await.is_err()
+			&& external::rsvg_convert(path, &cache).await.is_err()
+		{
+			// Show the source of malformed SVGs, or if `rsvg-convert` is unavailable
+			return Self::highlight(path, skip).await.map(PreviewData::Text);
 		}
 
 		Self::image(&cache).await
 	}
 
-	pub(super) async fn pdf(path: &Path, skip: usize) -> Result<PreviewData, PeekError> {
-		let cache = PREVIEW.cache(path, skip);
+	pub(super) async fn video(
+		path: &Path,
+		mtime: Option<SystemTime>,
+		skip: usize,
+	) -> Result<PreviewData, PeekError> {
+		if Self::zero_bytes(path).await {
+			return Ok(Self::note("0 bytes"));
+		}
+
+		let cache = PREVIEW.cache_with(path, mtime, skip, &PREVIEW.video_seek.to_string());
+		if fs::symlink_metadata(&cache).await.is_err() {
+			if !external::available("ffmpegthumbnailer") {
+				let note = "\x1b[2mInstall ffmpegthumbnailer for video previews\x1b[22m";
+				return Ok(PreviewData::Text(match Self::video_info(path).await {
+					Some(info) if PREVIEW.video_info => format!("{info}\n\n{note}"),
+					_ => note.to_owned(),
+				}));
+			}
+			if !external::ffmpegthumbnailer(path, &cache, skip).await? {
+				return Ok(Self::note("Cannot generate a thumbnail for this video"));
+			}
+		}
+
+		let info = if PREVIEW.video_info { Self::video_info(path).await } else { None };
+		match ADAPTOR.image_show(&cache, Self::image_rect(info.as_deref())).await {
+			Ok(()) => Ok(PreviewData::Image(info)),
+			Err(e) if Image::is_decode_error(&e) => {
+				Ok(Self::note("Cannot generate a thumbnail for this video"))
+			}
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	async fn video_info(path: &Path) -> Option<String> {
+		let info = external::ffprobe(path).await.ok()?;
+
+		let mut lines = vec![];
+		if let Some(secs) = info.format.duration.as_ref().and_then(|s| s.parse::<f64>().ok()) {
+			let secs = secs as u64;
+			lines.push(format!("Duration: {}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60));
+		}
+		if let Some(video) = info.video() {
+			if let (Some(w), Some(h)) = (video.width, video.height) {
+				lines.push(format!("Resolution: {w}x{h}"));
+			}
+			if let Some(codec) = &video.codec_name {
+				lines.push(format!("Codec: {codec}"));
+			}
+		}
+		if let Some(rate) = info.format.bit_rate.as_ref().and_then(|s| s.parse::<u64>().ok()) {
+			lines.push(format!("Bitrate: {} kbps", rate / 1000));
+		}
+		Some(lines.join("\n")).filter(|s| !s.is_empty())
+	}
+
+	pub(super) async fn audio(
+		path: &Path,
+		mtime: Option<SystemTime>,
+	) -> Result<PreviewData, PeekError> {
+		let info = external::ffprobe(path).await?;
+
+		let mut lines = vec![];
+		for (name, tag) in
+			[("Title", "title"), ("Artist", "artist"), ("Album", "album"), ("Track", "track")]
+		{
+			if let Some(v) = info.tag(tag) {
+				lines.push(format!("{name}: {v}"));
+			}
+		}
+		if let Some(secs) = info.format.duration.as_ref().and_then(|s| s.parse::<f64>().ok()) {
+			let secs = secs as u64;
+			lines.push(format!("Duration: {}:{:02}", secs / 60, secs % 60));
+		}
+		if let Some(rate) = info.format.bit_rate.as_ref().and_then(|s| s.parse::<u64>().ok()) {
+			lines.push(format!("Bitrate: {} kbps", rate / 1000));
+		}
+		if let Some(rate) = info.sample_rate() {
+			lines.push(format!("Sample rate: {rate} Hz"));
+		}
+		let text = lines.join("\n");
+
+		let cache = PREVIEW.cache(path, mtime, 0);
+		if info.has_cover() && fs::symlink_metadata(&cache).await.is_err() {
+			external::ffmpeg_cover(path, &cache).await.ok();
+		}
+		if fs::symlink_metadata(&cache).await.is_err() {
+			return Ok(PreviewData::Text(text));
+		}
+
+		let text = Some(text).filter(|s| !s.is_empty());
+		ADAPTOR.image_show(&cache, Self::image_rect(text.as_deref())).await?;
+		Ok(PreviewData::Image(text))
+	}
+
+	pub(super) async fn font(
+		path: &Path,
+		mtime: Option<SystemTime>,
+		skip: usize,
+	) -> Result<PreviewData, PeekError> {
+		let Ok(info) = external::fc_scan(path).await else {
+			return Self::hex(path, skip).await.map(PreviewData::Text);
+		};
+
+		let cache = PREVIEW.cache(path, mtime, 0);
+		if fs::symlink_metadata(&cache).await.is_err() {
+			let sample =
+				format!("{}\n\nThe quick brown fox jumps over the lazy dog.\n0123456789 !?&@", info.family);
+			if external::magick_font(path, &cache, &sample).await.is_err() {
+				return Self::hex(path, skip).await.map(PreviewData::Text);
+			}
+		}
+
+		let header = format!("Family: {}\nStyle: {}", info.family, info.style);
+		ADAPTOR.image_show(&cache, Self::image_rect(Some(&header))).await?;
+		Ok(PreviewData::Image(Some(header)))
+	}
+
+	pub(super) async fn pdf(
+		path: &Path,
+		mtime: Option<SystemTime>,
+		skip: usize,
+	) -> Result<PreviewData, PeekError> {
+		if PREVIEW.pdf_as_text {
+			// Scanned documents have no text layer, render them as images instead
+			match external::pdftotext(path, skip, MANAGER.layout.preview_height()).await {
+				Ok(s) if !s.trim().is_empty() => return Ok(PreviewData::Text(s)),
+				Err(e @ PeekError::Exceed(_)) => return Err(e),
+				_ => {}
+			}
+		}
+
+		let cache = PREVIEW.cache(path, mtime, skip);
 		if fs::symlink_metadata(&cache).await.is_err() {
 			external::pdftoppm(path, &cache, skip).await?;
 		}
@@ -65,27 +299,392 @@ impl Provider {
 	}
 
 	pub(super) async fn json(path: &Path, skip: usize) -> Result<String, PeekError> {
-		let result = external::jq(path, skip, MANAGER.layout.preview_height()).await;
-		if let Err(PeekError::Unexpected(_)) = result {
+		if !external::available("jq") {
 			return Self::highlight(path, skip).await;
 		}
-		result
+		match external::jq(path, skip, MANAGER.layout.preview_height()).await {
+			Ok((s, more)) => Ok(if more { Self::footer(&s, "… truncated") } else { s }),
+			Err(PeekError::Unexpected(_)) => Self::highlight(path, skip).await,
+			Err(e) => Err(e),
+		}
 	}
 
 	pub(super) async fn archive(path: &Path, skip: usize) -> Result<String, PeekError> {
-		Ok(
-			external::lsar(path, skip, MANAGER.layout.preview_height())
-				.await?
-				.into_iter()
-				.map(|f| f.name)
-				.collect::<Vec<_>>()
-				.join("\n"),
-		)
+		if !external::available("lsar") {
+			let size = fs::metadata(path).await.map(|m| readable_size(m.len())).unwrap_or_default();
+			return Ok(format!(
+				"Archive, {size}\n\n\x1b[2mInstall unar for listing its contents\x1b[22m"
+			));
+		}
+
+		// The first line is taken by the sizes of the whole archive
+		let limit = MANAGER.layout.preview_height().saturating_sub(1).max(1);
+		let (files, rest, unpacked) = external::lsar(path, skip, limit).await?;
+
+		let packed = fs::metadata(path).await.map(|m| m.len()).unwrap_or(0);
+		let mut header = format!("Archive, {}", readable_size(packed));
+		if unpacked > 0 {
+			let ratio = packed as f64 / unpacked as f64 * 100.0;
+			header.push_str(&format!(", {} unpacked, {ratio:.1}%", readable_size(unpacked)));
+		}
+
+		// Directories without an entry of their own are filled in from the paths
+		let mut lines = Vec::with_capacity(limit);
+		let mut dirs = HashSet::new();
+		for f in files {
+			let parts: Vec<_> = f.name.trim_end_matches('/').split('/').collect();
+			for d in 1..parts.len() {
+				if dirs.insert(parts[..d].join("/")) {
+					lines.push(format!("{}\x1b[1;34m{}/\x1b[0m", "  ".repeat(d - 1), parts[d - 1]));
+				}
+			}
+
+			let (depth, name) = (parts.len() - 1, parts[parts.len() - 1]);
+			if !f.is_dir {
+				let size = match (f.size, f.compressed_size) {
+					(Some(s), Some(c)) if c != s => {
+						format!("{} → {}", readable_size(c as u64), readable_size(s as u64))
+					}
+					(Some(s), _) => readable_size(s as u64),
+					(None, _) => String::new(),
+				};
+				lines.push(format!("{}{name}  \x1b[2m{size}\x1b[22m", "  ".repeat(depth)));
+			} else if dirs.insert(parts.join("/")) {
+				lines.push(format!("{}\x1b[1;34m{name}/\x1b[0m", "  ".repeat(depth)));
+			}
+		}
+
+		let body = if rest > 0 {
+			Self::footer(&lines.join("\n"), &format!("… {rest} more files"))
+		} else if lines.len() > limit {
+			lines.truncate(limit);
+			Self::footer(&lines.join("\n"), "… truncated")
+		} else {
+			lines.join("\n")
+		};
+		Ok(format!("\x1b[2m{header}\x1b[22m\n{body}"))
+	}
+
+	// Single-stream compressed files are previewed by their decompressed content,
+	// except for tarballs, which are listed as archives
+	pub(super) async fn compressed(
+		path: &Path,
+		mtime: Option<SystemTime>,
+		skip: usize,
+	) -> Result<String, PeekError> {
+		let mut magic = [0; 6];
+		let n = fs::File::open(path).await?.read(&mut magic).await?;
+		let tool = match &magic[..n] {
+			[0x1f, 0x8b, ..] => "gzip",
+			[b'B', b'Z', b'h', ..] => "bzip2",
+			[0xfd, b'7', b'z', b'X', b'Z', 0x00] => "xz",
+			[0x28, 0xb5, 0x2f, 0xfd, ..] => "zstd",
+			_ => return Self::hex(path, skip).await,
+		};
+
+		// Keep the inner name, e.g. `app.log` of `app.log.gz`, for syntax detection,
+		// it's written under another one first so a partial file is never picked up
+		let dir = PREVIEW.cache_with(path, mtime, 0, "decompressed");
+		let inner = dir.join(path.file_stem().unwrap_or_default());
+		let head = match fs::File::open(&inner).await {
+			Ok(mut f) => {
+				let mut head = vec![0; 512];
+				let n = f.read(&mut head).await?;
+				head.truncate(n);
+				head
+			}
+			Err(_) => {
+				let Ok(data) = external::decompress(tool, path, DECOMPRESS_CAP).await else {
+					return Self::hex(path, skip).await;
+				};
+
+				fs::create_dir_all(&dir).await?;
+				let part = dir.join(".part");
+				fs::write(&part, &data).await?;
+				fs::rename(&part, &inner).await?;
+				data
+			}
+		};
+
+		if mime_from_magic(&head) == Some("application/x-tar") {
+			return Self::archive(path, skip).await;
+		}
+		Self::highlight(&inner, skip).await
 	}
 
 	pub(super) async fn highlight(path: &Path, skip: usize) -> Result<String, PeekError> {
+		// Small files are fast enough to highlight, so only cache the big ones
+		let meta = fs::metadata(path).await?;
+		if meta.len() < 32 << 10 {
+			return Self::highlight_uncached(path, skip).await;
+		}
+
+		let rect = MANAGER.layout.preview_rect();
+		let salt = format!(
+			"highlight///{:?}///{}///{:?}///{}///{}///{}///{}x{}///{}",
+			THEME.manager.syntect_theme,
+			Term::light_background(),
+			PREVIEW.color_depth,
+			PREVIEW.tab_size,
+			PREVIEW.line_numbers,
+			PREVIEW.wrap,
+			rect.width,
+			rect.height,
+			Highlighter::syntaxes_stamp()
+		);
+		let cache = PREVIEW.cache_with(path, meta.modified().ok(), skip, &salt);
+
+		// The first line records the file it was made from, to detect staleness
+		let stamp = format!("{:?} {}\n", meta.modified().ok(), meta.len());
+		if let Ok(s) = fs::read_to_string(&cache).await {
+			if let Some(s) = s.strip_prefix(&stamp) {
+				return Ok(s.to_owned());
+			}
+		}
+
+		let result = Self::highlight_uncached(path, skip).await?;
+		fs::write(&cache, format!("{stamp}{result}")).await.ok();
+		Ok(result)
+	}
+
+	async fn highlight_uncached(path: &Path, skip: usize) -> Result<String, PeekError> {
+		let charset = Charset::sniff(path).await?;
+		if charset == Charset::Binary {
+			return Self::hex(path, skip).await;
+		}
+
+		// Reserve the last line to show the encoding, if it isn't UTF-8
+		let mut limit = MANAGER.layout.preview_height();
+		if charset != Charset::Utf8 {
+			limit = limit.saturating_sub(1);
+		}
+
+		let (result, more) =
+			Highlighter::new(path.to_owned()).with_charset(charset).highlight(skip, limit).await?;
+		let mut result = result.replace('\t', &" ".repeat(PREVIEW.tab_size as usize));
+		if PREVIEW.line_numbers || PREVIEW.wrap {
+			let wrap = Some(MANAGER.layout.preview_rect().width as usize).filter(|_| PREVIEW.wrap);
+			result = Self::layout(&result, skip, limit, PREVIEW.line_numbers, wrap);
+		}
+		if more {
+			result = Self::footer(&result, "… truncated");
+		}
+		Ok(if charset == Charset::Utf8 {
+			result
+		} else {
+			format!("{}\n\x1b[2m[{}]\x1b[22m", result.trim_end_matches('\n'), charset.name())
+		})
+	}
+
+	// The gutter is as wide as the largest line number on this page, and wrapped
+	// lines are cut to `limit` since they take more rows than the source has lines
+	fn layout(s: &str, skip: usize, limit: usize, numbers: bool, wrap: Option<usize>) -> String {
+		// Highlighted text ends with a reset, which is not a line of its own
+		let s = s.strip_suffix("\x1b[0m").unwrap_or(s);
+		let digits = if numbers { (skip + s.lines().count()).to_string().len() } else { 0 };
+		let gutter = if numbers { digits + 1 } else { 0 };
+
+		let mut result = String::with_capacity(s.len());
+		let mut rows = 0;
+		for (i, line) in s.lines().enumerate() {
+			let parts = match wrap {
+				Some(w) => Self::wrap(line, w.saturating_sub(gutter)),
+				None => vec![line.to_owned()],
+			};
+			for (j, part) in parts.into_iter().enumerate() {
+				if rows == limit {
+					return result + "\x1b[0m";
+				}
+				rows += 1;
+
+				if !numbers {
+					writeln!(result, "{part}").ok();
+				} else if j == 0 {
+					writeln!(result, "\x1b[2m{:>digits$} \x1b[22m{part}", skip + i + 1).ok();
+				} else {
+					writeln!(result, "{:gutter$}{part}", "").ok();
+				}
+			}
+		}
+		result + "\x1b[0m"
+	}
+
+	// Replaces the last line with a dimmed note, to tell the preview is cut off
+	fn footer(s: &str, note: &str) -> String {
+		let s = s.trim_end_matches("\x1b[0m").trim_end_matches('\n');
+		match s.rfind('\n') {
+			Some(i) => format!("{}\x1b[0m\n\x1b[2m{note}\x1b[22m", &s[..i]),
+			None => format!("\x1b[2m{note}\x1b[22m"),
+		}
+	}
+
+	// Wraps a line by its display width, carrying the escape sequences seen so
+	// far over to the indented continuation lines
+	fn wrap(line: &str, width: usize) -> Vec<String> {
+		const INDENT: usize = 2;
+		if width <= INDENT * 2 {
+			return vec![line.to_owned()];
+		}
+
+		let (mut lines, mut cur, mut sgr, mut col) = (vec![], String::new(), Sgr::default(), 0);
+		let mut it = line.chars().peekable();
+		while let Some(c) = it.next() {
+			if c == '\x1b' {
+				let mut seq = String::from(c);
+				if it.peek() == Some(&'[') {
+					seq.push(it.next().unwrap());
+					for c in it.by_ref() {
+						seq.push(c);
+						if ('@'..='~').contains(&c) {
+							break;
+						}
+					}
+				}
+				if let Some(params) = seq.strip_prefix("\x1b[").and_then(|s| s.strip_suffix('m')) {
+					sgr.apply(params);
+				}
+				cur.push_str(&seq);
+				continue;
+			}
+
+			let w = c.width().unwrap_or(0);
+			if col + w > width {
+				if !sgr.is_empty() {
+					cur.push_str("\x1b[0m");
+				}
+				lines.push(mem::replace(&mut cur, format!("{:INDENT$}{}", "", sgr.escape())));
+				col = INDENT;
+			}
+			cur.push(c);
+			col += w;
+		}
+
+		lines.push(cur);
+		lines
+	}
+
+	pub(super) async fn markdown(path: &Path, skip: usize) -> Result<String, PeekError> {
 		let limit = MANAGER.layout.preview_height();
-		let result = Highlighter::new(path.to_owned()).highlight(skip, limit).await?;
+		let result = Markdown::render(path, skip, limit).await?;
 		Ok(result.replace('\t', &" ".repeat(PREVIEW.tab_size as usize)))
 	}
+
+	pub(super) async fn notebook(path: &Path, skip: usize) -> Result<String, PeekError> {
+		let limit = MANAGER.layout.preview_height();
+		match Notebook::render(path, skip, limit).await {
+			Ok(s) => Ok(s.replace('\t', &" ".repeat(PREVIEW.tab_size as usize))),
+			Err(PeekError::Unexpected(_)) => Self::json(path, skip).await,
+			Err(e) => Err(e),
+		}
+	}
+
+	pub(super) async fn document(
+		path: &Path,
+		mtime: Option<SystemTime>,
+		skip: usize,
+	) -> Result<String, PeekError> {
+		let from = match path.extension().map(|e| e.to_ascii_lowercase()) {
+			Some(e) if e == "epub" || e == "docx" || e == "odt" || e == "rtf" || e == "fb2" => e,
+			_ => return Self::document_fallback(path, skip).await,
+		};
+
+		// Conversion is slow, keep the text around for paging through it
+		let cache = PREVIEW.cache_with(path, mtime, 0, "document");
+		let text = match fs::read_to_string(&cache).await {
+			Ok(s) => s,
+			Err(_) => match external::pandoc(path, &from.to_string_lossy()).await {
+				Ok(s) => {
+					fs::write(&cache, &s).await.ok();
+					s
+				}
+				Err(_) => return Self::document_fallback(path, skip).await,
+			},
+		};
+
+		let limit = MANAGER.layout.preview_height();
+		let lines: Vec<_> = text.trim_start_matches('\n').lines().collect();
+		if skip > 0 && lines.len() < skip + limit {
+			return Err(PeekError::Exceed(lines.len().saturating_sub(limit)));
+		}
+
+		let s = lines.into_iter().skip(skip).take(limit).collect::<Vec<_>>().join("\n");
+		let s = s.replace('\t', &" ".repeat(PREVIEW.tab_size as usize));
+		Ok(if PREVIEW.line_numbers || PREVIEW.wrap {
+			let wrap = Some(MANAGER.layout.preview_rect().width as usize).filter(|_| PREVIEW.wrap);
+			Self::layout(&s, skip, limit, PREVIEW.line_numbers, wrap)
+		} else {
+			s
+		})
+	}
+
+	// Most documents are zip containers, list them like archives when they can't
+	// be converted
+	async fn document_fallback(path: &Path, skip: usize) -> Result<String, PeekError> {
+		match Self::archive(path, skip).await {
+			Err(PeekError::Unexpected(_)) => Self::hex(path, skip).await,
+			r => r,
+		}
+	}
+
+	pub(super) async fn table(path: &Path, skip: usize) -> Result<String, PeekError> {
+		let (s, more) = Table::render(path, skip, MANAGER.layout.preview_height()).await?;
+		Ok(if more { Self::footer(&s, "… truncated") } else { s })
+	}
+
+	pub(super) async fn hex(path: &Path, skip: usize) -> Result<String, PeekError> {
+		// Flags and extended attributes, if any, sit above the first page of the dump
+		let header = if skip == 0 { Self::info(path).await.ok() } else { None };
+		let header = header.map(|s| s + "\n\n").unwrap_or_default();
+
+		let limit = MANAGER.layout.preview_height().saturating_sub(header.lines().count());
+		Ok(header + &Hex::render(path, skip, limit).await?)
+	}
+
+	pub(super) async fn info(path: &Path) -> Result<String, PeekError> {
+		let path = path.to_owned();
+		let info = tokio::task::spawn_blocking(move || format_xattrs(&path)).await.unwrap_or_default();
+		if info.is_empty() {
+			return Err("Unsupported mimetype".into());
+		}
+		Ok(info)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_layout() {
+		assert_eq!(
+			Provider::layout("a\nb\n\x1b[0m", 0, 10, true, None),
+			"\x1b[2m1 \x1b[22ma\n\x1b[2m2 \x1b[22mb\n\x1b[0m"
+		);
+		assert_eq!(
+			Provider::layout("a\nb\n", 8, 10, true, None),
+			"\x1b[2m 9 \x1b[22ma\n\x1b[2m10 \x1b[22mb\n\x1b[0m"
+		);
+		assert_eq!(Provider::layout("abcdefgh\n", 0, 10, false, Some(6)), "abcdef\n  gh\n\x1b[0m");
+		assert_eq!(Provider::layout("abcdefgh\nij\n", 0, 1, false, Some(6)), "abcdef\n\x1b[0m");
+	}
+
+	#[test]
+	fn test_footer() {
+		assert_eq!(Provider::footer("a\nb\nc\n\x1b[0m", "…"), "a\nb\x1b[0m\n\x1b[2m…\x1b[22m");
+		assert_eq!(Provider::footer("a", "…"), "\x1b[2m…\x1b[22m");
+	}
+
+	#[test]
+	fn test_wrap() {
+		assert_eq!(Provider::wrap("abcdefgh", 6), ["abcdef", "  gh"]);
+		assert_eq!(Provider::wrap("你好世界", 6), ["你好世", "  界"]);
+		assert_eq!(Provider::wrap("\x1b[31mabcdefg\x1b[0m", 6), [
+			"\x1b[31mabcdef\x1b[0m",
+			"  \x1b[31mg\x1b[0m"
+		]);
+		assert_eq!(Provider::wrap("\x1b[31mab\x1b[32mcd\x1b[1mefg", 6), [
+			"\x1b[31mab\x1b[32mcd\x1b[1mef\x1b[0m",
+			"  \x1b[1;32mg"
+		]);
+	}
 }
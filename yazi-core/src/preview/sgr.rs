@@ -0,0 +1,77 @@
+// The graphic rendition in effect at a point of a line, so that a continuation
+// line can start with just that, rather than every escape seen before it
+#[derive(Default)]
+pub(super) struct Sgr {
+	attrs: Vec<u8>,
+	fg:    Option<String>,
+	bg:    Option<String>,
+}
+
+impl Sgr {
+	// Applies the parameters of a `\x1b[...m` sequence
+	pub(super) fn apply(&mut self, params: &str) {
+		let mut it = params.split(';');
+		while let Some(p) = it.next() {
+			match p.parse::<u8>().unwrap_or(0) {
+				0 => *self = Self::default(),
+				n @ (38 | 48) => {
+					let len = match it.next() {
+						Some("5") => 1,
+						Some("2") => 3,
+						_ => continue,
+					};
+					let mut color = format!("{n};{}", if len == 1 { 5 } else { 2 });
+					for _ in 0..len {
+						color.push(';');
+						color.push_str(it.next().unwrap_or("0"));
+					}
+					*if n == 38 { &mut self.fg } else { &mut self.bg } = Some(color);
+				}
+				39 => self.fg = None,
+				49 => self.bg = None,
+				n @ (30..=37 | 90..=97) => self.fg = Some(n.to_string()),
+				n @ (40..=47 | 100..=107) => self.bg = Some(n.to_string()),
+				n @ 1..=9 if !self.attrs.contains(&n) => self.attrs.push(n),
+				22 => self.attrs.retain(|&a| a != 1 && a != 2),
+				n @ 23..=29 => self.attrs.retain(|&a| a != n - 20),
+				_ => {}
+			}
+		}
+	}
+
+	#[inline]
+	pub(super) fn is_empty(&self) -> bool {
+		self.attrs.is_empty() && self.fg.is_none() && self.bg.is_none()
+	}
+
+	pub(super) fn escape(&self) -> String {
+		if self.is_empty() {
+			return String::new();
+		}
+
+		let attrs = self.attrs.iter().map(|a| a.to_string());
+		let params: Vec<_> = attrs.chain(self.fg.clone()).chain(self.bg.clone()).collect();
+		format!("\x1b[{}m", params.join(";"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_apply() {
+		let mut sgr = Sgr::default();
+		for params in ["1", "38;2;1;2;3", "48;5;42", "38;2;4;5;6", "3", "23"] {
+			sgr.apply(params);
+		}
+		assert_eq!(sgr.escape(), "\x1b[1;38;2;4;5;6;48;5;42m");
+
+		sgr.apply("22;39");
+		assert_eq!(sgr.escape(), "\x1b[48;5;42m");
+
+		sgr.apply("");
+		assert!(sgr.is_empty());
+		assert_eq!(sgr.escape(), "");
+	}
+}
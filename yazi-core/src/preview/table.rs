@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use tokio::{fs::File, io::{AsyncBufReadExt, BufReader}};
+use unicode_width::UnicodeWidthChar;
+use yazi_shared::PeekError;
+
+const MAX_COLUMNS: usize = 64;
+const MAX_CELL_WIDTH: usize = 32;
+
+pub(super) struct Table;
+
+impl Table {
+	// Also tells whether there are more rows after this page
+	pub(super) async fn render(
+		path: &Path,
+		skip: usize,
+		limit: usize,
+	) -> Result<(String, bool), PeekError> {
+		let mut reader = BufReader::new(File::open(path).await?).lines();
+
+		let Some(first) = reader.next_line().await? else {
+			return Ok((String::new(), false));
+		};
+		let delimiter = Self::delimiter(&first);
+
+		// The header row is always kept, the rest scroll with `skip`
+		let mut rows = vec![Self::split(&first, delimiter)];
+		let (mut i, mut more) = (0, false);
+		while let Some(line) = reader.next_line().await? {
+			i += 1;
+			if i > skip + limit.saturating_sub(1) {
+				more = true;
+				break;
+			}
+			if i > skip {
+				rows.push(Self::split(&line, delimiter));
+			}
+		}
+
+		if skip > 0 && i < skip + limit.saturating_sub(1) {
+			return Err(PeekError::Exceed(i.saturating_sub(limit.saturating_sub(1))));
+		}
+
+		let mut widths = vec![0; rows.iter().map(|r| r.len()).max().unwrap_or(0)];
+		for row in &rows {
+			for (w, cell) in widths.iter_mut().zip(row) {
+				*w = (*w).max(Self::width(cell).min(MAX_CELL_WIDTH));
+			}
+		}
+
+		let mut lines = Vec::with_capacity(rows.len());
+		for (i, row) in rows.iter().enumerate() {
+			let mut line = String::new();
+			for (j, cell) in row.iter().enumerate() {
+				if j > 0 {
+					line.push_str(" │ ");
+				}
+				line.push_str(&Self::pad(cell, widths[j]));
+			}
+
+			let line = line.trim_end();
+			lines.push(if i == 0 { format!("\x1b[1m{line}\x1b[0m") } else { line.to_owned() });
+		}
+		Ok((lines.join("\n"), more))
+	}
+
+	fn delimiter(line: &str) -> char {
+		[',', '\t', ';']
+			.into_iter()
+			.max_by_key(|&d| line.matches(d).count())
+			.filter(|&d| line.contains(d))
+			.unwrap_or(',')
+	}
+
+	fn split(line: &str, delimiter: char) -> Vec<String> {
+		let mut cells = vec![];
+		let mut cell = String::new();
+
+		let (mut quoted, mut it) = (false, line.chars().peekable());
+		while let Some(c) = it.next() {
+			match c {
+				'"' if quoted && it.peek() == Some(&'"') => {
+					cell.push('"');
+					it.next();
+				}
+				'"' if quoted || cell.is_empty() => quoted = !quoted,
+				c if c == delimiter && !quoted => {
+					cells.push(std::mem::take(&mut cell));
+					if cells.len() >= MAX_COLUMNS {
+						return cells;
+					}
+				}
+				c => cell.push(c),
+			}
+		}
+
+		cells.push(cell);
+		cells
+	}
+
+	#[inline]
+	fn width(s: &str) -> usize { s.chars().map(|c| c.width().unwrap_or(0)).sum() }
+
+	fn pad(cell: &str, width: usize) -> String {
+		let mut s = String::with_capacity(width);
+		let mut w = 0;
+		for c in cell.chars() {
+			let cw = c.width().unwrap_or(0);
+			if w + cw > width || (w + cw == width && Self::width(cell) > width) {
+				s.push('…');
+				w += 1;
+				break;
+			}
+			s.push(c);
+			w += cw;
+		}
+
+		s.push_str(&" ".repeat(width.saturating_sub(w)));
+		s
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_split() {
+		assert_eq!(Table::split(r#"a,"b,c",d"#, ','), ["a", "b,c", "d"]);
+		assert_eq!(Table::split(r#""x ""y""";z"#, ';'), ["x \"y\"", "z"]);
+		assert_eq!(Table::delimiter("a\tb\tc,d"), '\t');
+	}
+
+	#[test]
+	fn test_pad() {
+		assert_eq!(Table::pad("abc", 5), "abc  ");
+		assert_eq!(Table::pad("abcdef", 4), "abc…");
+	}
+}
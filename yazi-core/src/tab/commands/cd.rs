@@ -80,15 +80,27 @@ impl Tab {
 			while let Some(result) = rx.next().await {
 				match result {
 					Ok(s) => {
-						let u = Url::from(expand_path(s));
-						let Ok(meta) = fs::metadata(&u).await else {
-							return;
+						let mut u = Url::from(s);
+						if u.is_regular() {
+							u.set_path(expand_path(&u));
+						}
+
+						// Walk up to the nearest existing ancestor if the path doesn't exist
+						let mut target = u.clone();
+						let meta = loop {
+							if let Ok(meta) = fs::metadata(&target).await {
+								break meta;
+							}
+							let Some(parent) = target.parent_url() else {
+								return;
+							};
+							target = parent;
 						};
 
 						if meta.is_dir() {
-							Tab::_cd(&u);
+							Tab::_cd(&target);
 						} else {
-							Tab::_reveal(&u);
+							Tab::_reveal(&target);
 						}
 					}
 					Err(InputError::Completed(before, ticket)) => {
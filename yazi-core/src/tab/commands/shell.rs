@@ -40,12 +40,15 @@ impl Tab {
 			Tasks::_open(
 				selected,
 				Some(Opener {
-					exec:   opt.cmd,
-					block:  opt.block,
-					orphan: false,
-					desc:   Default::default(),
-					for_:   None,
-					spread: true,
+					exec:       opt.cmd,
+					block:      opt.block,
+					orphan:     false,
+					desc:       Default::default(),
+					for_:       None,
+					spread:     true,
+					max_spread: None,
+					env:        Default::default(),
+					cwd:        None,
 				}),
 			);
 		});
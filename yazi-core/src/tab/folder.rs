@@ -1,6 +1,6 @@
 use ratatui::layout::Rect;
 use yazi_config::MANAGER;
-use yazi_shared::{emit, fs::{File, FilesOp}, fs::Url};
+use yazi_shared::{emit, fs::{File, FilesOp, GitStatus, Url}};
 
 use crate::{files::Files, Step};
 
@@ -29,6 +29,8 @@ impl Folder {
 			FilesOp::Full(_, items) => self.files.update_full(items),
 			FilesOp::Part(_, ticket, items) => self.files.update_part(ticket, items),
 			FilesOp::Size(_, items) => self.files.update_size(items),
+			FilesOp::Sizing(_, items) => self.files.update_sizing(items),
+			FilesOp::Git(_, items) => self.files.update_git(items),
 
 			FilesOp::Creating(_, items) => self.files.update_creating(items),
 			FilesOp::Deleting(_, items) => self.files.update_deleting(items),
@@ -131,6 +133,12 @@ impl Folder {
 	#[inline]
 	pub fn hovered(&self) -> Option<&File> { self.files.get(self.cursor) }
 
+	// Entries of a directory that's untracked or ignored as a whole are too
+	#[inline]
+	pub fn git(&self, url: &Url) -> Option<GitStatus> {
+		self.files.git.get(url).or_else(|| self.files.git.get(&self.cwd)).copied()
+	}
+
 	pub fn paginate(&self, page: usize) -> &[File] {
 		let len = self.files.len();
 		let limit = MANAGER.layout.folder_height();
@@ -2,7 +2,8 @@ use std::{borrow::Cow, collections::BTreeMap};
 
 use anyhow::Result;
 use tokio::task::JoinHandle;
-use yazi_shared::{event::PreviewLock, fs::File, fs::Url};
+use yazi_adaptor::ADAPTOR;
+use yazi_shared::{event::PreviewLock, fs::{File, Url}};
 
 use super::{Backstack, Config, Finder, Folder, Mode};
 use crate::preview::Preview;
@@ -56,6 +57,12 @@ impl Tab {
 			return false;
 		}
 
+		// Don't leave the placement of an image behind, if it got placed after being
+		// hidden
+		if !lock.is_image() && self.preview.lock.as_ref().is_some_and(|l| l.is_image()) {
+			ADAPTOR.image_erase().ok();
+		}
+
 		self.preview.lock = Some(lock);
 		true
 	}
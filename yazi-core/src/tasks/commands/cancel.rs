@@ -2,15 +2,21 @@ use yazi_shared::event::Exec;
 
 use crate::tasks::Tasks;
 
-pub struct Opt;
+pub struct Opt {
+	id: Option<usize>,
+}
 
 impl From<&Exec> for Opt {
-	fn from(_: &Exec) -> Self { Self }
+	fn from(e: &Exec) -> Self { Self { id: e.args.first().and_then(|s| s.parse().ok()) } }
+}
+impl From<usize> for Opt {
+	fn from(id: usize) -> Self { Self { id: Some(id) } }
 }
 
 impl Tasks {
-	pub fn cancel(&mut self, _: impl Into<Opt>) -> bool {
-		let id = self.scheduler.running.read().get_id(self.cursor);
+	pub fn cancel(&mut self, opt: impl Into<Opt>) -> bool {
+		let opt = opt.into() as Opt;
+		let id = opt.id.or_else(|| self.scheduler.running.read().get_id(self.cursor));
 		if id.map(|id| self.scheduler.cancel(id)) != Some(true) {
 			return false;
 		}
@@ -0,0 +1,39 @@
+use yazi_config::{popup::InputCfg, TASKS};
+use yazi_shared::event::Exec;
+
+use crate::{input::Input, tasks::Tasks};
+
+pub struct Opt;
+
+impl From<&Exec> for Opt {
+	fn from(_: &Exec) -> Self { Self }
+}
+
+impl Tasks {
+	pub fn escalate(&mut self, _: impl Into<Opt>) -> bool {
+		if TASKS.escalate.is_empty() {
+			return false;
+		}
+
+		let running = self.scheduler.running.read();
+		let Some((id, n)) =
+			running.get_id(self.cursor).and_then(|id| running.get(id)).map(|t| (t.id, t.denied.len()))
+		else {
+			return false;
+		};
+		if n == 0 {
+			return false;
+		}
+
+		let scheduler = self.scheduler.clone();
+		tokio::spawn(async move {
+			let mut result = Input::_show(InputCfg::escalate(n, &TASKS.escalate));
+			if let Some(Ok(choice)) = result.recv().await {
+				if choice == "y" || choice == "Y" {
+					scheduler.file_escalate(id);
+				}
+			}
+		});
+		false
+	}
+}
@@ -1,5 +1,6 @@
 mod arrow;
 mod cancel;
+mod escalate;
 mod inspect;
 mod open;
 mod toggle;
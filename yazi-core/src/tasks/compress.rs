@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use yazi_config::popup::InputCfg;
+use yazi_scheduler::{external::{self, ArchiveFormat}, Scheduler};
+use yazi_shared::fs::{expand_path, name_error, path_within, Url};
+
+use super::Tasks;
+use crate::input::Input;
+
+impl Tasks {
+	// Packs `sources` into the archive `to`, of the format its extension names,
+	// asking for it if it's not given. Symlinks are stored as they are unless
+	// `dereference`.
+	pub fn file_compress(&self, sources: Vec<Url>, to: Option<Url>, dereference: bool) -> bool {
+		// Entries are named relative to the directory holding them all
+		let Some(cwd) = sources.first().and_then(|u| u.parent_url()) else {
+			return false;
+		};
+
+		let scheduler = self.scheduler.clone();
+		tokio::spawn(async move {
+			let to = match to {
+				Some(to) => to,
+				None => {
+					// Suggests an archive named after the file, or the directory for several
+					let stem = if sources.len() == 1 { sources[0].file_name() } else { cwd.file_name() };
+					let value = format!("{}.zip", stem.unwrap_or_default().to_string_lossy());
+
+					let mut result = Input::_show(
+						InputCfg::compress()
+							.with_value(value)
+							.with_validator(|name| name_error(name, true).map(|e| e.to_owned())),
+					);
+					match result.recv().await {
+						Some(Ok(name)) => cwd.join(expand_path(name)),
+						_ => return,
+					}
+				}
+			};
+
+			let Some((format, to)) = Self::file_compress_check(&scheduler, &sources, &cwd, to) else {
+				return;
+			};
+			let (to, force) = match Self::resolve(to, &mut None).await {
+				Some(Some(r)) => r,
+				_ => return,
+			};
+			let names = sources.iter().filter_map(|u| u.file_name()).map(|n| n.to_owned()).collect();
+			scheduler.file_compress(format, cwd, names, to, force, dereference);
+		});
+		false
+	}
+
+	fn file_compress_check(
+		scheduler: &Arc<Scheduler>,
+		sources: &[Url],
+		cwd: &Url,
+		to: Url,
+	) -> Option<(ArchiveFormat, Url)> {
+		let name = format!("Compress to {:?}", to);
+		let Some(format) = to.file_name().and_then(|n| ArchiveFormat::from_name(&n.to_string_lossy()))
+		else {
+			let reason = "Unsupported format, the archive must end with .zip, .tar.gz or .tar.zst";
+			scheduler.file_refuse(name, reason.to_owned());
+			return None;
+		};
+		if !external::available(format.tool()) {
+			scheduler.file_refuse(name, format!("Install {} for creating this archive", format.tool()));
+			return None;
+		}
+
+		if sources.iter().any(|u| u.parent() != Some(cwd)) {
+			scheduler.file_refuse(name, "The files must all be in the same directory".to_owned());
+			return None;
+		}
+		if let Some(u) = sources.iter().find(|u| path_within(to.parent().unwrap_or(&to), u)) {
+			scheduler.file_refuse(name, format!("The archive can't go into {:?}, which it packs", u));
+			return None;
+		}
+		Some((format, to))
+	}
+}
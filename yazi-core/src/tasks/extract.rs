@@ -0,0 +1,137 @@
+use std::{collections::{BTreeSet, HashSet}, path::{Component, Path, PathBuf}};
+
+use yazi_scheduler::external;
+use yazi_shared::fs::Url;
+
+use super::Tasks;
+
+impl Tasks {
+	// Extracts each of `archives` into a subfolder of `dest` named after it, or
+	// with `here`, right into `dest`. Whatever is already there goes through the
+	// same conflict prompt as pasting.
+	pub fn file_extract(&self, archives: Vec<Url>, dest: &Url, here: bool) -> bool {
+		let (scheduler, dest) = (self.scheduler.clone(), dest.clone());
+		tokio::spawn(async move {
+			let mut all = None;
+			for archive in archives {
+				let name = format!("Extract {:?}", archive);
+				if !external::available("unar") {
+					scheduler.file_refuse(name, "Install unar for extracting archives".to_owned());
+					continue;
+				}
+
+				let (files, _, length) = match external::lsar(&archive, 0, usize::MAX).await {
+					Ok(r) => r,
+					Err(e) => {
+						scheduler.file_refuse(name, format!("Failed to list the archive: {e}"));
+						continue;
+					}
+				};
+
+				let entries: Vec<_> = files.iter().map(|f| (f.name.as_str(), f.link.is_some())).collect();
+				if let Some(bad) = Self::extract_unsafe(&entries) {
+					let reason = format!("Refused, {bad:?} would be written outside of the destination");
+					scheduler.file_refuse(name, reason);
+					continue;
+				}
+
+				let mut moves = vec![];
+				if here {
+					for top in Self::extract_tops(&entries) {
+						match Self::resolve(dest.join(&top), &mut all).await {
+							Some(Some((to, force))) => moves.push((PathBuf::from(top), to, force)),
+							Some(None) => {}
+							None => return,
+						}
+					}
+				} else {
+					let stem = Self::extract_stem(&archive.file_name().unwrap_or_default().to_string_lossy());
+					match Self::resolve(dest.join(stem), &mut all).await {
+						Some(Some((to, force))) => moves.push((PathBuf::new(), to, force)),
+						Some(None) => {}
+						None => return,
+					}
+				}
+
+				if !moves.is_empty() {
+					scheduler.file_extract(archive, dest.clone(), moves, length);
+				}
+			}
+		});
+		false
+	}
+
+	// The first entry that'd land outside of where it's extracted to: an absolute
+	// path, one with `..` in it, or one that goes through a symlink in the archive
+	fn extract_unsafe<'a>(entries: &[(&'a str, bool)]) -> Option<&'a str> {
+		let links: HashSet<_> = entries
+			.iter()
+			.filter(|(_, link)| *link)
+			.map(|(name, _)| name.trim_end_matches('/'))
+			.collect();
+
+		entries.iter().map(|&(name, _)| name).find(|name| {
+			let path = Path::new(name);
+			if path.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+				return true;
+			}
+			path.ancestors().skip(1).any(|p| p.to_str().is_some_and(|p| links.contains(p)))
+		})
+	}
+
+	// Names at the top of the archive, which are what get placed in the destination
+	fn extract_tops(entries: &[(&str, bool)]) -> BTreeSet<String> {
+		entries
+			.iter()
+			.filter_map(|(name, _)| {
+				Path::new(name).components().find(|c| matches!(c, Component::Normal(_)))
+			})
+			.map(|c| c.as_os_str().to_string_lossy().into_owned())
+			.collect()
+	}
+
+	// The archive's name without its extension, `.tar.*` taken as a whole
+	fn extract_stem(name: &str) -> String {
+		let mut stem = name.rsplit_once('.').map_or(name, |(s, _)| s);
+		if stem.len() > 4 && stem.get(stem.len() - 4..).is_some_and(|s| s.eq_ignore_ascii_case(".tar"))
+		{
+			stem = &stem[..stem.len() - 4];
+		}
+		if stem.is_empty() || stem == name {
+			format!("{name}.d")
+		} else {
+			stem.to_owned()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_extract_unsafe() {
+		assert_eq!(Tasks::extract_unsafe(&[("a/b.txt", false), ("./c", false)]), None);
+		assert_eq!(Tasks::extract_unsafe(&[("a", false), ("a/../../b", false)]), Some("a/../../b"));
+		assert_eq!(Tasks::extract_unsafe(&[("/etc/passwd", false)]), Some("/etc/passwd"));
+		assert_eq!(Tasks::extract_unsafe(&[("l", true), ("l/passwd", false)]), Some("l/passwd"));
+		assert_eq!(Tasks::extract_unsafe(&[("l", true), ("lib/a", false)]), None);
+	}
+
+	#[test]
+	fn test_extract_tops() {
+		let tops = Tasks::extract_tops(&[("b/", false), ("b/c", false), ("./a", false), ("b", false)]);
+		assert_eq!(tops.into_iter().collect::<Vec<_>>(), ["a", "b"]);
+	}
+
+	#[test]
+	fn test_extract_stem() {
+		assert_eq!(Tasks::extract_stem("foo.zip"), "foo");
+		assert_eq!(Tasks::extract_stem("foo.bar.tar.gz"), "foo.bar");
+		assert_eq!(Tasks::extract_stem("foo.TAR.xz"), "foo");
+		assert_eq!(Tasks::extract_stem("foo.tgz"), "foo");
+		assert_eq!(Tasks::extract_stem("foo"), "foo.d");
+		assert_eq!(Tasks::extract_stem(".zip"), ".zip.d");
+		assert_eq!(Tasks::extract_stem("日本.zip"), "日本");
+	}
+}
@@ -1,7 +1,12 @@
 mod commands;
+mod compress;
+mod extract;
+mod notifier;
+mod plan;
 mod progress;
 mod tasks;
 
+use notifier::*;
 pub use progress::*;
 pub use tasks::*;
 
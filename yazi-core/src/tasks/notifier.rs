@@ -0,0 +1,103 @@
+use std::{collections::{HashMap, HashSet}, mem};
+
+use yazi_scheduler::{Task, TaskKind};
+
+// Follows the user tasks across progress updates, to tell when all of them
+// are done, along with which ones finished and which ones failed meanwhile.
+#[derive(Default)]
+pub(super) struct TasksNotifier {
+	seen:   HashMap<usize, String>,
+	done:   Vec<String>,
+	failed: Vec<String>,
+	busy:   bool,
+}
+
+impl TasksNotifier {
+	// Returns the summary and body of a notification, once nothing is left to do
+	pub(super) fn tick<'a>(
+		&mut self,
+		tasks: impl Iterator<Item = &'a Task>,
+	) -> Option<(String, String)> {
+		let mut present = HashSet::new();
+		let mut pending = false;
+		for task in tasks.filter(|t| t.kind == TaskKind::User) {
+			present.insert(task.id);
+
+			// A failed task stays in the list until it's cancelled
+			if task.fail > 0 && task.succ + task.fail >= task.total {
+				if self.seen.remove(&task.id).is_some() {
+					self.failed.push(task.name.clone());
+				}
+			} else {
+				pending = true;
+				self.seen.entry(task.id).or_insert_with(|| task.name.clone());
+			}
+		}
+
+		// Whatever left the list otherwise has finished
+		let done = &mut self.done;
+		self.seen.retain(|id, name| {
+			if present.contains(id) {
+				return true;
+			}
+			done.push(mem::take(name));
+			false
+		});
+
+		if pending {
+			self.busy = true;
+			return None;
+		}
+		if !mem::take(&mut self.busy) || self.done.is_empty() && self.failed.is_empty() {
+			return None;
+		}
+
+		let (done, failed) = (mem::take(&mut self.done), mem::take(&mut self.failed));
+		let summary = match (done.len(), failed.len()) {
+			(n, 0) => format!("{n} task{} done", if n == 1 { "" } else { "s" }),
+			(0, f) => format!("{f} task{} failed", if f == 1 { "" } else { "s" }),
+			(n, f) => format!("{n} task{} done, {f} failed", if n == 1 { "" } else { "s" }),
+		};
+
+		const LINES: usize = 5;
+		let all: Vec<_> = failed.iter().map(|s| format!("Failed: {s}")).chain(done).collect();
+		let mut body = all.iter().take(LINES).cloned().collect::<Vec<_>>().join("\n");
+		if all.len() > LINES {
+			body.push_str(&format!("\n…and {} more", all.len() - LINES));
+		}
+		Some((summary, body))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn task(id: usize, total: u32, succ: u32, fail: u32) -> Task {
+		let mut task = Task::new(id, TaskKind::User, format!("Task {id}"));
+		(task.total, task.succ, task.fail) = (total, succ, fail);
+		task
+	}
+
+	#[test]
+	fn test_tick() {
+		let mut n = TasksNotifier::default();
+		assert_eq!(n.tick([].iter()), None);
+
+		let (a, b) = (task(1, 2, 1, 0), task(2, 3, 1, 0));
+		assert_eq!(n.tick([&a, &b].into_iter()), None);
+
+		// One failed, the other one finished and left the list
+		let b = task(2, 3, 2, 1);
+		assert_eq!(
+			n.tick([&b].into_iter()),
+			Some(("1 task done, 1 failed".to_owned(), "Failed: Task 2\nTask 1".to_owned()))
+		);
+
+		// Only reported once, and preloads don't count
+		assert_eq!(n.tick([&b].into_iter()), None);
+		let mut p = Task::new(3, TaskKind::Preload, String::new());
+		p.total = 1;
+		assert_eq!(n.tick([&b, &p].into_iter()), None);
+	}
+}
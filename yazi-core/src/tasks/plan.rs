@@ -0,0 +1,120 @@
+use std::{collections::HashSet, future::Future};
+
+use tokio::fs;
+use yazi_config::popup::SelectCfg;
+use yazi_shared::fs::{path_relative_to, path_within, same_file, unique_files, unique_path, Url};
+
+use super::Tasks;
+use crate::select::Select;
+
+// What an operation would do, item by item, listed in a popup instead of being
+// scheduled; nothing on the filesystem is changed.
+impl Tasks {
+	pub(super) fn file_plan(lines: impl Future<Output = Vec<String>> + Send + 'static) -> bool {
+		tokio::spawn(async move {
+			let lines = lines.await;
+			let title =
+				format!("Dry run, {} item{}:", lines.len(), if lines.len() == 1 { "" } else { "s" });
+			Select::_show(SelectCfg::review(title, lines)).await.ok();
+		});
+		false
+	}
+
+	pub(super) async fn plan_paste(
+		src: HashSet<Url>,
+		dest: Url,
+		cut: bool,
+		force: bool,
+	) -> Vec<String> {
+		let verb = if cut { "Move" } else { "Copy" };
+		let mut lines = Vec::with_capacity(src.len());
+
+		let (src, dupes) = unique_files(sorted(src));
+		for u in dupes {
+			lines.push(format!("Skip {}, it's the same file as another source", u.display()));
+		}
+		for u in src {
+			let to = dest.join(u.file_name().unwrap());
+			let line = if fs::symlink_metadata(&u).await.is_err() {
+				format!("Skip {}, it no longer exists", u.display())
+			} else if path_within(&dest, &u) {
+				format!("Refuse {}, it can't go into itself", u.display())
+			} else if force && same_file(&u, &to) {
+				format!("Skip {}, it's the same file", u.display())
+			} else if fs::symlink_metadata(&to).await.is_err() {
+				format!("{verb} {} → {}", u.display(), to.display())
+			} else if force {
+				format!("{verb} {} → {}, overwriting it", u.display(), to.display())
+			} else {
+				format!("{verb} {} → {}, which exists and will be asked about", u.display(), to.display())
+			};
+			lines.push(line);
+		}
+		lines
+	}
+
+	pub(super) async fn plan_link(
+		src: HashSet<Url>,
+		dest: Url,
+		relative: bool,
+		dereference: bool,
+		force: bool,
+	) -> Vec<String> {
+		let mut lines = Vec::with_capacity(src.len());
+		for u in sorted(src) {
+			let mut to = dest.join(u.file_name().unwrap());
+			if force && same_file(&u, &to) {
+				lines.push(format!("Skip {}, it's the same file", u.display()));
+				continue;
+			}
+
+			let mut target = u.to_path_buf();
+			if dereference {
+				match fs::canonicalize(&u).await {
+					Ok(p) => target = p,
+					Err(_) => {
+						lines.push(format!("Skip {}, its target doesn't exist", u.display()));
+						continue;
+					}
+				}
+			}
+			if relative {
+				if let Ok(dir) = fs::canonicalize(&dest).await {
+					target = path_relative_to(&target, &dir).into_owned();
+				}
+			}
+
+			let mut line = format!("Link {} → {}", to.display(), target.display());
+			if fs::symlink_metadata(&to).await.is_ok() {
+				if force {
+					line.push_str(", replacing it");
+				} else {
+					to = unique_path(to).await;
+					line = format!("Link {} → {}, as the name is taken", to.display(), target.display());
+				}
+			}
+			lines.push(line);
+		}
+		lines
+	}
+
+	pub(super) async fn plan_remove(targets: Vec<Url>, permanently: bool) -> Vec<String> {
+		let verb = if permanently { "Delete" } else { "Trash" };
+		let mut lines = Vec::with_capacity(targets.len());
+		for u in targets {
+			lines.push(match fs::symlink_metadata(&u).await {
+				Ok(m) if m.is_dir() => format!("{verb} {}, with everything inside", u.display()),
+				Ok(_) => format!("{verb} {}", u.display()),
+				Err(_) => format!("Skip {}, it no longer exists", u.display()),
+			});
+		}
+		lines
+	}
+}
+
+#[inline]
+fn sorted(src: HashSet<Url>) -> Vec<Url> {
+	let mut src: Vec<_> = src.into_iter().collect();
+	src.sort_unstable();
+	src
+}
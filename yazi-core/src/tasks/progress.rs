@@ -9,6 +9,8 @@ pub struct TasksProgress {
 
 	pub found:     u64,
 	pub processed: u64,
+
+	pub speed: u64,
 }
 
 impl From<&Running> for TasksProgress {
@@ -25,6 +27,7 @@ impl From<&Running> for TasksProgress {
 
 			progress.found += task.found;
 			progress.processed += task.processed;
+			progress.speed += task.speed as u64;
 		}
 		progress
 	}
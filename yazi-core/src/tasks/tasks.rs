@@ -1,26 +1,36 @@
-use std::{collections::{BTreeMap, HashMap, HashSet}, ffi::OsStr, path::Path, sync::Arc, time::Duration};
+use std::{collections::{BTreeMap, HashMap, HashSet}, ffi::OsStr, mem, path::Path, sync::Arc, time::{Duration, Instant, SystemTime}};
 
-use tokio::time::sleep;
+use parking_lot::Mutex;
+use tokio::{fs, sync::mpsc, time::{sleep, timeout}};
 use tracing::debug;
-use yazi_config::{manager::SortBy, open::Opener, popup::InputCfg, OPEN};
-use yazi_scheduler::{Scheduler, TaskSummary};
-use yazi_shared::{fs::{File, Url}, term::Term, MimeKind};
+use yazi_config::{manager::SortBy, open::Opener, popup::InputCfg, OPEN, TASKS};
+use yazi_scheduler::{external, Scheduler, TaskSummary};
+use yazi_shared::{fs::{calculate_size, name_error, path_within, same_file, unique_files, unique_path_with, File, Url}, readable_size, term::Term, MimeKind};
 
-use super::{TasksProgress, TASKS_PADDING, TASKS_PERCENT};
-use crate::{files::Files, input::Input};
+use super::{TasksNotifier, TasksProgress, TASKS_PADDING, TASKS_PERCENT};
+use crate::{input::Input, tab::Folder};
 
 pub struct Tasks {
 	pub(super) scheduler: Arc<Scheduler>,
+	trashed:              Arc<Mutex<Vec<Vec<Url>>>>,
 
 	pub visible:  bool,
 	pub cursor:   usize,
 	pub progress: TasksProgress,
 }
 
+#[derive(Clone, Copy)]
+pub(super) enum Conflict {
+	Overwrite,
+	Skip,
+	Rename,
+}
+
 impl Tasks {
 	pub fn start() -> Self {
 		let tasks = Self {
 			scheduler: Arc::new(Scheduler::start()),
+			trashed:   Default::default(),
 			visible:   false,
 			cursor:    0,
 			progress:  Default::default(),
@@ -28,11 +38,25 @@ impl Tasks {
 
 		let running = tasks.scheduler.running.clone();
 		tokio::spawn(async move {
-			let mut last = TasksProgress::default();
+			let (mut last, mut tick) = (TasksProgress::default(), Instant::now());
+			let mut notifier = TasksNotifier::default();
 			loop {
-				sleep(Duration::from_millis(500)).await;
+				sleep(Duration::from_millis(TASKS.progress_interval)).await;
+
+				let mut running = running.write();
+				running.sample(mem::replace(&mut tick, Instant::now()).elapsed());
+
+				let new = TasksProgress::from(&*running);
+				let drained = if TASKS.notify { notifier.tick(running.values()) } else { None };
+				drop(running);
 
-				let new = TasksProgress::from(&*running.read());
+				if let Some((summary, body)) = drained {
+					tokio::spawn(async move {
+						if let Err(e) = external::desktop_notify(&summary, &body).await {
+							debug!("Failed to send the desktop notification: {e}");
+						}
+					});
+				}
 				if last != new {
 					last = new;
 					Tasks::_update(new);
@@ -68,7 +92,10 @@ impl Tasks {
 
 	pub fn file_open_with(&self, opener: &Opener, args: &[impl AsRef<OsStr>]) -> bool {
 		if opener.spread {
-			self.scheduler.process_open(opener, args);
+			match opener.max_spread {
+				Some(n) => args.chunks(n).for_each(|c| self.scheduler.process_open(opener, c)),
+				None => self.scheduler.process_open(opener, args),
+			}
 			return false;
 		}
 		for target in args {
@@ -77,58 +104,241 @@ impl Tasks {
 		false
 	}
 
-	pub fn file_cut(&self, src: &HashSet<Url>, dest: &Url, force: bool) -> bool {
-		for u in src {
+	pub fn file_cut(
+		&self,
+		src: &HashSet<Url>,
+		dest: &Url,
+		force: bool,
+		verify: bool,
+		dry_run: bool,
+	) -> bool {
+		if dry_run {
+			return Self::file_plan(Self::plan_paste(src.clone(), dest.clone(), true, force));
+		}
+		if !force {
+			return self.file_paste_interactive(src, dest, true, verify);
+		}
+
+		for u in &Self::file_unique(&self.scheduler, src, true) {
 			let to = dest.join(u.file_name().unwrap());
-			if force && u == &to {
+			if Self::file_nested(&self.scheduler, u, &to, true) {
+				continue;
+			} else if force && same_file(u, &to) {
 				debug!("file_cut: same file, skipping {:?}", to);
 			} else {
-				self.scheduler.file_cut(u.clone(), to, force);
+				self.scheduler.file_cut(u.clone(), to, force, verify);
 			}
 		}
 		false
 	}
 
-	pub fn file_copy(&self, src: &HashSet<Url>, dest: &Url, force: bool) -> bool {
-		for u in src {
+	pub fn file_copy(
+		&self,
+		src: &HashSet<Url>,
+		dest: &Url,
+		force: bool,
+		verify: bool,
+		dry_run: bool,
+	) -> bool {
+		if dry_run {
+			return Self::file_plan(Self::plan_paste(src.clone(), dest.clone(), false, force));
+		}
+		if !force {
+			return self.file_paste_interactive(src, dest, false, verify);
+		}
+
+		for u in &Self::file_unique(&self.scheduler, src, false) {
 			let to = dest.join(u.file_name().unwrap());
-			if force && u == &to {
+			if Self::file_nested(&self.scheduler, u, &to, false) {
+				continue;
+			} else if force && same_file(u, &to) {
 				debug!("file_copy: same file, skipping {:?}", to);
 			} else {
-				self.scheduler.file_copy(u.clone(), to, force);
+				self.scheduler.file_copy(u.clone(), to, force, verify);
 			}
 		}
 		false
 	}
 
-	pub fn file_link(&self, src: &HashSet<Url>, dest: &Url, relative: bool, force: bool) -> bool {
+	fn file_paste_interactive(
+		&self,
+		src: &HashSet<Url>,
+		dest: &Url,
+		cut: bool,
+		verify: bool,
+	) -> bool {
+		let (src, dest) = (Self::file_unique(&self.scheduler, src, cut), dest.clone());
+		let scheduler = self.scheduler.clone();
+		tokio::spawn(async move {
+			let mut all = None;
+			for u in src {
+				let to = dest.join(u.file_name().unwrap());
+				if Self::file_nested(&scheduler, &u, &to, cut) {
+					continue;
+				}
+
+				// Pasting into its own directory makes a numbered copy, as without a prompt
+				let resolved =
+					if u == to { Some(Some((to, false))) } else { Self::resolve(to, &mut all).await };
+				let (to, force) = match resolved {
+					Some(Some((to, force))) if !(force && same_file(&u, &to)) => (to, force),
+					Some(_) => continue,
+					None => return,
+				};
+
+				if cut {
+					scheduler.file_cut(u, to, force, verify);
+				} else {
+					scheduler.file_copy(u, to, force, verify);
+				}
+			}
+		});
+		false
+	}
+
+	// Drops the sources that are the same file as another one, so that it isn't
+	// pasted twice, or moved away while it's still being copied
+	fn file_unique(scheduler: &Scheduler, src: &HashSet<Url>, cut: bool) -> Vec<Url> {
+		let mut src: Vec<_> = src.iter().cloned().collect();
+		src.sort_unstable();
+
+		let (kept, dupes) = unique_files(src);
+		let verb = if cut { "Cut" } else { "Copy" };
+		for u in dupes {
+			scheduler.file_refuse(
+				format!("{verb} {:?}", u),
+				format!("Skipped, it's the same file as another source: {:?}", u),
+			);
+		}
+		kept
+	}
+
+	// Refuses to put a directory into its own subtree, where it'd never finish
+	fn file_nested(scheduler: &Scheduler, from: &Url, to: &Url, cut: bool) -> bool {
+		if !path_within(to.parent().unwrap_or(to), from) {
+			return false;
+		}
+
+		let verb = if cut { "Cut" } else { "Copy" };
+		scheduler.file_refuse(
+			format!("{verb} {:?} to {:?}", from, to),
+			format!("Cannot {} a directory into itself: {:?}", verb.to_lowercase(), to),
+		);
+		true
+	}
+
+	// Settles where an item goes if `to` is taken, and whether to overwrite it.
+	// `Some(None)` skips this item, `None` aborts the rest of the operation.
+	pub(super) async fn resolve(to: Url, all: &mut Option<Conflict>) -> Option<Option<(Url, bool)>> {
+		if fs::symlink_metadata(&to).await.is_err() {
+			return Some(Some((to, false)));
+		}
+
+		let choice = match *all {
+			Some(c) => c,
+			None => {
+				let (c, apply) = Self::conflict(&to).await?;
+				if apply {
+					*all = Some(c);
+				}
+				c
+			}
+		};
+
+		Some(match choice {
+			Conflict::Skip => None,
+			Conflict::Overwrite => Some((to, true)),
+			Conflict::Rename => {
+				let suggested = unique_path_with(to.clone(), |i| format!(" ({i})")).await;
+				if all.is_some() {
+					return Some(Some((suggested, false)));
+				}
+
+				let name = suggested.file_name().unwrap().to_string_lossy();
+				let dir = to.parent().unwrap_or(&to).to_owned();
+				let mut result = Input::_show(
+					InputCfg::rename()
+						.with_value(name)
+						.with_validator(|name| name_error(name, false).map(|e| e.to_owned()))
+						.with_taken(dir, None),
+				);
+				match (result.recv().await, to.parent_url()) {
+					(Some(Ok(name)), Some(parent)) if !name.is_empty() => Some((parent.join(name), false)),
+					_ => None,
+				}
+			}
+		})
+	}
+
+	// Returns the choice, and whether it applies to all remaining conflicts;
+	// `None` if the prompt was cancelled, which aborts the rest of the operation.
+	async fn conflict(to: &Url) -> Option<(Conflict, bool)> {
+		let name = to.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+		loop {
+			let mut result = Input::_show(InputCfg::conflict(&name));
+			let Ok(choice) = result.recv().await? else {
+				return None;
+			};
+
+			let conflict = match choice.to_lowercase().as_str() {
+				"o" => Conflict::Overwrite,
+				"s" => Conflict::Skip,
+				"r" => Conflict::Rename,
+				_ => continue,
+			};
+			return Some((conflict, choice.chars().all(char::is_uppercase)));
+		}
+	}
+
+	pub fn file_link(
+		&self,
+		src: &HashSet<Url>,
+		dest: &Url,
+		relative: bool,
+		dereference: bool,
+		force: bool,
+		dry_run: bool,
+	) -> bool {
+		if dry_run {
+			return Self::file_plan(Self::plan_link(
+				src.clone(),
+				dest.clone(),
+				relative,
+				dereference,
+				force,
+			));
+		}
+
 		for u in src {
 			let to = dest.join(u.file_name().unwrap());
-			if force && *u == to {
+			if force && same_file(u, &to) {
 				debug!("file_link: same file, skipping {:?}", to);
 			} else {
-				self.scheduler.file_link(u.clone(), to, relative, force);
+				self.scheduler.file_link(u.clone(), to, relative, dereference, force);
 			}
 		}
 		false
 	}
 
-	pub fn file_remove(&self, targets: Vec<Url>, force: bool, permanently: bool) -> bool {
+	pub fn file_remove(
+		&self,
+		targets: Vec<Url>,
+		force: bool,
+		permanently: bool,
+		dry_run: bool,
+	) -> bool {
+		if dry_run {
+			return Self::file_plan(Self::plan_remove(targets, permanently));
+		}
 		if force {
-			for u in targets {
-				if permanently {
-					self.scheduler.file_delete(u);
-				} else {
-					self.scheduler.file_trash(u);
-				}
-			}
+			Self::file_remove_do(&self.scheduler, &self.trashed, targets, permanently);
 			return false;
 		}
 
-		let scheduler = self.scheduler.clone();
+		let (scheduler, trashed) = (self.scheduler.clone(), self.trashed.clone());
 		tokio::spawn(async move {
 			let mut result = Input::_show(if permanently {
-				InputCfg::delete(targets.len())
+				InputCfg::delete(targets.len(), &Self::removal_size(&targets).await)
 			} else {
 				InputCfg::trash(targets.len())
 			});
@@ -137,92 +347,233 @@ impl Tasks {
 				if choice != "y" && choice != "Y" {
 					return;
 				}
-				for u in targets {
-					if permanently {
-						scheduler.file_delete(u);
-					} else {
-						scheduler.file_trash(u);
-					}
+				Self::file_remove_do(&scheduler, &trashed, targets, permanently);
+			}
+		});
+		false
+	}
+
+	// The total size of `targets`, or as much of it as could be counted in a
+	// moment, so that a huge directory doesn't pass for a small one
+	async fn removal_size(targets: &[Url]) -> String {
+		let mut total = 0;
+		let counted = timeout(Duration::from_millis(500), async {
+			for u in targets {
+				total += calculate_size(u).await;
+			}
+		})
+		.await;
+
+		match counted {
+			Ok(()) => readable_size(total),
+			Err(_) if total > 0 => format!("at least {}", readable_size(total)),
+			Err(_) => "too large to size quickly".to_owned(),
+		}
+	}
+
+	fn file_remove_do(
+		scheduler: &Scheduler,
+		trashed: &Mutex<Vec<Vec<Url>>>,
+		targets: Vec<Url>,
+		permanently: bool,
+	) {
+		if !permanently && !targets.is_empty() {
+			trashed.lock().push(targets.clone());
+		}
+		for u in targets {
+			if permanently {
+				scheduler.file_delete(u);
+			} else {
+				scheduler.file_trash(u);
+			}
+		}
+	}
+
+	pub fn empty_trash(&self) -> bool {
+		let scheduler = self.scheduler.clone();
+		tokio::spawn(async move {
+			let mut result = Input::_show(InputCfg::empty_trash());
+			if let Some(Ok(choice)) = result.recv().await {
+				if choice == "y" || choice == "Y" {
+					scheduler.file_empty_trash();
 				}
 			}
 		});
 		false
 	}
 
-	#[inline]
-	pub fn precache_size(&self, targets: &Files) -> bool {
-		if targets.sorter().by != SortBy::Size {
+	pub fn file_restore(&self, targets: Vec<Url>) -> bool {
+		let scheduler = self.scheduler.clone();
+		tokio::spawn(async move { Self::file_restore_do(&scheduler, targets, None).await });
+		false
+	}
+
+	// Restores the files trashed most recently in this session. Whatever of them
+	// is skipped, fails, or is canceled is put back, so it can be tried again
+	pub fn file_restore_last(&self) -> bool {
+		let Some(targets) = self.trashed.lock().pop() else {
 			return false;
-		}
+		};
 
-		let targets: Vec<_> = targets
-			.iter()
-			.filter(|f| f.is_dir() && !targets.sizes.contains_key(&f.url))
-			.map(|f| &f.url)
-			.collect();
+		let (scheduler, trashed) = (self.scheduler.clone(), self.trashed.clone());
+		tokio::spawn(async move {
+			let (tx, mut rx) = mpsc::unbounded_channel();
+			Self::file_restore_do(&scheduler, targets.clone(), Some(tx)).await;
 
-		if !targets.is_empty() {
-			self.scheduler.precache_size(targets);
+			let mut done = HashSet::new();
+			while let Some(u) = rx.recv().await {
+				done.insert(u);
+			}
+
+			let left: Vec<_> = targets.into_iter().filter(|u| !done.contains(u)).collect();
+			if !left.is_empty() {
+				trashed.lock().push(left);
+			}
+		});
+		false
+	}
+
+	async fn file_restore_do(
+		scheduler: &Scheduler,
+		targets: Vec<Url>,
+		restored: Option<mpsc::UnboundedSender<Url>>,
+	) {
+		let mut all = None;
+		for u in targets {
+			match Self::resolve(u.clone(), &mut all).await {
+				Some(Some((to, force))) => scheduler.file_restore(u, to, force, restored.clone()),
+				Some(None) => continue,
+				None => return,
+			}
 		}
+	}
 
+	#[inline]
+	pub fn precache_size(&self, folder: &Folder) -> bool {
+		let files = &folder.files;
+		let targets: Vec<_> = if files.sorter().by == SortBy::Size {
+			files
+				.iter()
+				.filter(|f| {
+					f.is_dir() && (!files.sizes.contains_key(&f.url) || files.sizing.contains(&f.url))
+				})
+				.map(|f| &f.url)
+				.collect()
+		} else {
+			vec![]
+		};
+
+		// Always called, so that sizes of a directory the user has left get canceled
+		self.scheduler.precache_size(&folder.cwd, targets);
+		false
+	}
+
+	#[inline]
+	pub fn precache_git(&self, folder: &Folder) -> bool {
+		if !folder.cwd.is_search() {
+			self.scheduler.precache_git(&folder.cwd);
+		}
 		false
 	}
 
 	#[inline]
-	pub fn precache_mime(&self, targets: &[File], mimetype: &HashMap<Url, String>) -> bool {
+	// The hovered file goes in a batch of its own, so its preview doesn't wait on
+	// the rest of the page, which follows nearest to the cursor first
+	pub fn precache_mime(
+		&self,
+		targets: &[File],
+		mimetype: &HashMap<Url, String>,
+		folder: &Folder,
+	) -> bool {
 		let targets: Vec<_> = targets
 			.iter()
 			.filter(|f| !f.is_dir() && !mimetype.contains_key(&f.url))
-			.map(|f| f.url())
+			.map(|f| &f.url)
 			.collect();
 
+		let mut targets: Vec<_> =
+			Self::nearest_first(targets, folder).into_iter().map(|(u, _)| u).collect();
+		if targets.len() > 1 && folder.hovered().is_some_and(|h| h.url == targets[0]) {
+			let rest = targets.split_off(1);
+			self.scheduler.precache_mime(targets);
+			targets = rest;
+		}
+
 		if !targets.is_empty() {
 			self.scheduler.precache_mime(targets);
 		}
 		false
 	}
 
-	pub fn precache_image(&self, mimetype: &BTreeMap<Url, String>) -> bool {
+	pub fn precache_image(&self, mimetype: &BTreeMap<Url, String>, folder: &Folder) -> bool {
 		let targets: Vec<_> = mimetype
 			.iter()
 			.filter(|(_, m)| MimeKind::new(m) == MimeKind::Image)
-			.map(|(u, _)| u.clone())
+			.map(|(u, _)| u)
 			.collect();
 
 		if !targets.is_empty() {
-			self.scheduler.precache_image(targets);
+			self.scheduler.precache_image(Self::nearest_first(targets, folder));
 		}
 		false
 	}
 
-	pub fn precache_video(&self, mimetype: &BTreeMap<Url, String>) -> bool {
+	pub fn precache_video(&self, mimetype: &BTreeMap<Url, String>, folder: &Folder) -> bool {
 		let targets: Vec<_> = mimetype
 			.iter()
 			.filter(|(_, m)| MimeKind::new(m) == MimeKind::Video)
-			.map(|(u, _)| u.clone())
+			.map(|(u, _)| u)
 			.collect();
 
 		if !targets.is_empty() {
-			self.scheduler.precache_video(targets);
+			self.scheduler.precache_video(Self::nearest_first(targets, folder));
 		}
 		false
 	}
 
-	pub fn precache_pdf(&self, mimetype: &BTreeMap<Url, String>) -> bool {
-		let targets: Vec<_> = mimetype
-			.iter()
-			.filter(|(_, m)| MimeKind::new(m) == MimeKind::PDF)
-			.map(|(u, _)| u.clone())
-			.collect();
+	pub fn precache_pdf(&self, mimetype: &BTreeMap<Url, String>, folder: &Folder) -> bool {
+		let targets: Vec<_> =
+			mimetype.iter().filter(|(_, m)| MimeKind::new(m) == MimeKind::PDF).map(|(u, _)| u).collect();
 
 		if !targets.is_empty() {
-			self.scheduler.precache_pdf(targets);
+			self.scheduler.precache_pdf(Self::nearest_first(targets, folder));
+		}
+		false
+	}
+
+	// Warms up the previews of a page of files whose mimetypes are already known,
+	// e.g. when coming back to a directory; the rest is precached as their
+	// mimetypes arrive
+	pub fn precache_window(
+		&self,
+		targets: &[File],
+		mimetype: &HashMap<Url, String>,
+		folder: &Folder,
+	) -> bool {
+		let mimes: BTreeMap<_, _> =
+			targets.iter().filter_map(|f| Some((f.url(), mimetype.get(&f.url)?.clone()))).collect();
+
+		if !mimes.is_empty() {
+			self.precache_image(&mimes, folder);
+			self.precache_video(&mimes, folder);
+			self.precache_pdf(&mimes, folder);
 		}
 		false
 	}
 }
 
 impl Tasks {
+	// Files closest to the cursor are precached first, the ones not in the folder
+	// last, paired with their modification time if known, to key the caches by
+	fn nearest_first(targets: Vec<&Url>, folder: &Folder) -> Vec<(Url, Option<SystemTime>)> {
+		let known: HashMap<_, _> =
+			folder.files.iter().enumerate().map(|(i, f)| (&f.url, (i, f.modified))).collect();
+
+		let mut targets: Vec<_> = targets.into_iter().map(|u| (u, known.get(u))).collect();
+		targets.sort_by_key(|(_, k)| k.map_or(usize::MAX, |&(i, _)| i.abs_diff(folder.cursor)));
+		targets.into_iter().map(|(u, k)| (u.clone(), k.and_then(|&(_, m)| m))).collect()
+	}
+
 	#[inline]
 	pub fn len(&self) -> usize { self.scheduler.running.read().len() }
 }
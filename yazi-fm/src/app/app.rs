@@ -5,6 +5,7 @@ use crossterm::event::KeyEvent;
 use ratatui::{backend::Backend, prelude::Rect};
 use yazi_config::{keymap::Key, BOOT};
 use yazi_core::{input::InputMode, preview::COLLISION, Ctx};
+use yazi_scheduler::external;
 use yazi_shared::{emit, event::{Event, Exec}, fs::FilesOp, term::Term, Layer};
 
 use crate::{Executor, Logs, Panic, Root, Signals};
@@ -19,6 +20,7 @@ impl App {
 	pub(crate) async fn run() -> Result<()> {
 		Panic::install();
 		let _log = Logs::init()?;
+		Self::log_tools();
 		let term = Term::start()?;
 
 		let signals = Signals::start()?;
@@ -41,6 +43,18 @@ impl App {
 		Ok(())
 	}
 
+	// Previews fall back quietly without these, so leave a trace of which are
+	// missing
+	fn log_tools() {
+		let (found, missing): (Vec<&str>, Vec<&str>) =
+			external::OPTIONAL_TOOLS.iter().partition(|&&t| external::available(t));
+
+		tracing::debug!("Optional tools found: {found:?}");
+		if !missing.is_empty() {
+			tracing::warn!("Optional tools not found in $PATH: {missing:?}");
+		}
+	}
+
 	fn dispatch_quit(&mut self, no_cwd_file: bool) {
 		if let Some(p) = BOOT.cwd_file.as_ref().filter(|_| !no_cwd_file) {
 			let cwd = self.cx.manager.cwd().as_os_str();
@@ -131,7 +145,10 @@ impl App {
 		let tasks = &mut self.cx.tasks;
 		match event {
 			Event::Files(op) => {
-				let calc = !matches!(op, FilesOp::Size(..) | FilesOp::IOErr(_));
+				let calc = !matches!(
+					op,
+					FilesOp::Size(..) | FilesOp::Sizing(..) | FilesOp::Git(..) | FilesOp::IOErr(_)
+				);
 				let b = match op {
 					FilesOp::IOErr(..) => manager.update_ioerr(op),
 					_ => manager.update_read(op),
@@ -140,12 +157,14 @@ impl App {
 					emit!(Render);
 				}
 				if calc {
-					tasks.precache_size(&manager.current().files);
+					tasks.precache_size(manager.current());
+					tasks.precache_git(manager.current());
 				}
 			}
 			Event::Pages(page) => {
-				let targets = self.cx.manager.current().paginate(page);
-				tasks.precache_mime(targets, &self.cx.manager.mimetype);
+				let targets = manager.current().paginate(page);
+				tasks.precache_mime(targets, &manager.mimetype, manager.current());
+				tasks.precache_window(targets, &manager.mimetype, manager.current());
 			}
 			Event::Mimetype(mimes) => {
 				if manager.update_mimetype(mimes, tasks) {
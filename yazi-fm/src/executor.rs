@@ -136,11 +136,16 @@ impl<'a> Executor<'a> {
 		on!(MANAGER, paste, &self.app.cx.tasks);
 		on!(MANAGER, link, &self.app.cx.tasks);
 		on!(MANAGER, remove, &self.app.cx.tasks);
+		on!(MANAGER, restore, &self.app.cx.tasks);
+		on!(MANAGER, empty_trash, &self.app.cx.tasks);
+		on!(MANAGER, extract, &self.app.cx.tasks);
+		on!(MANAGER, compress, &self.app.cx.tasks);
 		on!(MANAGER, create);
 		on!(MANAGER, rename);
 		on!(ACTIVE, copy);
 		on!(ACTIVE, shell);
 		on!(ACTIVE, hidden);
+		on!(MANAGER, filter);
 		on!(ACTIVE, linemode);
 		on!(ACTIVE, search);
 		on!(ACTIVE, jump);
@@ -188,6 +193,7 @@ impl<'a> Executor<'a> {
 		on!(arrow);
 		on!(inspect);
 		on!(cancel);
+		on!(escalate);
 
 		match exec.cmd.as_str() {
 			"help" => self.app.cx.help.toggle(Layer::Tasks),
@@ -234,6 +240,8 @@ impl<'a> Executor<'a> {
 		on!(move_, "move");
 		on!(backward);
 		on!(forward);
+		on!(history);
+		on!(taken);
 
 		if exec.cmd.as_str() == "complete" {
 			return if exec.named.contains_key("trigger") {
@@ -263,6 +271,7 @@ impl<'a> Executor<'a> {
 			InputMode::Insert => {
 				on!(backspace);
 				on!(kill);
+				on!(tab);
 
 				false
 			}
@@ -1,7 +1,7 @@
 use std::ops::Range;
 
 use ansi_to_tui::IntoText;
-use ratatui::{buffer::Buffer, layout::Rect, text::{Line, Text}, widgets::{Block, BorderType, Borders, Paragraph, Widget}};
+use ratatui::{buffer::Buffer, layout::Rect, text::{Line, Text}, widgets::{block::{Position, Title}, Block, BorderType, Borders, Paragraph, Widget}};
 use yazi_config::THEME;
 use yazi_core::{input::InputMode, Ctx};
 use yazi_shared::term::Term;
@@ -27,21 +27,23 @@ impl<'a> Widget for Input<'a> {
 			Text::from(input.value())
 		};
 
+		let mut block = Block::new()
+			.borders(Borders::ALL)
+			.border_type(BorderType::Rounded)
+			.border_style(THEME.input.border.into())
+			.title({
+				let mut line = Line::from(input.title.as_str());
+				line.patch_style(THEME.input.title.into());
+				line
+			});
+		if let Some(error) = &input.error {
+			let mut line = Line::from(error.as_str());
+			line.patch_style(THEME.input.error.into());
+			block = block.title(Title::from(line).position(Position::Bottom));
+		}
+
 		widgets::Clear.render(area, buf);
-		Paragraph::new(value)
-			.block(
-				Block::new()
-					.borders(Borders::ALL)
-					.border_type(BorderType::Rounded)
-					.border_style(THEME.input.border.into())
-					.title({
-						let mut line = Line::from(input.title.as_str());
-						line.patch_style(THEME.input.title.into());
-						line
-					}),
-			)
-			.style(THEME.input.value.into())
-			.render(area, buf);
+		Paragraph::new(value).block(block).style(THEME.input.value.into()).render(area, buf);
 
 		if let Some(Range { start, end }) = input.selected() {
 			let x = win.width.min(area.x + 1 + start);
@@ -1,9 +1,14 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use anyhow::Result;
 use crossterm::event::{Event as CrosstermEvent, EventStream, KeyEvent, KeyEventKind};
 use futures::StreamExt;
 use tokio::{select, sync::{mpsc::{self, UnboundedReceiver, UnboundedSender}, oneshot}, task::JoinHandle};
 use yazi_shared::event::Event;
 
+// Set while a blocking process owns the terminal
+static HANDED_OFF: AtomicBool = AtomicBool::new(false);
+
 pub(super) struct Signals {
 	tx: UnboundedSender<Event>,
 	rx: UnboundedReceiver<Event>,
@@ -35,6 +40,7 @@ impl Signals {
 			return;
 		}
 
+		HANDED_OFF.store(state, Ordering::Relaxed);
 		if let Some(tx) = self.term_stop_tx.take() {
 			tx.send(()).ok();
 		} else {
@@ -63,6 +69,8 @@ impl Signals {
 		Ok(tokio::spawn(async move {
 			while let Some(signal) = signals.next().await {
 				match signal {
+					// The foreground child gets Ctrl-C and Ctrl-\ too, leave them to it
+					SIGQUIT | SIGINT if HANDED_OFF.load(Ordering::Relaxed) => {}
 					SIGHUP | SIGTERM | SIGQUIT | SIGINT => {
 						if tx.send(Event::Quit(false)).is_err() {
 							break;
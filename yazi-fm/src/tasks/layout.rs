@@ -1,6 +1,7 @@
 use ratatui::{buffer::Buffer, layout::{self, Alignment, Constraint, Direction, Rect}, text::Line, widgets::{Block, BorderType, Borders, List, ListItem, Padding, Widget}};
 use yazi_config::THEME;
 use yazi_core::{tasks::TASKS_PERCENT, Ctx};
+use yazi_shared::readable_size;
 
 use crate::widgets;
 
@@ -11,6 +12,21 @@ pub(crate) struct Layout<'a> {
 impl<'a> Layout<'a> {
 	pub(crate) fn new(cx: &'a Ctx) -> Self { Self { cx } }
 
+	// e.g. "12.3 MB/s, ~45s left", or just the throughput when the total is unknown
+	fn rate(speed: u64, eta: Option<u64>) -> Option<String> {
+		if speed == 0 {
+			return None;
+		}
+
+		let speed = format!("{}/s", readable_size(speed));
+		Some(match eta {
+			Some(s) if s >= 3600 => format!("{speed}, ~{}h{}m left", s / 3600, s % 3600 / 60),
+			Some(s) if s >= 60 => format!("{speed}, ~{}m{}s left", s / 60, s % 60),
+			Some(s) => format!("{speed}, ~{s}s left"),
+			None => speed,
+		})
+	}
+
 	pub(super) fn area(area: Rect) -> Rect {
 		let chunk = layout::Layout::new()
 			.direction(Direction::Vertical)
@@ -56,7 +72,11 @@ impl<'a> Widget for Layout<'a> {
 			.iter()
 			.enumerate()
 			.map(|(i, v)| {
-				let mut item = ListItem::new(v.name.clone());
+				let mut item = ListItem::new(match (v.retrying, Self::rate(v.speed, v.eta)) {
+					(Some((n, max)), _) => format!("{} (retrying {n}/{max})", v.name),
+					(None, Some(rate)) => format!("{} ({rate})", v.name),
+					(None, None) => v.name.clone(),
+				});
 				if i == tasks.cursor {
 					item = item.style(THEME.tasks.hovered.into());
 				}
@@ -23,9 +23,10 @@ function Folder:highlighted_name(file)
 		prefix = prefix .. "/"
 	end
 
-	-- Range highlighting for filenames
+	-- Range highlighting for filenames, which are computed against the real name
 	local highlights = file:highlights()
-	local spans = ui.highlight_ranges(prefix .. file.name, highlights)
+	local name = highlights == nil and file.display_name or file.name
+	local spans = ui.highlight_ranges(prefix .. name, highlights)
 
 	-- Show symlink target
 	if MANAGER.show_symlink and file.link_to ~= nil then
@@ -60,6 +61,8 @@ function Folder:linemode(area)
 			spans[#spans + 1] = ui.Span(os.date("%y-%m-%d %H:%M", f.modified))
 		elseif mode == "permissions" then
 			spans[#spans + 1] = ui.Span(f:permissions() or "")
+		elseif mode == "owner" then
+			spans[#spans + 1] = ui.Span(f:owner() and f:owner() .. ":" .. f:group() or "")
 		end
 
 		spans[#spans + 1] = ui.Span(" ")
@@ -110,6 +113,27 @@ function Folder:markers(area, markers)
 	return elements
 end
 
+-- The last row of the preview is taken by the summary, which the window of
+-- files leaves out
+function Folder:summary(area, folder, items)
+	if area.h < 2 or #items == 0 then
+		return { ui.List(area, items) }
+	end
+
+	local dirs, files, size = folder.files:summary()
+	local summary = string.format("%d dirs, %d files", dirs, files)
+	if size ~= nil then
+		summary = summary .. ", " .. utils.readable_size(size)
+	end
+
+	local list = ui.Rect { x = area.x, y = area.y, w = area.w, h = area.h - 1 }
+	local last = ui.Rect { x = area.x, y = area.y + area.h - 1, w = area.w, h = 1 }
+	return {
+		ui.List(list, items),
+		ui.Paragraph(last, { ui.Line { ui.Span(" " .. summary):dim() } }),
+	}
+end
+
 function Folder:parent(area)
 	local folder = self:by_kind(self.PARENT)
 	if folder == nil then
@@ -118,7 +142,7 @@ function Folder:parent(area)
 
 	local items = {}
 	for _, f in ipairs(folder.window) do
-		local item = ui.ListItem(ui.Line { self:icon(f), ui.Span(f.name) })
+		local item = ui.ListItem(ui.Line { self:icon(f), ui.Span(f.display_name) })
 		if f:is_hovered() then
 			item = item:style(THEME.manager.hovered)
 		else
@@ -165,7 +189,7 @@ function Folder:preview(area)
 
 	local items = {}
 	for _, f in ipairs(folder.window) do
-		local item = ui.ListItem(ui.Line { self:icon(f), ui.Span(f.name) })
+		local item = ui.ListItem(ui.Line { self:icon(f), ui.Span(f.display_name) })
 		if f:is_hovered() then
 			item = item:style(THEME.manager.preview_hovered)
 		else
@@ -174,7 +198,7 @@ function Folder:preview(area)
 		items[#items + 1] = item
 	end
 
-	return { ui.List(area, items) }
+	return self:summary(area, folder, items)
 end
 
 function Folder:render(area, args)
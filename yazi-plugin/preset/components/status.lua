@@ -42,7 +42,19 @@ function Status:name()
 		return ui.Span("")
 	end
 
-	return ui.Span(" " .. h.name)
+	-- The parent's hovered file is the current directory itself
+	local p = cx.active.parent
+	if p and p.hovered and h:is_mount_point(p.hovered.dev) then
+		return ui.Span(" " .. h.display_name .. " [mount]")
+	end
+
+	local status = h.link_status
+	if status == nil then
+		return ui.Span(" " .. h.display_name)
+	elseif status == "resolved" then
+		return ui.Span(" " .. h.display_name .. " -> " .. tostring(h.link_to))
+	end
+	return ui.Span(" " .. h.display_name .. " -> " .. tostring(h.link_to) .. " (" .. status .. ")")
 end
 
 function Status:permissions()
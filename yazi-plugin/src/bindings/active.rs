@@ -126,7 +126,9 @@ impl<'a, 'b> Active<'a, 'b> {
 				.as_ref()
 				.filter(|l| l.is_folder())
 				.and_then(|l| tab.history(&l.url).map(|f| (l, f)))
-				.and_then(|(l, f)| self.folder(f, Some((l.skip, MANAGER.layout.preview_height()))).ok()),
+				.and_then(|(l, f)| {
+					self.folder(f, Some((l.skip, MANAGER.layout.preview_folder_height()))).ok()
+				}),
 		)?;
 
 		Ok(ud)
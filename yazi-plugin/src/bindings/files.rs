@@ -1,7 +1,8 @@
 use std::time::UNIX_EPOCH;
 
 use mlua::{AnyUserData, IntoLua, MetaMethod, UserData, UserDataFields, UserDataMethods, UserDataRef};
-use yazi_config::THEME;
+use yazi_config::{theme::Icon, MANAGER, THEME};
+use yazi_shared::MimeKind;
 
 use super::{Range, Url};
 use crate::{layout::Style, LUA};
@@ -45,17 +46,24 @@ impl Files {
 				let files = me.borrow::<yazi_core::files::Files>()?;
 				Ok(files.iter().skip(skip).take(take).map(File::from).collect::<Vec<_>>())
 			});
+
+			reg.add_method("summary", |_, me, ()| Ok(me.summary()));
 		})?;
 
 		LUA.register_userdata_type::<yazi_shared::fs::File>(|reg| {
 			reg.add_field_method_get("url", |_, me| Ok(Url::from(&me.url)));
 			reg.add_field_method_get("link_to", |_, me| Ok(me.link_to().map(Url::from)));
+			reg.add_field_method_get("link_status", |_, me| {
+				Ok(me.symlink_info().map(|(_, s)| s.as_str()))
+			});
 			reg.add_field_method_get("is_link", |_, me| Ok(me.is_link()));
 			reg.add_field_method_get("is_hidden", |_, me| Ok(me.is_hidden()));
 
 			// Metadata
 			reg.add_field_method_get("is_dir", |_, me| Ok(me.is_dir()));
 			reg.add_field_method_get("is_symlink", |_, me| Ok(me.is_link()));
+			reg.add_field_method_get("is_junction", |_, me| Ok(me.is_junction()));
+			reg.add_field_method_get("is_reparse", |_, me| Ok(me.is_reparse()));
 			#[cfg(unix)]
 			{
 				reg.add_field_method_get("is_block_device", |_, me| Ok(me.is_block_device()));
@@ -64,6 +72,12 @@ impl Files {
 				reg.add_field_method_get("is_socket", |_, me| Ok(me.is_socket()));
 			}
 			reg.add_field_method_get("length", |_, me| Ok(me.len));
+			reg.add_field_method_get("allocated", |_, me| Ok(me.allocated()));
+			reg.add_field_method_get("is_sparse", |_, me| Ok(me.is_sparse()));
+			reg.add_field_method_get("nlink", |_, me| Ok(me.cha.nlink()));
+			reg.add_field_method_get("dev", |_, me| Ok(me.cha.dev()));
+			reg.add_method("is_mount_point", |_, me, parent_dev: u64| Ok(me.is_mount_point(parent_dev)));
+			reg.add_field_method_get("is_hardlinked", |_, me| Ok(me.is_hardlinked()));
 			reg.add_field_method_get("created", |_, me| {
 				Ok(me.created.and_then(|t| t.duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).ok()))
 			});
@@ -81,11 +95,37 @@ impl Files {
 					None::<String>,
 				)
 			});
+			reg.add_method("owner", |_, me, ()| {
+				Ok(
+					#[cfg(unix)]
+					Some(me.cha.owner()),
+					#[cfg(windows)]
+					None::<String>,
+				)
+			});
+			reg.add_method("group", |_, me, ()| {
+				Ok(
+					#[cfg(unix)]
+					Some(me.cha.group()),
+					#[cfg(windows)]
+					None::<String>,
+				)
+			});
 
 			// Extension
 			reg.add_field_method_get("name", |_, me| {
 				Ok(me.url.file_name().map(|n| n.to_string_lossy().to_string()))
 			});
+			reg.add_field_method_get("display_name", |_, me| {
+				Ok(me.url.file_name().map(|n| MANAGER.display_name(&n.to_string_lossy()).into_owned()))
+			});
+			reg.add_method("xattrs", |lua, me, ()| {
+				lua.create_table_from(me.xattrs().into_iter().map(|x| (x.name.clone(), x.value_lossy())))
+			});
+			reg.add_method("xattr", |lua, me, name: String| {
+				me.xattr(&name).map(|v| lua.create_string(v)).transpose()
+			});
+			reg.add_method("flags", |_, me, ()| Ok(me.flags()));
 			reg.add_function("size", |_, me: AnyUserData| {
 				let file = me.borrow::<yazi_shared::fs::File>()?;
 				if !file.is_dir() {
@@ -111,14 +151,18 @@ impl Files {
 				p.next_back();
 				Ok(Some(p.as_path().to_string_lossy().to_string()))
 			});
-			reg.add_method("icon", |_, me, ()| {
-				Ok(
-					THEME
-						.icons
-						.iter()
-						.find(|&x| x.name.match_path(&me.url, Some(me.is_dir())))
-						.map(|x| x.display.to_string()),
-				)
+			reg.add_function("icon", |_, me: AnyUserData| {
+				let manager = me.named_user_value::<UserDataRef<yazi_core::manager::Manager>>("manager")?;
+				let folder = me.named_user_value::<UserDataRef<yazi_core::tab::Folder>>("folder")?;
+				let file = me.borrow::<yazi_shared::fs::File>()?;
+				let mime = manager.mimetype.get(&file.url).map(|m| MimeKind::new(m));
+				let git = folder.git(&file.url);
+				Ok(Icon::resolve(&THEME.icons, &file.url, file.is_dir(), mime, git).map(|s| s.into_owned()))
+			});
+			reg.add_function("git", |_, me: AnyUserData| {
+				let folder = me.named_user_value::<UserDataRef<yazi_core::tab::Folder>>("folder")?;
+				let file = me.borrow::<yazi_shared::fs::File>()?;
+				Ok(folder.git(&file.url).map(|g| g.marker()))
 			});
 			reg.add_function("style", |_, me: AnyUserData| {
 				let manager = me.named_user_value::<UserDataRef<yazi_core::manager::Manager>>("manager")?;
@@ -1,7 +1,7 @@
 use ansi_to_tui::IntoText;
-use ratatui::{buffer::Buffer, layout::Rect, widgets::{Paragraph, Widget}};
+use ratatui::{buffer::Buffer, layout::Rect, style::{Style, Stylize}, widgets::{Paragraph, Widget}};
 use yazi_core::Ctx;
-use yazi_shared::event::PreviewData;
+use yazi_shared::{event::{PreviewData, PreviewLock}, MimeKind};
 
 use super::Folder;
 
@@ -11,6 +11,19 @@ pub(super) struct Preview<'a> {
 
 impl<'a> Preview<'a> {
 	pub(super) fn new(cx: &'a Ctx) -> Self { Self { cx } }
+
+	// The visible line range, in the top-right corner; skipped for PDFs where
+	// `skip` counts pages rather than lines
+	fn range(&self, lock: &PreviewLock, lines: usize, area: Rect, buf: &mut Buffer) {
+		let mime = self.cx.manager.mimetype.get(&lock.url).map(String::as_str).unwrap_or_default();
+		if MimeKind::new(mime) == MimeKind::PDF {
+			return;
+		}
+
+		let s = format!(" {}-{} ", lock.skip + 1, lock.skip + lines);
+		let x = area.right().saturating_sub(s.len() as u16).max(area.x);
+		buf.set_string(x, area.y, s, Style::new().reversed());
+	}
 }
 
 impl<'a> Widget for Preview<'a> {
@@ -26,8 +39,18 @@ impl<'a> Widget for Preview<'a> {
 			PreviewData::Text(s) => {
 				let p = Paragraph::new(s.as_bytes().into_text().unwrap());
 				p.render(area, buf);
+
+				let lines = s.lines().count();
+				if lock.skip > 0 || lines >= area.height as usize {
+					self.range(lock, lines, area, buf);
+				}
+			}
+			PreviewData::Image(None) => {}
+			PreviewData::Image(Some(exif)) => {
+				let height = (exif.lines().count() as u16).min(area.height);
+				let area = Rect { y: area.bottom() - height, height, ..area };
+				Paragraph::new(exif.as_str()).style(Style::new().dim()).render(area, buf);
 			}
-			PreviewData::Image => {}
 		}
 	}
 }
@@ -0,0 +1,77 @@
+use std::{path::Path, sync::OnceLock, time::{SystemTime, UNIX_EPOCH}};
+
+use serde_json::json;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::mpsc};
+use yazi_config::TASKS;
+use yazi_shared::fs::expand_path;
+
+static APPENDER: OnceLock<mpsc::UnboundedSender<String>> = OnceLock::new();
+
+// Appends the outcome of a file operation to `[tasks] audit_log` as a JSON
+// line, `error` is `None` if it succeeded
+pub(crate) fn audit(op: &str, from: &Path, to: Option<&Path>, error: Option<&str>) {
+	if TASKS.audit_log.is_empty() {
+		return;
+	}
+
+	let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+	APPENDER.get_or_init(appender).send(line(time, op, from, to, error)).ok();
+}
+
+// A single task does the writing, so lines from concurrent operations never
+// interleave. The file is reopened for each batch of them, to follow rotation
+fn appender() -> mpsc::UnboundedSender<String> {
+	let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+	tokio::spawn(async move {
+		let path = expand_path(&TASKS.audit_log);
+		while let Some(mut buf) = rx.recv().await {
+			while let Ok(line) = rx.try_recv() {
+				buf.push_str(&line);
+			}
+
+			let result = match OpenOptions::new().create(true).append(true).open(&path).await {
+				Ok(mut f) => f.write_all(buf.as_bytes()).await,
+				Err(e) => Err(e),
+			};
+			if let Err(e) = result {
+				tracing::warn!("Failed to write the audit log: {e}");
+			}
+		}
+	});
+	tx
+}
+
+fn line(time: u64, op: &str, from: &Path, to: Option<&Path>, error: Option<&str>) -> String {
+	let mut line = json!({
+		"time": time,
+		"op": op,
+		"from": from.to_string_lossy(),
+		"to": to.map(|p| p.to_string_lossy()),
+		"result": if error.is_some() { "error" } else { "ok" },
+		"error": error,
+	})
+	.to_string();
+	line.push('\n');
+	line
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_line() {
+		assert_eq!(
+			line(1, "move", Path::new("/a"), Some(Path::new("/b")), None),
+			"{\"error\":null,\"from\":\"/a\",\"op\":\"move\",\"result\":\"ok\",\"time\":1,\"to\":\"/b\"}\n"
+		);
+
+		let line = line(2, "delete", Path::new("/a\n\"b\""), None, Some("Permission denied"));
+		let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+		assert_eq!(value["from"], "/a\n\"b\"");
+		assert_eq!(value["to"], serde_json::Value::Null);
+		assert_eq!(value["result"], "error");
+		assert_eq!(value["error"], "Permission denied");
+		assert_eq!(line.matches('\n').count(), 1);
+	}
+}
@@ -0,0 +1,125 @@
+use std::{ffi::OsString, path::Path, process::Stdio};
+
+use anyhow::Result;
+use tokio::process::{Child, Command};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+	Zip,
+	TarGz,
+	TarZst,
+}
+
+impl ArchiveFormat {
+	// Tells the format from the extension of the archive's name
+	pub fn from_name(name: &str) -> Option<Self> {
+		let name = name.to_ascii_lowercase();
+		if name.ends_with(".zip") {
+			Some(Self::Zip)
+		} else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+			Some(Self::TarGz)
+		} else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+			Some(Self::TarZst)
+		} else {
+			None
+		}
+	}
+
+	#[inline]
+	pub fn tool(self) -> &'static str {
+		match self {
+			Self::Zip => "zip",
+			Self::TarGz | Self::TarZst => "tar",
+		}
+	}
+
+	// The path of the entry the tool reports to have added on `line`, GNU tar
+	// lists them on stdout, bsdtar on stderr prefixed by "a "
+	pub fn added(self, line: &str, stderr: bool) -> Option<&str> {
+		let path = match (self, stderr) {
+			(Self::Zip, false) => line.trim_start().strip_prefix("adding: ")?.rsplit_once(" (")?.0,
+			(Self::TarGz | Self::TarZst, false) => line,
+			(Self::TarGz | Self::TarZst, true) => line.strip_prefix("a ")?,
+			(Self::Zip, true) => return None,
+		};
+		Some(path).filter(|p| !p.is_empty())
+	}
+}
+
+// Packs `names` in `cwd` into `archive`, storing symlinks as they are unless
+// `dereference`, and listing each entry as it's added
+pub fn compress(
+	format: ArchiveFormat,
+	archive: &Path,
+	cwd: &Path,
+	names: &[OsString],
+	dereference: bool,
+) -> Result<Child> {
+	let mut cmd = Command::new(format.tool());
+	match format {
+		ArchiveFormat::Zip => {
+			cmd.arg("-r");
+			if !dereference {
+				cmd.arg("-y");
+			}
+			cmd.arg(archive);
+
+			// zip takes no `--`, names that look like options are made relative
+			for name in names {
+				if name.to_string_lossy().starts_with('-') {
+					let mut s = OsString::from("./");
+					s.push(name);
+					cmd.arg(s);
+				} else {
+					cmd.arg(name);
+				}
+			}
+		}
+		ArchiveFormat::TarGz | ArchiveFormat::TarZst => {
+			cmd.arg("-cv");
+			cmd.arg(if format == ArchiveFormat::TarGz { "-z" } else { "--zstd" });
+			if dereference {
+				cmd.arg("-h");
+			}
+			cmd.arg("-f").arg(archive).arg("--").args(names);
+		}
+	}
+
+	Ok(
+		cmd
+			.current_dir(cwd)
+			.stdin(Stdio::null())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.kill_on_drop(true)
+			.spawn()?,
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ArchiveFormat;
+
+	#[test]
+	fn test_from_name() {
+		assert_eq!(ArchiveFormat::from_name("a.ZIP"), Some(ArchiveFormat::Zip));
+		assert_eq!(ArchiveFormat::from_name("a.tar.gz"), Some(ArchiveFormat::TarGz));
+		assert_eq!(ArchiveFormat::from_name("a.tzst"), Some(ArchiveFormat::TarZst));
+		assert_eq!(ArchiveFormat::from_name("a.gz"), None);
+		assert_eq!(ArchiveFormat::from_name("a.tar"), None);
+	}
+
+	#[test]
+	fn test_added() {
+		assert_eq!(
+			ArchiveFormat::Zip.added("  adding: a (b).txt (deflated 12%)", false),
+			Some("a (b).txt")
+		);
+		assert_eq!(ArchiveFormat::Zip.added("updating: a.txt", false), None);
+		assert_eq!(ArchiveFormat::Zip.added("  adding: a.txt (stored 0%)", true), None);
+		assert_eq!(ArchiveFormat::TarGz.added("dir/a.txt", false), Some("dir/a.txt"));
+		assert_eq!(ArchiveFormat::TarGz.added("a dir/a.txt", true), Some("dir/a.txt"));
+		assert_eq!(ArchiveFormat::TarGz.added("tar: Removing leading '/'", true), None);
+		assert_eq!(ArchiveFormat::TarZst.added("", false), None);
+	}
+}
@@ -0,0 +1,25 @@
+use std::{path::Path, process::Stdio};
+
+use anyhow::{bail, Result};
+use tokio::{io::AsyncReadExt, process::Command};
+
+// Decompresses at most `cap` bytes of a single-stream file, so that a small
+// bomb can't expand into the whole memory
+pub async fn decompress(tool: &str, path: &Path, cap: usize) -> Result<Vec<u8>> {
+	let mut child = Command::new(tool)
+		.arg("-dc")
+		.arg(path)
+		.stdout(Stdio::piped())
+		.stderr(Stdio::null())
+		.kill_on_drop(true)
+		.spawn()?;
+
+	let mut buf = Vec::with_capacity(cap.min(64 << 10));
+	child.stdout.take().unwrap().take(cap as u64).read_to_end(&mut buf).await?;
+	child.start_kill().ok();
+
+	if buf.is_empty() && !child.wait().await?.success() {
+		bail!("failed to decompress {:?} with {tool}", path);
+	}
+	Ok(buf)
+}
@@ -0,0 +1,62 @@
+use std::process::Stdio;
+
+use anyhow::Result;
+use tokio::process::Command;
+
+// Shows a desktop notification, through whatever the platform ships for it
+pub async fn desktop_notify(summary: &str, body: &str) -> Result<()> {
+	#[cfg(target_os = "macos")]
+	let mut cmd = {
+		let mut cmd = Command::new("osascript");
+		cmd.args([
+			"-e",
+			"on run argv",
+			"-e",
+			"display notification (item 2 of argv) with title (item 1 of argv)",
+			"-e",
+			"end run",
+			summary,
+			body,
+		]);
+		cmd
+	};
+
+	#[cfg(all(unix, not(target_os = "macos")))]
+	let mut cmd = {
+		let mut cmd = Command::new("notify-send");
+		cmd.args(["--app-name=Yazi", "--", summary, body]);
+		cmd
+	};
+
+	#[cfg(windows)]
+	let mut cmd = {
+		const SCRIPT: &str = r#"
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null
+$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$texts = $xml.GetElementsByTagName('text')
+$texts.Item(0).AppendChild($xml.CreateTextNode($env:YAZI_NOTIFY_SUMMARY)) > $null
+$texts.Item(1).AppendChild($xml.CreateTextNode($env:YAZI_NOTIFY_BODY)) > $null
+$toast = [Windows.UI.Notifications.ToastNotification]::new($xml)
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('Yazi').Show($toast)
+"#;
+		let mut cmd = Command::new("powershell");
+		cmd
+			.args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+			.env("YAZI_NOTIFY_SUMMARY", summary)
+			.env("YAZI_NOTIFY_BODY", body);
+		cmd
+	};
+
+	let status = cmd
+		.stdin(Stdio::null())
+		.stdout(Stdio::null())
+		.stderr(Stdio::null())
+		.kill_on_drop(true)
+		.status()
+		.await?;
+
+	if !status.success() {
+		anyhow::bail!("notifier exited with {status}");
+	}
+	Ok(())
+}
@@ -0,0 +1,19 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use tokio::process::Command;
+
+pub async fn exiftool(path: &Path) -> Result<String> {
+	let output = Command::new("exiftool")
+		.args(["-S", "-Model", "-ImageSize", "-ISO", "-ExposureTime", "-FNumber", "-FocalLength"])
+		.args(["-DateTimeOriginal", "-GPSPosition"])
+		.arg(path)
+		.kill_on_drop(true)
+		.output()
+		.await?;
+
+	if !output.status.success() {
+		bail!("failed to get EXIF: {}", String::from_utf8_lossy(&output.stderr));
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_owned())
+}
@@ -0,0 +1,28 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use tokio::process::Command;
+
+pub struct FontInfo {
+	pub family: String,
+	pub style:  String,
+}
+
+pub async fn fc_scan(path: &Path) -> Result<FontInfo> {
+	let output = Command::new("fc-scan")
+		.args(["--format", "%{family[0]}\\n%{style[0]}\\n"])
+		.arg(path)
+		.kill_on_drop(true)
+		.output()
+		.await?;
+
+	// Collections print one pair of lines per face, only the first one is used
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	let mut lines = stdout.lines();
+	match (lines.next(), lines.next()) {
+		(Some(family), style) if output.status.success() && !family.is_empty() => {
+			Ok(FontInfo { family: family.to_owned(), style: style.unwrap_or_default().to_owned() })
+		}
+		_ => bail!("failed to parse font: {}", String::from_utf8_lossy(&output.stderr)),
+	}
+}
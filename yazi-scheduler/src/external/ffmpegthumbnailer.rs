@@ -1,13 +1,15 @@
 use std::path::Path;
 
 use tokio::process::Command;
+use tracing::debug;
 use yazi_config::PREVIEW;
 use yazi_shared::PeekError;
 
-pub async fn ffmpegthumbnailer(src: &Path, dest: &Path, skip: usize) -> Result<(), PeekError> {
-	let percentage = 5 + skip;
+// Whether a thumbnail was written, it isn't if the video can't be decoded
+pub async fn ffmpegthumbnailer(src: &Path, dest: &Path, skip: usize) -> Result<bool, PeekError> {
+	let percentage = PREVIEW.video_seek as usize + skip;
 	if percentage > 95 {
-		return Err(PeekError::Exceed(95 - 5));
+		return Err(PeekError::Exceed(95 - PREVIEW.video_seek as usize));
 	}
 
 	let output = Command::new("ffmpegthumbnailer")
@@ -22,7 +24,8 @@ pub async fn ffmpegthumbnailer(src: &Path, dest: &Path, skip: usize) -> Result<(
 		.await?;
 
 	if !output.status.success() {
-		return Err(String::from_utf8_lossy(&output.stderr).to_string().into());
+		debug!("ffmpegthumbnailer failed on {src:?}: {}", String::from_utf8_lossy(&output.stderr));
+		return Ok(false);
 	}
-	Ok(())
+	Ok(true)
 }
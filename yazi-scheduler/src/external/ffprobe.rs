@@ -0,0 +1,81 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::Deserialize;
+use tokio::process::Command;
+use yazi_config::PREVIEW;
+use yazi_shared::PeekError;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FfprobeInfo {
+	#[serde(default)]
+	pub format:  FfprobeFormat,
+	#[serde(default)]
+	pub streams: Vec<FfprobeStream>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FfprobeFormat {
+	pub duration: Option<String>,
+	pub bit_rate: Option<String>,
+	#[serde(default)]
+	pub tags:     HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FfprobeStream {
+	pub codec_type:  Option<String>,
+	pub codec_name:  Option<String>,
+	pub sample_rate: Option<String>,
+	pub width:       Option<u32>,
+	pub height:      Option<u32>,
+}
+
+impl FfprobeInfo {
+	// Tag names vary in case across containers, e.g. `title` in ID3 and `TITLE` in
+	// FLAC
+	pub fn tag(&self, name: &str) -> Option<&str> {
+		self.format.tags.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+	}
+
+	pub fn sample_rate(&self) -> Option<&str> {
+		self.streams.iter().find_map(|s| s.sample_rate.as_deref())
+	}
+
+	// Embedded cover art shows up as a video stream
+	pub fn has_cover(&self) -> bool { self.video().is_some() }
+
+	pub fn video(&self) -> Option<&FfprobeStream> {
+		self.streams.iter().find(|s| s.codec_type.as_deref() == Some("video"))
+	}
+}
+
+pub async fn ffprobe(path: &Path) -> Result<FfprobeInfo, PeekError> {
+	let output = Command::new("ffprobe")
+		.args(["-v", "quiet", "-print_format", "json", "-show_format", "-show_streams"])
+		.arg(path)
+		.kill_on_drop(true)
+		.output()
+		.await?;
+
+	if !output.status.success() {
+		return Err(String::from_utf8_lossy(&output.stderr).to_string().into());
+	}
+	serde_json::from_slice(&output.stdout).map_err(|e| e.to_string().into())
+}
+
+pub async fn ffmpeg_cover(src: &Path, dest: &Path) -> Result<(), PeekError> {
+	let output = Command::new("ffmpeg")
+		.args(["-v", "quiet", "-i"])
+		.arg(src)
+		.args(["-an", "-frames:v", "1", "-vf", &format!("scale='min({},iw)':-2", PREVIEW.max_width)])
+		.args(["-c:v", "mjpeg", "-q:v", "6", "-f", "image2", "-y"])
+		.arg(dest)
+		.kill_on_drop(true)
+		.output()
+		.await?;
+
+	if !output.status.success() {
+		return Err(String::from_utf8_lossy(&output.stderr).to_string().into());
+	}
+	Ok(())
+}
@@ -2,9 +2,9 @@ use std::collections::BTreeMap;
 
 use anyhow::{bail, Result};
 use futures::TryFutureExt;
-use tokio::process::Command;
+use tokio::{fs::File, io::AsyncReadExt, process::Command};
 use tracing::error;
-use yazi_shared::{fs::Url, MimeKind};
+use yazi_shared::{fs::Url, mime_from_magic, MimeKind, MIME_MAGIC_LEN};
 
 async fn _file(files: &[&Url]) -> Result<BTreeMap<Url, String>> {
 	if files.is_empty() {
@@ -17,10 +17,12 @@ async fn _file(files: &[&Url]) -> Result<BTreeMap<Url, String>> {
 		.kill_on_drop(true)
 		.output()
 		.inspect_err(|e| error!("failed to execute `file`: {}", e))
-		.await?;
+		.await
+		.map(|o| o.stdout)
+		.unwrap_or_default();
 
-	let output = String::from_utf8_lossy(&output.stdout);
-	let mimes = BTreeMap::from_iter(
+	let output = String::from_utf8_lossy(&output);
+	let mut mimes = BTreeMap::from_iter(
 		files
 			.iter()
 			.zip(output.trim().lines())
@@ -28,6 +30,15 @@ async fn _file(files: &[&Url]) -> Result<BTreeMap<Url, String>> {
 			.map(|(&f, m)| (f.clone(), m.to_string())),
 	);
 
+	// `file` knows nothing about some formats, or isn't installed at all
+	for &f in files {
+		if mimes.get(f).map_or(true, |m| m == "application/octet-stream") {
+			if let Some(m) = sniff(f).await {
+				mimes.insert(f.clone(), m.to_owned());
+			}
+		}
+	}
+
 	if mimes.is_empty() {
 		error!("failed to get mime types: {:?}", files);
 		bail!("failed to get mime types");
@@ -35,6 +46,12 @@ async fn _file(files: &[&Url]) -> Result<BTreeMap<Url, String>> {
 	Ok(mimes)
 }
 
+async fn sniff(path: &Url) -> Option<&'static str> {
+	let mut buf = Vec::with_capacity(MIME_MAGIC_LEN);
+	File::open(path).await.ok()?.take(MIME_MAGIC_LEN as u64).read_to_end(&mut buf).await.ok()?;
+	mime_from_magic(&buf)
+}
+
 pub async fn file(files: &[impl AsRef<Url>]) -> Result<BTreeMap<Url, String>> {
 	_file(&files.iter().map(AsRef::as_ref).collect::<Vec<_>>()).await
 }
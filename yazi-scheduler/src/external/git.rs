@@ -0,0 +1,123 @@
+use std::{collections::BTreeMap, path::{Component, Path, PathBuf}};
+
+use anyhow::{bail, Result};
+use tokio::{fs, process::Command};
+use yazi_shared::fs::{GitStatus, Url};
+
+// The status of each entry of `dir` with changes in it, from a single `git
+// status` over the whole directory. A directory counts as changed by what's
+// inside it, and `dir` itself is in the map if it's untracked or ignored as a
+// whole.
+pub async fn git_status(dir: &Url) -> Result<BTreeMap<Url, GitStatus>> {
+	let root = Command::new("git")
+		.arg("-C")
+		.arg(dir)
+		.args(["rev-parse", "--show-toplevel"])
+		.kill_on_drop(true)
+		.output()
+		.await?;
+	if !root.status.success() {
+		bail!("{:?} is not in a git repository", dir);
+	}
+
+	let output = Command::new("git")
+		.arg("-C")
+		.arg(dir)
+		.args(["status", "--porcelain=v1", "-z", "--ignored=matching", "--untracked-files=normal"])
+		.args(["--", "."])
+		.kill_on_drop(true)
+		.output()
+		.await?;
+	if !output.status.success() {
+		bail!("git status failed in {:?}", dir);
+	}
+
+	let root = String::from_utf8_lossy(&root.stdout).trim_end_matches('\n').to_owned();
+	let real = fs::canonicalize(dir).await?;
+	Ok(git_parse(Path::new(&root), &real, dir, &output.stdout))
+}
+
+// `real` is the canonical path of `dir`, as git reports paths under `root`
+fn git_parse(root: &Path, real: &Path, dir: &Url, output: &[u8]) -> BTreeMap<Url, GitStatus> {
+	let mut map = BTreeMap::new();
+	let mut records = output.split(|&b| b == 0).filter(|r| r.len() > 3);
+	while let Some(r) = records.next() {
+		// A rename or copy is followed by the path it came from
+		if matches!(r[0], b'R' | b'C') {
+			records.next();
+		}
+		let Some(status) = git_code(r[0], r[1]) else {
+			continue;
+		};
+
+		let path = root.join(git_path(&r[3..]));
+		let url = match path.strip_prefix(real).ok().and_then(|p| p.components().next()) {
+			Some(Component::Normal(name)) => dir.join(name),
+			_ if real.starts_with(&path) => dir.clone(),
+			_ => continue,
+		};
+
+		let old = map.entry(url).or_insert(status);
+		if git_rank(status) > git_rank(*old) {
+			*old = status;
+		}
+	}
+	map
+}
+
+#[inline]
+fn git_path(b: &[u8]) -> PathBuf {
+	#[cfg(unix)]
+	return PathBuf::from(<std::ffi::OsStr as std::os::unix::ffi::OsStrExt>::from_bytes(b));
+	#[cfg(windows)]
+	return String::from_utf8_lossy(b).into_owned().into();
+}
+
+fn git_code(x: u8, y: u8) -> Option<GitStatus> {
+	Some(match (x, y) {
+		(b'?', b'?') => GitStatus::Untracked,
+		(b'!', b'!') => GitStatus::Ignored,
+		(b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => GitStatus::Conflicted,
+		(_, y) if y != b' ' => GitStatus::Modified,
+		(x, _) if x != b' ' => GitStatus::Staged,
+		_ => return None,
+	})
+}
+
+// Which status wins for a directory holding entries of several
+#[inline]
+fn git_rank(s: GitStatus) -> u8 {
+	match s {
+		GitStatus::Ignored => 0,
+		GitStatus::Untracked => 1,
+		GitStatus::Staged => 2,
+		GitStatus::Modified => 3,
+		GitStatus::Conflicted => 4,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_git_parse() {
+		let (root, dir) = (Path::new("/repo"), Url::from("/repo/src"));
+		let output = b" M src/a.rs\0M  src/b.rs\0R  src/c.rs\0src/old.rs\0?? src/new/\0!! src/target/\0MM src/d/e.rs\0?? src/d/f.rs\0UU src/g.rs\0 M README.md\0";
+
+		let map = git_parse(root, Path::new("/repo/src"), &dir, output);
+		assert_eq!(map.get(&dir.join("a.rs")), Some(&GitStatus::Modified));
+		assert_eq!(map.get(&dir.join("b.rs")), Some(&GitStatus::Staged));
+		assert_eq!(map.get(&dir.join("c.rs")), Some(&GitStatus::Staged));
+		assert_eq!(map.get(&dir.join("old.rs")), None);
+		assert_eq!(map.get(&dir.join("new")), Some(&GitStatus::Untracked));
+		assert_eq!(map.get(&dir.join("target")), Some(&GitStatus::Ignored));
+		assert_eq!(map.get(&dir.join("d")), Some(&GitStatus::Modified));
+		assert_eq!(map.get(&dir.join("g.rs")), Some(&GitStatus::Conflicted));
+		assert_eq!(map.len(), 7);
+
+		// A directory that's untracked as a whole
+		let map = git_parse(root, Path::new("/repo/src/new/x"), &dir, b"?? src/new/\0");
+		assert_eq!(map.get(&dir), Some(&GitStatus::Untracked));
+	}
+}
@@ -5,7 +5,8 @@ use tokio::{io::{AsyncBufReadExt, AsyncReadExt, BufReader}, process::Command, se
 use yazi_config::PREVIEW;
 use yazi_shared::PeekError;
 
-pub async fn jq(path: &Path, skip: usize, limit: usize) -> Result<String, PeekError> {
+// Also tells whether there are more lines after this page
+pub async fn jq(path: &Path, skip: usize, limit: usize) -> Result<(String, bool), PeekError> {
 	let mut child = Command::new("jq")
 		.args(["-C", "--tab", "."])
 		.arg(path)
@@ -14,12 +15,13 @@ pub async fn jq(path: &Path, skip: usize, limit: usize) -> Result<String, PeekEr
 		.kill_on_drop(true)
 		.spawn()?;
 
-	let mut i = 0;
+	let (mut i, mut more) = (0, false);
 	let mut it = BufReader::new(child.stdout.take().unwrap()).lines();
 	let mut lines = String::new();
 	while let Ok(Some(line)) = it.next_line().await {
 		i += 1;
 		if i > skip + limit {
+			more = true;
 			break;
 		}
 		if i > skip {
@@ -40,6 +42,6 @@ pub async fn jq(path: &Path, skip: usize, limit: usize) -> Result<String, PeekEr
 	if skip > 0 && i < skip + limit {
 		Err(PeekError::Exceed(i.saturating_sub(limit)))
 	} else {
-		Ok(lines.replace('\t', &" ".repeat(PREVIEW.tab_size as usize)))
+		Ok((lines.replace('\t', &" ".repeat(PREVIEW.tab_size as usize)), more))
 	}
 }
@@ -25,13 +25,23 @@ pub struct LsarFile {
 	pub compressed_size:  Option<usize>,
 	#[serde(rename = "XADCompressionName")]
 	pub compression_name: Option<String>,
+	#[serde(rename = "XADLinkDestination")]
+	pub link:             Option<String>,
 
 	#[serde(skip)]
 	pub attributes: Option<LsarAttr>,
+	#[serde(skip)]
+	pub is_dir:     bool,
 }
 
+// Also returns how many files there are after this page, and the unpacked
+// size of all of them
 #[allow(clippy::manual_map)]
-pub async fn lsar(path: &Path, skip: usize, limit: usize) -> Result<Vec<LsarFile>, PeekError> {
+pub async fn lsar(
+	path: &Path,
+	skip: usize,
+	limit: usize,
+) -> Result<(Vec<LsarFile>, usize, u64), PeekError> {
 	let output = Command::new("lsar").arg("-j").arg(path).kill_on_drop(true).output().await?;
 	if !output.status.success() {
 		return Err(String::from_utf8_lossy(&output.stderr).to_string().into());
@@ -46,8 +56,10 @@ pub async fn lsar(path: &Path, skip: usize, limit: usize) -> Result<Vec<LsarFile
 	let output = String::from_utf8_lossy(&output.stdout);
 	let contents = serde_json::from_str::<Outer>(output.trim()).map_err(|e| anyhow!(e))?.contents;
 
-	let mut i = 0;
-	let mut files = Vec::with_capacity(limit);
+	let (mut i, total) = (0, contents.len());
+	let size = contents.iter().filter_map(|c| c.get("XADFileSize")?.as_u64()).sum();
+
+	let mut files = Vec::with_capacity(limit.min(total));
 	for content in contents {
 		i += 1;
 		if i > skip + limit {
@@ -66,14 +78,20 @@ pub async fn lsar(path: &Path, skip: usize, limit: usize) -> Result<Vec<LsarFile
 			None
 		};
 
+		let is_dir = content
+			.get("XADIsDirectory")
+			.is_some_and(|v| v.as_bool() == Some(true) || v.as_u64() == Some(1));
+
 		let mut file = serde_json::from_value::<LsarFile>(content).map_err(|e| anyhow!(e))?;
 		file.attributes = attributes;
+		file.is_dir = is_dir || file.name.ends_with('/');
 		files.push(file);
 	}
 
 	if skip > 0 && files.len() < limit {
 		Err(PeekError::Exceed(i.saturating_sub(limit)))
 	} else {
-		Ok(files)
+		let rest = total.saturating_sub(skip + files.len());
+		Ok((files, rest, size))
 	}
 }
@@ -0,0 +1,35 @@
+use std::path::Path;
+
+use tokio::{fs, process::Command};
+use yazi_config::PREVIEW;
+use yazi_shared::PeekError;
+
+use super::available;
+
+// ImageMagick 6 only ships `convert`, which on Windows is a system tool instead
+fn bin() -> &'static str {
+	if cfg!(unix) && !available("magick") && available("convert") {
+		"convert"
+	} else {
+		"magick"
+	}
+}
+
+pub async fn magick_font(src: &Path, dest: &Path, text: &str) -> Result<(), PeekError> {
+	let output = Command::new(bin())
+		.args(["-background", "white", "-fill", "black", "-pointsize", "48", "-size"])
+		.arg(format!("{}x", PREVIEW.max_width))
+		.arg("-font")
+		.arg(src)
+		.arg(format!("caption:{text}"))
+		.arg(format!("png:{}", dest.display()))
+		.kill_on_drop(true)
+		.output()
+		.await?;
+
+	if !output.status.success() {
+		fs::remove_file(dest).await.ok();
+		return Err(String::from_utf8_lossy(&output.stderr).to_string().into());
+	}
+	Ok(())
+}
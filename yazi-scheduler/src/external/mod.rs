@@ -1,25 +1,51 @@
 mod clipboard;
+mod compress;
+mod decompress;
+mod desktop;
+mod exiftool;
+mod fc_scan;
 mod fd;
 mod ffmpegthumbnailer;
+mod ffprobe;
 mod file;
 mod fzf;
+mod git;
 mod jq;
 mod lsar;
+mod magick;
+mod pandoc;
 mod pdftoppm;
+mod pdftotext;
+mod previewer;
 mod rg;
+mod rsvg_convert;
 mod shell;
+mod tools;
 mod unar;
 mod zoxide;
 
 pub use clipboard::*;
+pub use compress::*;
+pub use decompress::*;
+pub use desktop::*;
+pub use exiftool::*;
+pub use fc_scan::*;
 pub use fd::*;
 pub use ffmpegthumbnailer::*;
+pub use ffprobe::*;
 pub use file::*;
 pub use fzf::*;
+pub use git::*;
 pub use jq::*;
 pub use lsar::*;
+pub use magick::*;
+pub use pandoc::*;
 pub use pdftoppm::*;
+pub use pdftotext::*;
+pub use previewer::*;
 pub use rg::*;
+pub use rsvg_convert::*;
 pub use shell::*;
+pub use tools::*;
 pub use unar::*;
 pub use zoxide::*;
@@ -0,0 +1,18 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use tokio::process::Command;
+
+pub async fn pandoc(path: &Path, from: &str) -> Result<String> {
+	let output = Command::new("pandoc")
+		.args(["--from", from, "--to", "plain", "--wrap", "none"])
+		.arg(path)
+		.kill_on_drop(true)
+		.output()
+		.await?;
+
+	if !output.status.success() {
+		bail!("failed to convert {:?}: {}", path, String::from_utf8_lossy(&output.stderr));
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
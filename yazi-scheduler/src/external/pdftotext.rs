@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use regex::Regex;
+use tokio::process::Command;
+use yazi_shared::PeekError;
+
+pub async fn pdftotext(src: &Path, skip: usize, limit: usize) -> Result<String, PeekError> {
+	let page = (skip + 1).to_string();
+	let output = Command::new("pdftotext")
+		.args(["-layout", "-f", &page, "-l", &page])
+		.arg(src)
+		.arg("-")
+		.kill_on_drop(true)
+		.output()
+		.await?;
+
+	if !output.status.success() {
+		let s = String::from_utf8_lossy(&output.stderr);
+		let pages: usize = Regex::new(r"the last page \((\d+)\)")
+			.unwrap()
+			.captures(&s)
+			.map(|cap| cap[1].parse().unwrap())
+			.unwrap_or(0);
+
+		return if pages > 0 { Err(PeekError::Exceed(pages - 1)) } else { Err(s.to_string().into()) };
+	}
+
+	let s = String::from_utf8_lossy(&output.stdout);
+	Ok(s.trim_start_matches('\n').lines().take(limit).collect::<Vec<_>>().join("\n"))
+}
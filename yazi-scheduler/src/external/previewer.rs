@@ -0,0 +1,56 @@
+use std::{path::Path, process::Stdio};
+
+use anyhow::Result;
+use tokio::{io::{self, AsyncBufReadExt, BufReader}, process::Command};
+
+// Runs a user previewer as `exec <path> <width> <height>`, and reads up to
+// `max` lines of its output; it's killed once that many have been read
+pub async fn previewer(
+	exec: &str,
+	path: &Path,
+	size: (u16, u16),
+	max: usize,
+) -> Result<Vec<String>> {
+	let (w, h) = (size.0.to_string(), size.1.to_string());
+
+	#[cfg(unix)]
+	let mut cmd = {
+		let mut cmd = Command::new("sh");
+		cmd.args(["-c", exec, ""]).arg(path).args([&w, &h]);
+		cmd
+	};
+	#[cfg(windows)]
+	let mut cmd = {
+		let mut cmd = Command::new("cmd");
+		cmd.args(["/C", exec]).arg(path).args([&w, &h]);
+		cmd
+	};
+
+	let mut child = cmd
+		.env("YAZI_PREVIEW_WIDTH", &w)
+		.env("YAZI_PREVIEW_HEIGHT", &h)
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::null())
+		.kill_on_drop(true)
+		.spawn()?;
+
+	let mut it = BufReader::new(child.stdout.take().unwrap()).lines();
+	let mut lines = Vec::with_capacity(max.min(1000));
+	while lines.len() < max {
+		match it.next_line().await {
+			Ok(Some(line)) => lines.push(line),
+			Ok(None) => break,
+			Err(e) if e.kind() == io::ErrorKind::InvalidData => break,
+			Err(e) => Err(e)?,
+		}
+	}
+
+	if lines.is_empty() {
+		let status = child.wait().await?;
+		if !status.success() {
+			anyhow::bail!("exited with {status}");
+		}
+	}
+	Ok(lines)
+}
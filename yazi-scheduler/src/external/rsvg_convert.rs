@@ -0,0 +1,23 @@
+use std::path::Path;
+
+use tokio::{fs, process::Command};
+use yazi_config::PREVIEW;
+use yazi_shared::PeekError;
+
+pub async fn rsvg_convert(src: &Path, dest: &Path) -> Result<(), PeekError> {
+	// SVGs without an intrinsic size are rendered into the maximum preview size
+	let output = Command::new("rsvg-convert")
+		.args(["-f", "png", "-a", "-w", &PREVIEW.max_width.to_string()])
+		.args(["-h", &PREVIEW.max_height.to_string(), "-o"])
+		.arg(dest)
+		.arg(src)
+		.kill_on_drop(true)
+		.output()
+		.await?;
+
+	if !output.status.success() {
+		fs::remove_file(dest).await.ok();
+		return Err(String::from_utf8_lossy(&output.stderr).to_string().into());
+	}
+	Ok(())
+}
@@ -1,4 +1,4 @@
-use std::{ffi::OsString, process::Stdio};
+use std::{ffi::OsString, path::PathBuf, process::Stdio};
 
 use anyhow::Result;
 use tokio::process::{Child, Command};
@@ -8,6 +8,8 @@ pub struct ShellOpt {
 	pub args:   Vec<OsString>,
 	pub piped:  bool,
 	pub orphan: bool,
+	pub env:    Vec<(String, String)>,
+	pub cwd:    Option<PathBuf>,
 }
 
 impl ShellOpt {
@@ -31,7 +33,12 @@ impl ShellOpt {
 pub fn shell(opt: ShellOpt) -> Result<Child> {
 	#[cfg(unix)]
 	return Ok(unsafe {
-		Command::new("sh")
+		let mut cmd = Command::new("sh");
+		if let Some(cwd) = &opt.cwd {
+			cmd.current_dir(cwd);
+		}
+		cmd
+			.envs(opt.env.iter().map(|(k, v)| (k, v)))
 			.arg("-c")
 			.stdin(opt.stdio())
 			.stdout(opt.stdio())
@@ -54,8 +61,14 @@ pub fn shell(opt: ShellOpt) -> Result<Child> {
 		let args: Vec<String> = opt.args.iter().map(|s| s.to_string_lossy().to_string()).collect();
 		let args_: Vec<&str> = args.iter().map(|s| s.as_ref()).collect();
 		let expanded = parser::parse(opt.cmd.to_string_lossy().as_ref(), &args_);
+
+		let mut cmd = Command::new("cmd");
+		if let Some(cwd) = &opt.cwd {
+			cmd.current_dir(cwd);
+		}
 		Ok(
-			Command::new("cmd")
+			cmd
+				.envs(opt.env.iter().map(|(k, v)| (k, v)))
 				.arg("/C")
 				.args(&expanded)
 				.stdin(opt.stdio())
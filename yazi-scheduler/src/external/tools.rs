@@ -0,0 +1,68 @@
+use std::{collections::HashMap, env, path::Path};
+
+use parking_lot::Mutex;
+
+// Tools the previews and commands use when present
+pub const OPTIONAL_TOOLS: &[&str] = &[
+	"exiftool",
+	"fc-scan",
+	"fd",
+	"ffmpegthumbnailer",
+	"ffprobe",
+	"fzf",
+	"jq",
+	"lsar",
+	"magick",
+	"pandoc",
+	"pdftoppm",
+	"pdftotext",
+	"rg",
+	"rsvg-convert",
+	"unar",
+	"zoxide",
+];
+
+static AVAILABLE: Mutex<Option<HashMap<&'static str, bool>>> = Mutex::new(None);
+
+// Whether `bin` can be found in $PATH, looked up once per name
+pub fn available(bin: &'static str) -> bool {
+	let mut cache = AVAILABLE.lock();
+	*cache.get_or_insert_with(HashMap::new).entry(bin).or_insert_with(|| lookup(bin))
+}
+
+fn lookup(bin: &str) -> bool {
+	let Some(paths) = env::var_os("PATH") else {
+		return false;
+	};
+
+	env::split_paths(&paths).any(|dir| {
+		#[cfg(unix)]
+		return executable(&dir.join(bin));
+		#[cfg(windows)]
+		return ["exe", "cmd", "bat"].iter().any(|ext| executable(&dir.join(bin).with_extension(ext)));
+	})
+}
+
+fn executable(path: &Path) -> bool {
+	let Ok(meta) = path.metadata() else {
+		return false;
+	};
+
+	#[cfg(unix)]
+	return meta.is_file()
+		&& std::os::unix::fs::PermissionsExt::mode(&meta.permissions()) & 0o111 != 0;
+	#[cfg(windows)]
+	return meta.is_file();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[cfg(unix)]
+	#[test]
+	fn test_available() {
+		assert!(available("sh"));
+		assert!(!available("yazi-surely-not-installed"));
+	}
+}
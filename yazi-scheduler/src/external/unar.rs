@@ -1,7 +1,29 @@
 use std::{path::Path, process::Stdio};
 
 use anyhow::{bail, Result};
-use tokio::{io::AsyncReadExt, process::Command};
+use tokio::{io::AsyncReadExt, process::{Child, Command}};
+
+// Extracts everything in `path` right into `to`, without a containing directory
+pub fn unar(path: &Path, to: &Path) -> Result<Child> {
+	Ok(
+		Command::new("unar")
+			.args(["-D", "-f", "-o"])
+			.args([to, path])
+			.stdin(Stdio::null())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.kill_on_drop(true)
+			.spawn()?,
+	)
+}
+
+// The size of the entry unar reports to have extracted on `line`, which looks
+// like `  foo/bar.txt  (123 B)... OK.`
+pub fn unar_extracted(line: &str) -> Option<u64> {
+	let line = line.strip_suffix("... OK.")?;
+	let (_, size) = line.rsplit_once(" (")?;
+	size.strip_suffix(" B)")?.parse().ok()
+}
 
 pub async fn unar_head(path: &Path, target: &Path) -> Result<Vec<u8>> {
 	let mut child = Command::new("unar")
@@ -21,3 +43,16 @@ pub async fn unar_head(path: &Path, target: &Path) -> Result<Vec<u8>> {
 	}
 	Ok(buf)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::unar_extracted;
+
+	#[test]
+	fn test_unar_extracted() {
+		assert_eq!(unar_extracted("  foo/bar (1).txt  (123 B)... OK."), Some(123));
+		assert_eq!(unar_extracted("  foo/  (dir)... OK."), None);
+		assert_eq!(unar_extracted("  foo.txt  (123 B)... Failed! (Checksum error)"), None);
+		assert_eq!(unar_extracted("foo.zip: Zip"), None);
+	}
+}
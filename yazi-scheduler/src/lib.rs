@@ -1,7 +1,9 @@
 #![allow(clippy::unit_arg)]
 
+mod audit;
 mod blocker;
 pub mod external;
+mod power;
 mod running;
 mod scheduler;
 mod task;
@@ -0,0 +1,116 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, Arc}, time::Duration};
+
+use tokio::time::sleep;
+use yazi_config::TASKS;
+
+const POWER_INTERVAL: Duration = Duration::from_secs(5);
+
+// Whether speculative precaching should hold off, rechecked periodically
+// against the battery and the load average, as configured in `[tasks]`
+#[derive(Default)]
+pub(crate) struct Power {
+	throttled: AtomicBool,
+}
+
+impl Power {
+	pub(crate) fn start() -> Arc<Self> {
+		let power = Arc::new(Self::default());
+		if TASKS.precache_battery && TASKS.precache_load <= 0.0 {
+			return power;
+		}
+
+		tokio::spawn({
+			let power = power.clone();
+			async move {
+				loop {
+					let throttled = Self::check().await;
+					if power.throttled.swap(throttled, Ordering::Relaxed) != throttled {
+						tracing::debug!("Precaching {}", if throttled { "paused" } else { "resumed" });
+					}
+					sleep(POWER_INTERVAL).await;
+				}
+			}
+		});
+		power
+	}
+
+	#[inline]
+	pub(crate) fn throttled(&self) -> bool { self.throttled.load(Ordering::Relaxed) }
+
+	pub(crate) async fn ready(&self) {
+		while self.throttled() {
+			sleep(Duration::from_secs(1)).await;
+		}
+	}
+
+	async fn check() -> bool {
+		if TASKS.precache_load > 0.0 && Self::load().is_some_and(|l| l > TASKS.precache_load) {
+			return true;
+		}
+		!TASKS.precache_battery && Self::on_battery().await
+	}
+
+	// The 1-minute load average per CPU core
+	fn load() -> Option<f32> {
+		#[cfg(unix)]
+		{
+			let mut avg = [0f64; 1];
+			if unsafe { libc::getloadavg(avg.as_mut_ptr(), 1) } != 1 {
+				return None;
+			}
+
+			let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+			Some((avg[0] / cores as f64) as f32)
+		}
+		#[cfg(windows)]
+		None
+	}
+
+	async fn on_battery() -> bool {
+		#[cfg(target_os = "macos")]
+		{
+			let output = tokio::process::Command::new("pmset").args(["-g", "batt"]).output().await;
+			output.is_ok_and(|o| pmset_on_battery(&String::from_utf8_lossy(&o.stdout)))
+		}
+		#[cfg(not(target_os = "macos"))]
+		{
+			let Ok(mut it) = tokio::fs::read_dir("/sys/class/power_supply").await else {
+				return false;
+			};
+
+			// While plugged in, batteries are charging, full, or not charging
+			while let Ok(Some(entry)) = it.next_entry().await {
+				let path = entry.path();
+				if tokio::fs::read_to_string(path.join("type")).await.is_ok_and(|s| s.trim() == "Battery")
+					&& tokio::fs::read_to_string(path.join("status"))
+						.await
+						.is_ok_and(|s| s.trim() == "Discharging")
+				{
+					return true;
+				}
+			}
+			false
+		}
+	}
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn pmset_on_battery(output: &str) -> bool {
+	output.lines().next().is_some_and(|l| l.contains("'Battery Power'"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_pmset_on_battery() {
+		assert!(pmset_on_battery(
+			"Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t81%; discharging;"
+		));
+		assert!(!pmset_on_battery(
+			"Now drawing from 'AC Power'\n -InternalBattery-0 (id=1234)\t100%; charged; 'Battery Power'"
+		));
+		assert!(!pmset_on_battery(""));
+	}
+}
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, time::Duration};
 
 use futures::future::BoxFuture;
 use yazi_config::TASKS;
@@ -16,6 +16,10 @@ pub struct Running {
 }
 
 impl Running {
+	pub fn sample(&mut self, elapsed: Duration) {
+		self.all.values_mut().for_each(|t| t.sample(elapsed));
+	}
+
 	pub(super) fn add(&mut self, kind: TaskKind, name: String) -> usize {
 		self.incr += 1;
 		self.all.insert(self.incr, Task::new(self.incr, kind, name));
@@ -1,19 +1,22 @@
-use std::{ffi::OsStr, sync::Arc, time::Duration};
+use std::{collections::hash_map::Entry, ffi::{OsStr, OsString}, mem, path::{Path, PathBuf}, sync::Arc, time::{Duration, SystemTime}};
 
 use futures::{future::BoxFuture, FutureExt};
 use parking_lot::RwLock;
-use tokio::{fs, select, sync::{mpsc::{self, UnboundedReceiver}, oneshot}};
+use tokio::{fs, select, sync::{mpsc::{self, UnboundedReceiver}, oneshot, Semaphore}, time::sleep};
 use yazi_config::{open::Opener, TASKS};
-use yazi_shared::{emit, event::Exec, fs::{unique_path, Url}, Layer, Throttle};
+use yazi_shared::{emit, event::Exec, fs::{expand_path, unique_path, Url}, Layer, Throttle};
 
 use super::{Running, TaskOp, TaskStage};
-use crate::{workers::{File, FileOpDelete, FileOpLink, FileOpPaste, FileOpTrash, Precache, PrecacheOpMime, PrecacheOpSize, Process, ProcessOpOpen}, TaskKind};
+use crate::{external::ArchiveFormat, workers::{File, FileOp, FileOpCompress, FileOpDelete, FileOpExtract, FileOpLink, FileOpPaste, FileOpRestore, FileOpTrash, Precache, PrecacheOpMime, PrecacheOpSize, Process, ProcessOpOpen}, TaskKind};
 
 pub struct Scheduler {
 	file:     Arc<File>,
 	precache: Arc<Precache>,
 	process:  Arc<Process>,
 
+	transfers: async_channel::Sender<(usize, FileOp)>,
+	precaches: Arc<Semaphore>,
+
 	todo:        async_channel::Sender<BoxFuture<'static, ()>>,
 	prog:        mpsc::UnboundedSender<TaskOp>,
 	pub running: Arc<RwLock<Running>>,
@@ -23,12 +26,16 @@ impl Scheduler {
 	pub fn start() -> Self {
 		let (todo_tx, todo_rx) = async_channel::unbounded();
 		let (prog_tx, prog_rx) = mpsc::unbounded_channel();
+		let (transfer_tx, transfer_rx) = async_channel::unbounded();
 
 		let scheduler = Self {
 			file:     Arc::new(File::new(prog_tx.clone())),
 			precache: Arc::new(Precache::new(prog_tx.clone())),
 			process:  Arc::new(Process::new(prog_tx.clone())),
 
+			transfers: transfer_tx,
+			precaches: Arc::new(Semaphore::new(TASKS.max_precaches as usize)),
+
 			todo:    todo_tx,
 			prog:    prog_tx,
 			running: Default::default(),
@@ -40,6 +47,9 @@ impl Scheduler {
 		for _ in 0..TASKS.macro_workers {
 			scheduler.schedule_macro(todo_rx.clone());
 		}
+		for _ in 0..TASKS.max_transfers {
+			scheduler.schedule_transfer(transfer_rx.clone());
+		}
 		scheduler.progress(prog_rx);
 		scheduler
 	}
@@ -57,6 +67,7 @@ impl Scheduler {
 	fn schedule_macro(&self, rx: async_channel::Receiver<BoxFuture<'static, ()>>) {
 		let file = self.file.clone();
 		let precache = self.precache.clone();
+		let (transfers, precaches) = (self.transfers.clone(), self.precaches.clone());
 
 		let prog = self.prog.clone();
 		let running = self.running.clone();
@@ -72,18 +83,20 @@ impl Scheduler {
 					Ok(fut) = rx.recv() => {
 						fut.await;
 					}
-					Ok((id, mut op)) = file.recv() => {
-						if !running.read().exists(id) {
-							continue;
-						}
-						if let Err(e) = file.work(&mut op).await {
-							prog.send(TaskOp::Fail(id, format!("Failed to work on this task: {:?}", e))).ok();
+					Ok((id, op)) = file.recv() => {
+						// Copies and archives get a lane of their own, so that waiting
+						// for one doesn't hold up the other operations
+						if matches!(op, FileOp::Paste(_) | FileOp::Extract(_) | FileOp::Compress(_)) {
+							transfers.send((id, op)).await.ok();
+						} else {
+							Self::file_work(&file, &prog, &running, id, op).await;
 						}
 					}
 					Ok((id, mut op)) = precache.recv() => {
 						if !running.read().exists(id) {
 							continue;
 						}
+						let _permit = precaches.acquire().await;
 						if let Err(e) = precache.work(&mut op).await {
 							prog.send(TaskOp::Fail(id, format!("Failed to work on this task: {:?}", e))).ok();
 						}
@@ -93,6 +106,55 @@ impl Scheduler {
 		});
 	}
 
+	// At most `max_transfers` of them run at once
+	fn schedule_transfer(&self, rx: async_channel::Receiver<(usize, FileOp)>) {
+		let file = self.file.clone();
+		let prog = self.prog.clone();
+		let running = self.running.clone();
+
+		tokio::spawn(async move {
+			while let Ok((id, op)) = rx.recv().await {
+				Self::file_work(&file, &prog, &running, id, op).await;
+			}
+		});
+	}
+
+	async fn file_work(
+		file: &File,
+		prog: &mpsc::UnboundedSender<TaskOp>,
+		running: &RwLock<Running>,
+		id: usize,
+		mut op: FileOp,
+	) {
+		if !running.read().exists(id) {
+			return;
+		}
+
+		let canceled = select! {
+			result = file.work(&mut op) => {
+				match result {
+					Ok(()) => {}
+					Err(e) if file.retry(&mut op, &e) => {}
+					Err(e) => {
+						op.audit(&e.to_string());
+						prog.send(TaskOp::Fail(id, format!("Failed to work on this task: {:?}", e))).ok();
+					}
+				}
+				false
+			}
+			_ = Self::canceled(running, id) => true,
+		};
+		if canceled {
+			File::cleanup(&op).await;
+		}
+	}
+
+	async fn canceled(running: &RwLock<Running>, id: usize) {
+		while running.read().exists(id) {
+			sleep(Duration::from_millis(100)).await;
+		}
+	}
+
 	fn progress(&self, mut rx: UnboundedReceiver<TaskOp>) {
 		let todo = self.todo.clone();
 		let running = self.running.clone();
@@ -111,6 +173,7 @@ impl Scheduler {
 						if let Some(task) = running.get_mut(id) {
 							task.succ += succ;
 							task.processed += processed;
+							task.retrying = None;
 						}
 						if succ > 0 {
 							if let Some(fut) = running.try_remove(id, TaskStage::Pending) {
@@ -134,6 +197,23 @@ impl Scheduler {
 							}
 						}
 					}
+					TaskOp::Denied(id, op, reason) => {
+						if let Some(task) = running.write().get_mut(id) {
+							task.fail += 1;
+							task.denied.push(op);
+							task.logs.push_str(&reason);
+							task.logs.push('\n');
+
+							if let Some(logger) = &task.logger {
+								logger.send(reason).ok();
+							}
+						}
+					}
+					TaskOp::Retry(id, attempt, limit) => {
+						if let Some(task) = running.write().get_mut(id) {
+							task.retrying = Some((attempt, limit));
+						}
+					}
 					TaskOp::Log(id, line) => {
 						if let Some(task) = running.write().get_mut(id) {
 							task.logs.push_str(&line);
@@ -172,7 +252,7 @@ impl Scheduler {
 		));
 	}
 
-	pub fn file_cut(&self, from: Url, mut to: Url, force: bool) {
+	pub fn file_cut(&self, from: Url, mut to: Url, force: bool, verify: bool) {
 		let mut running = self.running.write();
 		let id = running.add(TaskKind::User, format!("Cut {:?} to {:?}", from, to));
 
@@ -197,13 +277,26 @@ impl Scheduler {
 				if !force {
 					to = unique_path(to).await;
 				}
-				file.paste(FileOpPaste { id, from, to, cut: true, follow: false, retry: 0 }).await.ok();
+				file
+					.paste(FileOpPaste {
+						id,
+						from,
+						to,
+						cut: true,
+						follow: false,
+						verify,
+						retry: 0,
+						attempt: 0,
+						copied: false,
+					})
+					.await
+					.ok();
 			}
 			.boxed()
 		});
 	}
 
-	pub fn file_copy(&self, from: Url, mut to: Url, force: bool) {
+	pub fn file_copy(&self, from: Url, mut to: Url, force: bool, verify: bool) {
 		let name = format!("Copy {:?} to {:?}", from, to);
 		let id = self.running.write().add(TaskKind::User, name);
 
@@ -213,13 +306,26 @@ impl Scheduler {
 				if !force {
 					to = unique_path(to).await;
 				}
-				file.paste(FileOpPaste { id, from, to, cut: false, follow: true, retry: 0 }).await.ok();
+				file
+					.paste(FileOpPaste {
+						id,
+						from,
+						to,
+						cut: false,
+						follow: true,
+						verify,
+						retry: 0,
+						attempt: 0,
+						copied: false,
+					})
+					.await
+					.ok();
 			}
 			.boxed()
 		});
 	}
 
-	pub fn file_link(&self, from: Url, mut to: Url, relative: bool, force: bool) {
+	pub fn file_link(&self, from: Url, mut to: Url, relative: bool, dereference: bool, force: bool) {
 		let name = format!("Link {from:?} to {to:?}");
 		let id = self.running.write().add(TaskKind::User, name);
 
@@ -230,7 +336,84 @@ impl Scheduler {
 					to = unique_path(to).await;
 				}
 				file
-					.link(FileOpLink { id, from, to, meta: None, resolve: false, relative, delete: false })
+					.link(FileOpLink {
+						id,
+						from,
+						to,
+						meta: None,
+						resolve: false,
+						dereference,
+						relative,
+						delete: false,
+					})
+					.await
+					.ok();
+			}
+			.boxed()
+		});
+	}
+
+	// Extracts `archive` into a staging directory in `dest`, from where the
+	// `moves` take its contents to their place
+	pub fn file_extract(
+		&self,
+		archive: Url,
+		dest: Url,
+		moves: Vec<(PathBuf, Url, bool)>,
+		length: u64,
+	) {
+		let name = format!("Extract {:?} to {:?}", archive, dest);
+		let id = self.running.write().add(TaskKind::User, name);
+
+		_ = self.todo.send_blocking({
+			let file = self.file.clone();
+			async move {
+				let name = archive.file_name().unwrap_or_default().to_string_lossy();
+				let staging = unique_path(dest.join(format!(".{name}.extracting"))).await;
+				file.extract(FileOpExtract { id, archive, staging, length, moves }).await.ok();
+			}
+			.boxed()
+		});
+	}
+
+	// Packs `names` in `cwd` into the archive `to`, written under a temporary
+	// name next to it until it's complete
+	pub fn file_compress(
+		&self,
+		format: ArchiveFormat,
+		cwd: Url,
+		names: Vec<OsString>,
+		to: Url,
+		force: bool,
+		dereference: bool,
+	) {
+		let name = format!(
+			"Compress {} item{} in {:?} to {:?}",
+			names.len(),
+			if names.len() == 1 { "" } else { "s" },
+			cwd,
+			to
+		);
+		let id = self.running.write().add(TaskKind::User, name);
+
+		_ = self.todo.send_blocking({
+			let file = self.file.clone();
+			async move {
+				let name = to.file_name().unwrap_or_default().to_string_lossy();
+				let staging =
+					unique_path(Url::from(to.with_file_name(format!(".{name}.compressing")))).await;
+				file
+					.compress(FileOpCompress {
+						id,
+						format,
+						cwd,
+						names,
+						staging,
+						to,
+						force,
+						dereference,
+						length: 0,
+					})
 					.await
 					.ok();
 			}
@@ -238,6 +421,12 @@ impl Scheduler {
 		});
 	}
 
+	// A task that's refused before it starts, kept in the list with the reason
+	pub fn file_refuse(&self, name: String, reason: String) {
+		let id = self.running.write().add(TaskKind::User, name);
+		self.prog.send(TaskOp::Fail(id, reason)).ok();
+	}
+
 	pub fn file_delete(&self, target: Url) {
 		let mut running = self.running.write();
 		let id = running.add(TaskKind::User, format!("Delete {:?}", target));
@@ -260,7 +449,7 @@ impl Scheduler {
 		_ = self.todo.send_blocking({
 			let file = self.file.clone();
 			async move {
-				file.delete(FileOpDelete { id, target, length: 0 }).await.ok();
+				file.delete(FileOpDelete { id, target, length: 0, attempt: 0 }).await.ok();
 			}
 			.boxed()
 		});
@@ -279,6 +468,70 @@ impl Scheduler {
 		});
 	}
 
+	// `restored` gets the target once it's out of the trash, and is dropped
+	// without it if the restore fails or is canceled
+	pub fn file_restore(
+		&self,
+		target: Url,
+		to: Url,
+		force: bool,
+		restored: Option<mpsc::UnboundedSender<Url>>,
+	) {
+		let name = format!("Restore {:?}", target);
+		let id = self.running.write().add(TaskKind::User, name);
+
+		_ = self.todo.send_blocking({
+			let file = self.file.clone();
+			async move {
+				file.restore(FileOpRestore { id, target, to, force, restored }).await.ok();
+			}
+			.boxed()
+		});
+	}
+
+	pub fn file_empty_trash(&self) {
+		let id = self.running.write().add(TaskKind::User, "Empty the trash".to_owned());
+
+		_ = self.todo.send_blocking({
+			let file = self.file.clone();
+			async move {
+				file.empty_trash(id).await.ok();
+			}
+			.boxed()
+		});
+	}
+
+	pub fn file_escalate(&self, id: usize) -> bool {
+		if cfg!(windows) || TASKS.escalate.is_empty() {
+			return false;
+		}
+
+		let denied = match self.running.write().get_mut(id) {
+			Some(task) => mem::take(&mut task.denied),
+			None => return false,
+		};
+		if denied.is_empty() {
+			return false;
+		}
+
+		// `<helper> <cmd> "${n}"...` for each item, chained with `&&`
+		let (mut exec, mut args) = (Vec::with_capacity(denied.len()), vec![]);
+		for op in &denied {
+			let (cmd, paths) = op.command();
+			let mut line = format!("{} {cmd}", TASKS.escalate);
+			for path in paths {
+				args.push(path);
+				line.push_str(&format!(" \"${{{}}}\"", args.len()));
+			}
+			exec.push(line);
+		}
+
+		let name = format!("Retry {} permission-denied items with `{}`", denied.len(), TASKS.escalate);
+		let opener = Opener { exec: exec.join(" && "), block: true, ..Default::default() };
+		self.process_spawn(name, &opener, args);
+		true
+	}
+
 	pub fn process_open(&self, opener: &Opener, args: &[impl AsRef<OsStr>]) {
 		let name = {
 			let s = format!("Execute `{}`", opener.exec);
@@ -286,10 +539,31 @@ impl Scheduler {
 			if args.is_empty() { s } else { format!("{} with `{}`", s, args) }
 		};
 
+		let args = args.iter().map(|a| a.as_ref().to_os_string()).collect::<Vec<_>>();
+		self.process_spawn(name, opener, args);
+	}
+
+	fn process_spawn(&self, name: String, opener: &Opener, args: Vec<OsString>) {
+		// A relative `cwd` is resolved against the directory of the first file
+		let cwd = opener.cwd.as_ref().map(expand_path).map(|p| match args.first() {
+			Some(a) if p.is_relative() => Path::new(a).parent().map(|d| d.join(&p)).unwrap_or(p),
+			_ => p,
+		});
+
 		let mut running = self.running.write();
 		let id = running.add(TaskKind::User, name);
 
 		let (cancel_tx, mut cancel_rx) = oneshot::channel();
+		let task = ProcessOpOpen {
+			id,
+			cmd: opener.exec.clone().into(),
+			args,
+			block: opener.block,
+			orphan: opener.orphan,
+			env: opener.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+			cwd,
+			cancel: cancel_tx,
+		};
 		running.hooks.insert(id, {
 			let running = self.running.clone();
 			Box::new(move |canceled: bool| {
@@ -303,38 +577,26 @@ impl Scheduler {
 			})
 		});
 
-		let args = args.iter().map(|a| a.as_ref().to_os_string()).collect::<Vec<_>>();
 		tokio::spawn({
 			let process = self.process.clone();
-			let opener = opener.clone();
 			async move {
-				process
-					.open(ProcessOpOpen {
-						id,
-						cmd: opener.exec.into(),
-						args,
-						block: opener.block,
-						orphan: opener.orphan,
-						cancel: cancel_tx,
-					})
-					.await
-					.ok();
+				process.open(task).await.ok();
 			}
 		});
 	}
 
-	pub fn precache_size(&self, targets: Vec<&Url>) {
-		let throttle = Arc::new(Throttle::new(targets.len(), Duration::from_millis(300)));
+	pub fn precache_size(&self, cwd: &Url, targets: Vec<&Url>) {
 		let mut handing = self.precache.size_handing.lock();
-		let mut running = self.running.write();
+		handing.retain(|u| u.parent_url().as_ref() == Some(cwd));
 
-		for target in targets {
-			if !handing.contains(target) {
-				handing.insert(target.clone());
-			} else {
-				continue;
-			}
+		let targets: Vec<_> = targets.into_iter().filter(|&u| handing.insert(u.clone())).collect();
+		if targets.is_empty() {
+			return;
+		}
 
+		let throttle = Arc::new(Throttle::new(targets.len(), Duration::from_millis(300)));
+		let mut running = self.running.write();
+		for target in targets {
 			let id = running.add(TaskKind::Preload, format!("Calculate the size of {:?}", target));
 			_ = self.todo.send_blocking({
 				let precache = self.precache.clone();
@@ -348,6 +610,30 @@ impl Scheduler {
 		}
 	}
 
+	// Runs once more when asked again meanwhile, so it ends with the directory
+	// as it is now
+	pub fn precache_git(&self, cwd: &Url) {
+		match self.precache.git_handing.lock().entry(cwd.clone()) {
+			Entry::Occupied(mut e) => {
+				e.insert(true);
+				return;
+			}
+			Entry::Vacant(e) => _ = e.insert(false),
+		}
+
+		let name = format!("Preload git status of {:?}", cwd);
+		let id = self.running.write().add(TaskKind::Preload, name);
+
+		_ = self.todo.send_blocking({
+			let precache = self.precache.clone();
+			let cwd = cwd.clone();
+			async move {
+				precache.git(id, cwd).await.ok();
+			}
+			.boxed()
+		});
+	}
+
 	pub fn precache_mime(&self, targets: Vec<Url>) {
 		let name = format!("Preload mimetype for {} files", targets.len());
 		let id = self.running.write().add(TaskKind::Preload, name);
@@ -361,21 +647,21 @@ impl Scheduler {
 		});
 	}
 
-	pub fn precache_image(&self, targets: Vec<Url>) {
+	pub fn precache_image(&self, targets: Vec<(Url, Option<SystemTime>)>) {
 		let name = format!("Precache of {} image files", targets.len());
 		let id = self.running.write().add(TaskKind::Preload, name);
 
 		self.precache.image(id, targets).ok();
 	}
 
-	pub fn precache_video(&self, targets: Vec<Url>) {
+	pub fn precache_video(&self, targets: Vec<(Url, Option<SystemTime>)>) {
 		let name = format!("Precache of {} video files", targets.len());
 		let id = self.running.write().add(TaskKind::Preload, name);
 
 		self.precache.video(id, targets).ok();
 	}
 
-	pub fn precache_pdf(&self, targets: Vec<Url>) {
+	pub fn precache_pdf(&self, targets: Vec<(Url, Option<SystemTime>)>) {
 		let name = format!("Precache of {} PDF files", targets.len());
 		let id = self.running.write().add(TaskKind::Preload, name);
 
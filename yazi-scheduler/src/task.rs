@@ -1,4 +1,7 @@
+use std::{ffi::OsString, time::Duration};
+
 use tokio::sync::mpsc;
+use yazi_shared::fs::Url;
 
 #[derive(Debug, Default)]
 pub struct Task {
@@ -14,14 +17,41 @@ pub struct Task {
 	pub found:     u64,
 	pub processed: u64,
 
+	// Bytes per second, smoothed across samples
+	pub speed:          f64,
+	pub(crate) sampled: u64,
+
+	// Attempt and limit of the retry in progress
+	pub retrying: Option<(u8, u8)>,
+
 	pub logs:   String,
 	pub logger: Option<mpsc::UnboundedSender<String>>,
+
+	pub denied: Vec<TaskDenied>,
 }
 
 impl Task {
 	pub fn new(id: usize, kind: TaskKind, name: String) -> Self {
 		Self { id, kind, name, ..Default::default() }
 	}
+
+	// Folds the bytes processed since the last sample into an exponential moving
+	// average
+	pub(crate) fn sample(&mut self, elapsed: Duration) {
+		const ALPHA: f64 = 0.3;
+
+		let rate = self.processed.saturating_sub(self.sampled) as f64 / elapsed.as_secs_f64();
+		self.sampled = self.processed;
+		self.speed = if self.speed == 0.0 { rate } else { ALPHA * rate + (1.0 - ALPHA) * self.speed };
+	}
+
+	// Seconds left, only known when the total size is
+	pub fn eta(&self) -> Option<u64> {
+		if self.found == 0 || self.speed < 1.0 || self.processed >= self.found {
+			return None;
+		}
+		Some(((self.found - self.processed) as f64 / self.speed).ceil() as u64)
+	}
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -31,8 +61,28 @@ pub enum TaskKind {
 	Preload,
 }
 
+#[derive(Clone, Debug)]
+pub enum TaskDenied {
+	Paste { from: Url, to: Url, cut: bool },
+	Delete(Url),
+}
+
+impl TaskDenied {
+	// The command to retry with, and its path arguments
+	pub fn command(&self) -> (&'static str, Vec<OsString>) {
+		match self {
+			Self::Paste { from, to, cut } => (if *cut { "mv -f --" } else { "cp -f --" }, vec![
+				from.as_os_str().to_owned(),
+				to.as_os_str().to_owned(),
+			]),
+			Self::Delete(target) => ("rm -f --", vec![target.as_os_str().to_owned()]),
+		}
+	}
+}
+
 #[derive(Debug)]
 pub struct TaskSummary {
+	pub id:   usize,
 	pub name: String,
 
 	pub total: u32,
@@ -41,11 +91,16 @@ pub struct TaskSummary {
 
 	pub found:     u64,
 	pub processed: u64,
+
+	pub speed:    u64,
+	pub eta:      Option<u64>,
+	pub retrying: Option<(u8, u8)>,
 }
 
 impl From<&Task> for TaskSummary {
 	fn from(task: &Task) -> Self {
 		TaskSummary {
+			id:   task.id,
 			name: task.name.clone(),
 
 			total: task.total,
@@ -54,6 +109,10 @@ impl From<&Task> for TaskSummary {
 
 			found:     task.found,
 			processed: task.processed,
+
+			speed:    task.speed as u64,
+			eta:      task.eta(),
+			retrying: task.retrying,
 		}
 	}
 }
@@ -70,6 +129,10 @@ pub enum TaskOp {
 	Fail(usize, String),
 	// id, line
 	Log(usize, String),
+	// id, attempt, limit
+	Retry(usize, u8, u8),
+	// id, op, reason
+	Denied(usize, TaskDenied, String),
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
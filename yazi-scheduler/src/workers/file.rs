@@ -1,13 +1,13 @@
-use std::{borrow::Cow, collections::VecDeque, fs::Metadata, path::{Path, PathBuf}};
+use std::{borrow::Cow, collections::VecDeque, ffi::OsString, fs::Metadata, path::{Path, PathBuf}};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use futures::{future::BoxFuture, FutureExt};
-use tokio::{fs, io::{self, ErrorKind::{AlreadyExists, NotFound}}, sync::mpsc};
+use tokio::{fs, io::{self, AsyncBufReadExt, BufReader, ErrorKind::{AlreadyExists, InvalidData, NotFound, PermissionDenied}, Lines}, process::{Child, ChildStderr, ChildStdout}, select, sync::mpsc, time::{sleep, Duration}};
 use tracing::warn;
 use yazi_config::TASKS;
-use yazi_shared::fs::{calculate_size, copy_with_progress, path_relative_to, Url};
+use yazi_shared::{fs::{calculate_size, copy_with_checksum, copy_with_progress, path_relative_to, Url}, readable_size};
 
-use crate::TaskOp;
+use crate::{audit::audit, external::{self, ArchiveFormat}, TaskDenied, TaskOp};
 
 pub(crate) struct File {
 	tx: async_channel::Sender<FileOp>,
@@ -16,40 +16,49 @@ pub(crate) struct File {
 	sch: mpsc::UnboundedSender<TaskOp>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) enum FileOp {
 	Paste(FileOpPaste),
 	Link(FileOpLink),
 	Delete(FileOpDelete),
 	Trash(FileOpTrash),
+	Restore(FileOpRestore),
+	Extract(FileOpExtract),
+	Compress(FileOpCompress),
 }
 
 #[derive(Clone, Debug)]
 pub(crate) struct FileOpPaste {
-	pub id:     usize,
-	pub from:   Url,
-	pub to:     Url,
-	pub cut:    bool,
-	pub follow: bool,
-	pub retry:  u8,
+	pub id:      usize,
+	pub from:    Url,
+	pub to:      Url,
+	pub cut:     bool,
+	pub follow:  bool,
+	pub verify:  bool,
+	pub retry:   u8,
+	pub attempt: u8,
+	// Once the copy is whole, `to` is no longer removed on cancellation
+	pub copied:  bool,
 }
 
 #[derive(Clone, Debug)]
 pub(crate) struct FileOpLink {
-	pub id:       usize,
-	pub from:     Url,
-	pub to:       Url,
-	pub meta:     Option<Metadata>,
-	pub resolve:  bool,
-	pub relative: bool,
-	pub delete:   bool,
+	pub id:          usize,
+	pub from:        Url,
+	pub to:          Url,
+	pub meta:        Option<Metadata>,
+	pub resolve:     bool,
+	pub dereference: bool,
+	pub relative:    bool,
+	pub delete:      bool,
 }
 
 #[derive(Clone, Debug)]
 pub(crate) struct FileOpDelete {
-	pub id:     usize,
-	pub target: Url,
-	pub length: u64,
+	pub id:      usize,
+	pub target:  Url,
+	pub length:  u64,
+	pub attempt: u8,
 }
 
 #[derive(Clone, Debug)]
@@ -59,6 +68,39 @@ pub(crate) struct FileOpTrash {
 	pub length: u64,
 }
 
+#[derive(Clone, Debug)]
+pub(crate) struct FileOpRestore {
+	pub id:       usize,
+	pub target:   Url,
+	pub to:       Url,
+	pub force:    bool,
+	pub restored: Option<mpsc::UnboundedSender<Url>>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct FileOpExtract {
+	pub id:      usize,
+	pub archive: Url,
+	pub staging: Url,
+	pub length:  u64,
+	// Paths in `staging`, where they go and whether to overwrite what's there;
+	// an empty path stands for `staging` itself
+	pub moves:   Vec<(PathBuf, Url, bool)>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct FileOpCompress {
+	pub id:          usize,
+	pub format:      ArchiveFormat,
+	pub cwd:         Url,
+	pub names:       Vec<OsString>,
+	pub staging:     Url,
+	pub to:          Url,
+	pub force:       bool,
+	pub dereference: bool,
+	pub length:      u64,
+}
+
 impl File {
 	pub(crate) fn new(sch: mpsc::UnboundedSender<TaskOp>) -> Self {
 		let (tx, rx) = async_channel::unbounded();
@@ -72,6 +114,9 @@ impl File {
 			FileOp::Link(t) => (t.id, FileOp::Link(t)),
 			FileOp::Delete(t) => (t.id, FileOp::Delete(t)),
 			FileOp::Trash(t) => (t.id, FileOp::Trash(t)),
+			FileOp::Restore(t) => (t.id, FileOp::Restore(t)),
+			FileOp::Extract(t) => (t.id, FileOp::Extract(t)),
+			FileOp::Compress(t) => (t.id, FileOp::Compress(t)),
 		})
 	}
 
@@ -79,15 +124,30 @@ impl File {
 		match op {
 			FileOp::Paste(task) => {
 				match fs::remove_file(&task.to).await {
+					Err(e) if e.kind() == PermissionDenied => return self.denied_paste(task, e),
 					Err(e) if e.kind() != NotFound => Err(e)?,
 					_ => {}
 				}
 
-				let mut it = copy_with_progress(&task.from, &task.to);
+				let mut it = if task.verify {
+					copy_with_checksum(&task.from, &task.to)
+				} else {
+					copy_with_progress(&task.from, &task.to)
+				};
 				while let Some(res) = it.recv().await {
 					match res {
 						Ok(0) => {
+							task.copied = true;
+							if TASKS.preserve_attrs {
+								self.preserve(task).await?;
+							}
+							// Only give up the source once the copy is known to be whole
 							if task.cut {
+								if !task.verify && !Self::same_len(&task.from, &task.to).await {
+									let reason = format!("Incomplete copy of {:?}, keeping the source", task.to);
+									task.audit(Some(&reason));
+									return self.fail(task.id, reason);
+								}
 								fs::remove_file(&task.from).await.ok();
 							}
 							break;
@@ -107,9 +167,17 @@ impl File {
 							task.retry += 1;
 							return Ok(self.tx.send(FileOp::Paste(task.clone())).await?);
 						}
+						Err(e) if e.kind() == PermissionDenied => return self.denied_paste(task, e),
+						// Keep the source, and the bad copy for inspection
+						Err(e) if e.kind() == InvalidData => {
+							let reason = format!("Verification failed for {:?}: {e}", task.to);
+							task.audit(Some(&reason));
+							return self.fail(task.id, reason);
+						}
 						Err(e) => Err(e)?,
 					}
 				}
+				task.audit(None);
 				self.sch.send(TaskOp::Adv(task.id, 1, 0))?;
 			}
 			FileOp::Link(task) => {
@@ -128,6 +196,21 @@ impl File {
 					Cow::Borrowed(task.from.as_path())
 				};
 
+				// Point at the final target of a chain of links, which has to exist
+				let src = if task.dereference {
+					let Ok(target) = fs::canonicalize(&src).await else {
+						self.log(task.id, format!("Link task skipped, target doesn't exist: {:?}", task))?;
+						return Ok(self.sch.send(TaskOp::Adv(task.id, 1, meta.len()))?);
+					};
+					if fs::canonicalize(&task.to).await.is_ok_and(|p| p == target) {
+						self.log(task.id, format!("Link task skipped, already the target: {:?}", task))?;
+						return Ok(self.sch.send(TaskOp::Adv(task.id, 1, meta.len()))?);
+					}
+					Cow::Owned(target)
+				} else {
+					src
+				};
+
 				let src = if task.relative {
 					path_relative_to(&src, &fs::canonicalize(task.to.parent().unwrap()).await?)
 				} else {
@@ -159,11 +242,20 @@ impl File {
 			}
 			FileOp::Delete(task) => {
 				if let Err(e) = fs::remove_file(&task.target).await {
+					if e.kind() == PermissionDenied {
+						audit("delete", &task.target, None, Some(&e.to_string()));
+						let reason = format!("Delete task denied: {:?}, {e}", task);
+						return Ok(self.sch.send(TaskOp::Denied(
+							task.id,
+							TaskDenied::Delete(task.target.clone()),
+							reason,
+						))?);
+					}
 					if e.kind() != NotFound && fs::symlink_metadata(&task.target).await.is_ok() {
-						self.fail(task.id, format!("Delete task failed: {:?}, {e}", task))?;
-						Err(e)?
+						Err(anyhow::Error::from(e).context(format!("Delete task failed: {:?}", task)))?
 					}
 				}
+				audit("delete", &task.target, None, None);
 				self.sch.send(TaskOp::Adv(task.id, 1, task.length))?
 			}
 			FileOp::Trash(task) => {
@@ -178,19 +270,138 @@ impl File {
 				{
 					trash::delete(&task.target)?;
 				}
+				audit("trash", &task.target, None, None);
 				self.sch.send(TaskOp::Adv(task.id, 1, task.length))?;
 			}
+			FileOp::Restore(task) => {
+				if task.force {
+					match fs::symlink_metadata(&task.to).await {
+						Ok(m) if m.is_dir() => fs::remove_dir_all(&task.to).await?,
+						Ok(_) => fs::remove_file(&task.to).await?,
+						Err(_) => {}
+					}
+				}
+
+				let task_ = task.clone();
+				tokio::task::spawn_blocking(move || Self::restore_do(&task_)).await??;
+				audit("restore", &task.target, Some(&task.to), None);
+				if let Some(tx) = &task.restored {
+					tx.send(task.target.clone()).ok();
+				}
+				self.sch.send(TaskOp::Adv(task.id, 1, 0))?;
+			}
+			FileOp::Extract(task) => {
+				// Whatever is left was skipped, or is from a failure midway
+				let result = self.extract_do(task).await;
+				fs::remove_dir_all(&task.staging).await.ok();
+				result?;
+				self.sch.send(TaskOp::Adv(task.id, 1, 0))?;
+			}
+			FileOp::Compress(task) => {
+				let mut child =
+					external::compress(task.format, &task.staging, &task.cwd, &task.names, task.dereference)?;
+				let mut lines = ChildLines::new(&mut child);
+				let mut stderr = String::new();
+				while let Some((line, is_err)) = lines.next().await? {
+					let Some(path) = task.format.added(&line, is_err) else {
+						if is_err {
+							stderr.push_str(&line);
+							stderr.push('\n');
+						}
+						continue;
+					};
+					match Self::metadata(&task.cwd.join(path), task.dereference).await {
+						Ok(m) if !m.is_dir() => self.sch.send(TaskOp::Adv(task.id, 0, m.len()))?,
+						_ => {}
+					}
+				}
+
+				if !child.wait().await?.success() {
+					fs::remove_file(&task.staging).await.ok();
+					bail!("{} failed to create {:?}: {}", task.format.tool(), task.to, stderr.trim());
+				}
+
+				if task.force {
+					match fs::symlink_metadata(&task.to).await {
+						Ok(m) if m.is_dir() => fs::remove_dir_all(&task.to).await?,
+						Ok(_) => fs::remove_file(&task.to).await?,
+						Err(_) => {}
+					}
+				}
+				fs::rename(&task.staging, &task.to).await?;
+				audit("compress", &task.cwd, Some(&task.to), None);
+
+				let size = fs::metadata(&task.to).await.map(|m| m.len()).unwrap_or(0);
+				self.log(task.id, format!("Created {:?}, {}", task.to, readable_size(size)))?;
+				self.sch.send(TaskOp::Adv(task.id, 1, 0))?;
+			}
 		}
 		Ok(())
 	}
 
+	// Re-queues `op` after a backoff if `e` looks transient and attempts are left
+	pub(crate) fn retry(&self, op: &mut FileOp, e: &anyhow::Error) -> bool {
+		let (id, attempt, limit) = match op {
+			FileOp::Paste(t) => (t.id, &mut t.attempt, TASKS.paste_retry),
+			FileOp::Delete(t) => (t.id, &mut t.attempt, TASKS.delete_retry),
+			_ => return false,
+		};
+		if *attempt >= limit || !e.downcast_ref::<io::Error>().is_some_and(Self::transient) {
+			return false;
+		}
+
+		*attempt += 1;
+		if self.sch.send(TaskOp::Retry(id, *attempt, limit)).is_err() {
+			return false;
+		}
+
+		let delay = TASKS.retry_backoff.saturating_mul(1 << (*attempt - 1).min(10));
+		let (tx, op) = (self.tx.clone(), op.clone());
+		tokio::spawn(async move {
+			sleep(Duration::from_millis(delay)).await;
+			tx.send(op).await.ok();
+		});
+		true
+	}
+
+	// Errors that may go away on their own; a full disk or a denied permission
+	// won't
+	fn transient(e: &io::Error) -> bool {
+		use io::ErrorKind::*;
+		if matches!(
+			e.kind(),
+			Interrupted
+				| WouldBlock
+				| TimedOut
+				| ConnectionReset
+				| ConnectionAborted
+				| BrokenPipe
+				| UnexpectedEof
+		) {
+			return true;
+		}
+
+		#[cfg(unix)]
+		return matches!(
+			e.raw_os_error(),
+			Some(libc::EBUSY | libc::EAGAIN | libc::EIO | libc::ETIMEDOUT | libc::ESTALE)
+		);
+		#[cfg(windows)]
+		return false;
+	}
+
 	pub(crate) async fn paste(&self, mut task: FileOpPaste) -> Result<()> {
-		if task.cut {
+		if task.cut && Self::same_device(&task.from, &task.to).await {
 			match fs::rename(&task.from, &task.to).await {
-				Ok(_) => return self.succ(task.id),
+				Ok(_) => {
+					task.audit(None);
+					return self.succ(task.id);
+				}
 				Err(e) if e.kind() == NotFound => return self.succ(task.id),
 				_ => {}
 			}
+		} else if task.cut {
+			self.log(task.id, format!("Moving {:?} across filesystems, copying it first", task.from))?;
 		}
 
 		let meta = Self::metadata(&task.from, task.follow).await?;
@@ -311,6 +522,244 @@ impl File {
 		self.succ(id)
 	}
 
+	pub(crate) async fn extract(&self, task: FileOpExtract) -> Result<()> {
+		let id = task.id;
+
+		self.sch.send(TaskOp::New(id, task.length))?;
+		self.tx.send(FileOp::Extract(task)).await?;
+		self.succ(id)
+	}
+
+	pub(crate) async fn compress(&self, mut task: FileOpCompress) -> Result<()> {
+		let id = task.id;
+		for name in &task.names {
+			task.length += calculate_size(&task.cwd.join(name)).await;
+		}
+
+		self.sch.send(TaskOp::New(id, task.length))?;
+		self.tx.send(FileOp::Compress(task)).await?;
+		self.succ(id)
+	}
+
+	async fn extract_do(&self, task: &FileOpExtract) -> Result<()> {
+		let mut child = external::unar(&task.archive, &task.staging)?;
+		let mut lines = ChildLines::new(&mut child);
+		let mut stderr = String::new();
+		while let Some((line, is_err)) = lines.next().await? {
+			if let Some(len) = external::unar_extracted(&line) {
+				self.sch.send(TaskOp::Adv(task.id, 0, len))?;
+			} else if line.contains("... Failed!") {
+				self.log(task.id, line.trim().to_owned())?;
+			} else if is_err {
+				stderr.push_str(&line);
+				stderr.push('\n');
+			}
+		}
+
+		if !child.wait().await?.success() {
+			bail!("unar failed to extract {:?}: {}", task.archive, stderr.trim());
+		}
+
+		for (path, to, force) in &task.moves {
+			let from =
+				if path.as_os_str().is_empty() { task.staging.clone() } else { task.staging.join(path) };
+			if fs::symlink_metadata(&from).await.is_err() {
+				continue;
+			}
+
+			if *force {
+				Self::merge(&from, to).await?;
+			} else {
+				fs::rename(&from, to).await?;
+			}
+			audit("extract", &task.archive, Some(to), None);
+		}
+		Ok(())
+	}
+
+	// Moves `from` onto `to` like an overwriting paste does, directories are
+	// merged, and only the files that clash are replaced
+	fn merge<'a>(from: &'a Path, to: &'a Path) -> BoxFuture<'a, io::Result<()>> {
+		async move {
+			let Ok(dest) = fs::symlink_metadata(to).await else {
+				return fs::rename(from, to).await;
+			};
+
+			let src = fs::symlink_metadata(from).await?;
+			match (src.is_dir(), dest.is_dir()) {
+				(true, true) => {
+					let mut it = fs::read_dir(from).await?;
+					while let Some(entry) = it.next_entry().await? {
+						Self::merge(&entry.path(), &to.join(entry.file_name())).await?;
+					}
+					fs::remove_dir(from).await
+				}
+				(false, true) => {
+					Err(io::Error::new(AlreadyExists, format!("{to:?} is a directory, not overwriting it")))
+				}
+				_ => {
+					fs::remove_file(to).await?;
+					fs::rename(from, to).await
+				}
+			}
+		}
+		.boxed()
+	}
+
+	pub(crate) async fn restore(&self, task: FileOpRestore) -> Result<()> {
+		let id = task.id;
+
+		self.sch.send(TaskOp::New(id, 0))?;
+		self.tx.send(FileOp::Restore(task)).await?;
+		self.succ(id)
+	}
+
+	// Pick the most recently trashed item that came from `target`
+	#[cfg(not(target_os = "macos"))]
+	fn restore_do(task: &FileOpRestore) -> Result<()> {
+		let item = trash::os_limited::list()?
+			.into_iter()
+			.filter(|i| i.original_path() == *task.target)
+			.max_by_key(|i| i.time_deleted)
+			.ok_or_else(|| anyhow::anyhow!("{:?} is not in the trash", task.target))?;
+
+		if task.to == task.target {
+			return Ok(trash::os_limited::restore_all([item])?);
+		}
+
+		// Freedesktop keeps the item beside its `.trashinfo`, so it can be moved out
+		#[cfg(unix)]
+		{
+			let file = Self::trashed_file(&item)
+				.ok_or_else(|| anyhow::anyhow!("Malformed trash entry {:?}", item.id))?;
+
+			std::fs::rename(file, &task.to)?;
+			Ok(std::fs::remove_file(&item.id)?)
+		}
+		#[cfg(windows)]
+		anyhow::bail!("Restoring under another name isn't supported on this platform")
+	}
+
+	#[cfg(target_os = "macos")]
+	fn restore_do(_: &FileOpRestore) -> Result<()> {
+		anyhow::bail!("Restoring from the trash isn't supported on macOS")
+	}
+
+	// Every trash directory is listed, including the ones on other volumes
+	#[cfg(not(target_os = "macos"))]
+	pub(crate) async fn empty_trash(&self, id: usize) -> Result<()> {
+		let items = tokio::task::spawn_blocking(trash::os_limited::list).await??;
+
+		let mut sized = Vec::with_capacity(items.len());
+		for item in items {
+			let len = match Self::trashed_file(&item) {
+				Some(p) => calculate_size(&p).await,
+				None => 0,
+			};
+			self.sch.send(TaskOp::New(id, len))?;
+			sized.push((item, len));
+		}
+
+		let mut reclaimed = 0;
+		for (item, len) in sized {
+			let name = item.original_path();
+			match tokio::task::spawn_blocking(move || trash::os_limited::purge_all([item])).await? {
+				Ok(()) => {
+					reclaimed += len;
+					self.sch.send(TaskOp::Adv(id, 1, len))?;
+				}
+				Err(e) => self.fail(id, format!("Failed to purge {name:?}: {e}"))?,
+			}
+		}
+
+		self.log(id, format!("Reclaimed {}", yazi_shared::readable_size(reclaimed)))?;
+		self.succ(id)
+	}
+
+	#[cfg(target_os = "macos")]
+	pub(crate) async fn empty_trash(&self, id: usize) -> Result<()> {
+		self.fail(id, "Emptying the trash isn't supported on macOS".to_owned())
+	}
+
+	#[cfg(all(unix, not(target_os = "macos")))]
+	fn trashed_file(item: &trash::TrashItem) -> Option<PathBuf> {
+		let info = Path::new(&item.id);
+		let root = info.parent()?.parent()?;
+		Some(root.join("files").join(info.file_stem()?))
+	}
+
+	#[cfg(windows)]
+	fn trashed_file(_: &trash::TrashItem) -> Option<PathBuf> { None }
+
+	#[cfg(unix)]
+	async fn preserve(&self, task: &FileOpPaste) -> Result<()> {
+		use yazi_shared::fs::{set_owner, set_times};
+
+		// The copy itself is whole, so none of these fails the task, a cut still
+		// gives up its source
+		let meta = match fs::metadata(&task.from).await {
+			Ok(m) => m,
+			Err(e) => {
+				return self
+					.log(task.id, format!("Failed to preserve the attributes of {:?}: {e}", task.to))
+			}
+		};
+		if let Err(e) = fs::set_permissions(&task.to, meta.permissions()).await {
+			self.log(task.id, format!("Failed to preserve the permissions of {:?}: {e}", task.to))?;
+		}
+
+		let to = task.to.to_path_buf();
+		let (times, owner) =
+			tokio::task::spawn_blocking(move || (set_times(&to, &meta), set_owner(&to, &meta))).await?;
+
+		if let Err(e) = times {
+			self.log(task.id, format!("Failed to preserve the timestamps of {:?}: {e}", task.to))?;
+		}
+		// Changing the owner usually requires privileges
+		if let Err(e) = owner {
+			self.log(task.id, format!("Failed to preserve the ownership of {:?}: {e}", task.to))?;
+		}
+		Ok(())
+	}
+
+	#[cfg(windows)]
+	async fn preserve(&self, _: &FileOpPaste) -> Result<()> { Ok(()) }
+
+	pub(crate) async fn cleanup(op: &FileOp) {
+		// Remove the partially written destination of an interrupted copy
+		match op {
+			FileOp::Paste(task) if !task.copied => _ = fs::remove_file(&task.to).await,
+			FileOp::Extract(task) => _ = fs::remove_dir_all(&task.staging).await,
+			FileOp::Compress(task) => _ = fs::remove_file(&task.staging).await,
+			_ => {}
+		}
+	}
+
+	// Whether `to` can be reached with a rename, `true` if that's unknown so it
+	// still gets a try
+	#[cfg(unix)]
+	async fn same_device(from: &Path, to: &Path) -> bool {
+		use std::os::unix::fs::MetadataExt;
+
+		let Some(parent) = to.parent() else {
+			return true;
+		};
+		match (fs::symlink_metadata(from).await, fs::metadata(parent).await) {
+			(Ok(a), Ok(b)) => a.dev() == b.dev(),
+			_ => true,
+		}
+	}
+
+	#[cfg(windows)]
+	async fn same_device(_: &Path, _: &Path) -> bool { true }
+
+	async fn same_len(from: &Path, to: &Path) -> bool {
+		match (fs::metadata(from).await, fs::metadata(to).await) {
+			(Ok(a), Ok(b)) => a.len() == b.len(),
+			_ => false,
+		}
+	}
+
 	async fn metadata(path: &Path, follow: bool) -> io::Result<Metadata> {
 		if !follow {
 			return fs::symlink_metadata(path).await;
@@ -352,18 +801,138 @@ impl File {
 
 	#[inline]
 	fn log(&self, id: usize, line: String) -> Result<()> { Ok(self.sch.send(TaskOp::Log(id, line))?) }
+
+	#[inline]
+	fn denied_paste(&self, task: &FileOpPaste, e: io::Error) -> Result<()> {
+		task.audit(Some(&e.to_string()));
+		let op = TaskDenied::Paste { from: task.from.clone(), to: task.to.clone(), cut: task.cut };
+		Ok(self.sch.send(TaskOp::Denied(task.id, op, format!("Paste task denied: {:?}, {e}", task)))?)
+	}
+}
+
+impl FileOp {
+	// Records a failure that ended the operation as a whole
+	pub(crate) fn audit(&self, error: &str) {
+		match self {
+			Self::Paste(t) => t.audit(Some(error)),
+			Self::Link(_) => {}
+			Self::Delete(t) => audit("delete", &t.target, None, Some(error)),
+			Self::Trash(t) => audit("trash", &t.target, None, Some(error)),
+			Self::Restore(t) => audit("restore", &t.target, Some(&t.to), Some(error)),
+			Self::Extract(t) => audit("extract", &t.archive, t.staging.parent(), Some(error)),
+			Self::Compress(t) => audit("compress", &t.cwd, Some(&t.to), Some(error)),
+		}
+	}
 }
 
 impl FileOpPaste {
+	#[inline]
+	fn audit(&self, error: Option<&str>) {
+		audit(if self.cut { "move" } else { "copy" }, &self.from, Some(&self.to), error);
+	}
+
 	fn to_link(&self, meta: Metadata) -> FileOpLink {
 		FileOpLink {
-			id:       self.id,
-			from:     self.from.clone(),
-			to:       self.to.clone(),
-			meta:     Some(meta),
-			resolve:  true,
-			relative: false,
-			delete:   self.cut,
+			id:          self.id,
+			from:        self.from.clone(),
+			to:          self.to.clone(),
+			meta:        Some(meta),
+			resolve:     true,
+			dereference: false,
+			relative:    false,
+			delete:      self.cut,
+		}
+	}
+}
+
+// Lines of both pipes of a child as they come, so that neither fills up and
+// blocks it while the other is being read, with whether it's from stderr
+struct ChildLines {
+	out: Option<Lines<BufReader<ChildStdout>>>,
+	err: Option<Lines<BufReader<ChildStderr>>>,
+}
+
+impl ChildLines {
+	fn new(child: &mut Child) -> Self {
+		Self {
+			out: child.stdout.take().map(|s| BufReader::new(s).lines()),
+			err: child.stderr.take().map(|s| BufReader::new(s).lines()),
+		}
+	}
+
+	async fn next(&mut self) -> io::Result<Option<(String, bool)>> {
+		loop {
+			let (line, is_err) = match (&mut self.out, &mut self.err) {
+				(None, None) => return Ok(None),
+				(Some(out), None) => (out.next_line().await?, false),
+				(None, Some(err)) => (err.next_line().await?, true),
+				(Some(out), Some(err)) => select! {
+					line = out.next_line() => (line?, false),
+					line = err.next_line() => (line?, true),
+				},
+			};
+
+			match line {
+				Some(line) => return Ok(Some((line, is_err))),
+				None if is_err => self.err = None,
+				None => self.out = None,
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::{create_dir_all, read_to_string, write};
+
+	use yazi_shared::fs::TempDir;
+
+	use super::*;
+
+	#[tokio::test]
+	async fn test_merge() {
+		let root = TempDir::new("merge").unwrap();
+		let (from, to) = (root.join("staging/a"), root.join("a"));
+		create_dir_all(from.join("sub")).unwrap();
+		create_dir_all(to.join("sub")).unwrap();
+		write(from.join("sub/clash"), "new").unwrap();
+		write(from.join("added"), "").unwrap();
+		write(to.join("sub/clash"), "old").unwrap();
+		write(to.join("sub/kept"), "").unwrap();
+
+		File::merge(&from, &to).await.unwrap();
+		assert_eq!(read_to_string(to.join("sub/clash")).unwrap(), "new");
+		assert!(to.join("sub/kept").exists());
+		assert!(to.join("added").exists());
+		assert!(!from.exists());
+
+		// A file doesn't replace a directory
+		write(root.join("file"), "").unwrap();
+		assert!(File::merge(&root.join("file"), &to).await.is_err());
+		assert!(to.join("sub/kept").exists());
+	}
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn test_child_lines() {
+		// More than a pipe holds on stderr before anything on stdout
+		let mut child = tokio::process::Command::new("sh")
+			.args(["-c", "yes x | head -n 100000 >&2; echo done"])
+			.stdout(std::process::Stdio::piped())
+			.stderr(std::process::Stdio::piped())
+			.spawn()
+			.unwrap();
+
+		let (mut out, mut err) = (vec![], 0);
+		let mut lines = ChildLines::new(&mut child);
+		while let Some((line, is_err)) = lines.next().await.unwrap() {
+			if is_err {
+				err += 1
+			} else {
+				out.push(line)
+			}
 		}
+		assert_eq!(out, ["done"]);
+		assert_eq!(err, 100000);
+		assert!(child.wait().await.unwrap().success());
 	}
 }
@@ -1,21 +1,26 @@
-use std::{collections::{BTreeMap, BTreeSet}, sync::Arc};
+use std::{collections::{BTreeMap, BTreeSet, HashMap, HashSet}, fs::Metadata, sync::Arc, time::{Duration, Instant, SystemTime}};
 
 use anyhow::Result;
 use parking_lot::Mutex;
 use tokio::{fs, sync::mpsc};
 use yazi_adaptor::Image;
-use yazi_config::PREVIEW;
-use yazi_shared::{emit, fs::{calculate_size, FilesOp, Url}, Throttle};
+use yazi_config::{MANAGER, PREVIEW};
+use yazi_shared::{emit, fs::{FilesOp, Url}, Throttle};
 
-use crate::{external, TaskOp};
+use crate::{external, power::Power, TaskOp};
+
+const SIZE_PARTIAL_INTERVAL: Duration = Duration::from_millis(500);
 
 pub(crate) struct Precache {
 	tx: async_channel::Sender<PrecacheOp>,
 	rx: async_channel::Receiver<PrecacheOp>,
 
-	sch: mpsc::UnboundedSender<TaskOp>,
+	sch:   mpsc::UnboundedSender<TaskOp>,
+	power: Arc<Power>,
 
 	pub(crate) size_handing: Mutex<BTreeSet<Url>>,
+	// Directories whose git status is being read, and whether to read it again
+	pub(crate) git_handing:  Mutex<HashMap<Url, bool>>,
 }
 
 #[derive(Debug)]
@@ -42,28 +47,40 @@ pub(crate) struct PrecacheOpMime {
 pub(crate) struct PrecacheOpImage {
 	pub id:     usize,
 	pub target: Url,
+	pub mtime:  Option<SystemTime>,
 }
 
 #[derive(Debug)]
 pub(crate) struct PrecacheOpVideo {
 	pub id:     usize,
 	pub target: Url,
+	pub mtime:  Option<SystemTime>,
 }
 
 #[derive(Debug)]
 pub(crate) struct PrecacheOpPDF {
 	pub id:     usize,
 	pub target: Url,
+	pub mtime:  Option<SystemTime>,
 }
 
 impl Precache {
 	pub(crate) fn new(sch: mpsc::UnboundedSender<TaskOp>) -> Self {
 		let (tx, rx) = async_channel::unbounded();
-		Self { tx, rx, sch, size_handing: Default::default() }
+		Self {
+			tx,
+			rx,
+			sch,
+			power: Power::start(),
+			size_handing: Default::default(),
+			git_handing: Default::default(),
+		}
 	}
 
-	#[inline]
+	// Held back while throttled, the preview of the hovered file doesn't go
+	// through here so it never is
 	pub(crate) async fn recv(&self) -> Result<(usize, PrecacheOp)> {
+		self.power.ready().await;
 		Ok(match self.rx.recv().await? {
 			PrecacheOp::Image(t) => (t.id, PrecacheOp::Image(t)),
 			PrecacheOp::Video(t) => (t.id, PrecacheOp::Video(t)),
@@ -74,7 +91,7 @@ impl Precache {
 	pub(crate) async fn work(&self, task: &mut PrecacheOp) -> Result<()> {
 		match task {
 			PrecacheOp::Image(task) => {
-				let cache = PREVIEW.cache(&task.target, 0);
+				let cache = PREVIEW.cache(&task.target, Self::mtime(&task.target, task.mtime).await, 0);
 				if fs::symlink_metadata(&cache).await.is_ok() {
 					return Ok(self.sch.send(TaskOp::Adv(task.id, 1, 0))?);
 				}
@@ -82,7 +99,12 @@ impl Precache {
 				self.sch.send(TaskOp::Adv(task.id, 1, 0))?;
 			}
 			PrecacheOp::Video(task) => {
-				let cache = PREVIEW.cache(&task.target, 0);
+				let cache = PREVIEW.cache_with(
+					&task.target,
+					Self::mtime(&task.target, task.mtime).await,
+					0,
+					&PREVIEW.video_seek.to_string(),
+				);
 				if fs::symlink_metadata(&cache).await.is_ok() {
 					return Ok(self.sch.send(TaskOp::Adv(task.id, 1, 0))?);
 				}
@@ -91,7 +113,7 @@ impl Precache {
 				self.sch.send(TaskOp::Adv(task.id, 1, 0))?;
 			}
 			PrecacheOp::Pdf(task) => {
-				let cache = PREVIEW.cache(&task.target, 0);
+				let cache = PREVIEW.cache(&task.target, Self::mtime(&task.target, task.mtime).await, 0);
 				if fs::symlink_metadata(&cache).await.is_ok() {
 					return Ok(self.sch.send(TaskOp::Adv(task.id, 1, 0))?);
 				}
@@ -113,50 +135,156 @@ impl Precache {
 		self.succ(task.id)
 	}
 
+	pub(crate) async fn git(&self, id: usize, cwd: Url) -> Result<()> {
+		self.sch.send(TaskOp::New(id, 0))?;
+		loop {
+			// Outside of a repository there's nothing to show
+			if let Ok(status) = external::git_status(&cwd).await {
+				emit!(Files(FilesOp::Git(cwd.clone(), status)));
+			}
+
+			let mut handing = self.git_handing.lock();
+			if handing.get(&cwd) != Some(&true) {
+				handing.remove(&cwd);
+				break;
+			}
+			handing.insert(cwd.clone(), false);
+		}
+
+		self.sch.send(TaskOp::Adv(id, 1, 0))?;
+		self.succ(id)
+	}
+
 	pub(crate) async fn size(&self, task: PrecacheOpSize) -> Result<()> {
 		self.sch.send(TaskOp::New(task.id, 0))?;
 
-		let length = calculate_size(&task.target).await;
-		task.throttle.done((task.target, length), |buf| {
+		let length = self.size_do(&task.target).await;
+		task.throttle.done((task.target, length), |mut buf| {
+			// Sizes canceled by leaving the directory are no longer handed, drop them
 			let mut handing = self.size_handing.lock();
-			for (path, _) in &buf {
-				handing.remove(path);
-			}
+			buf.retain(|(path, _)| handing.remove(path));
 
-			let parent = buf[0].0.parent_url().unwrap();
-			emit!(Files(FilesOp::Size(parent, BTreeMap::from_iter(buf))));
+			if let Some(parent) = buf.first().and_then(|(path, _)| path.parent_url()) {
+				emit!(Files(FilesOp::Size(parent, BTreeMap::from_iter(buf))));
+			}
 		});
 
 		self.sch.send(TaskOp::Adv(task.id, 1, 0))?;
 		self.succ(task.id)
 	}
 
-	pub(crate) fn image(&self, id: usize, targets: Vec<Url>) -> Result<()> {
-		for target in targets {
+	async fn size_do(&self, target: &Url) -> u64 {
+		let Ok(meta) = fs::symlink_metadata(target).await else {
+			return 0;
+		};
+		if !meta.is_dir() {
+			return Self::len(&meta);
+		}
+
+		// Stay on the filesystem of the target, not to wander into network mounts
+		let (parent, dev) = (target.parent_url(), Self::dev(&meta));
+		let mut seen = HashSet::new();
+		let (mut total, mut last) = (0, Instant::now());
+
+		let mut stack = vec![target.to_path_buf()];
+		while let Some(path) = stack.pop() {
+			if !self.size_handing.lock().contains(target) {
+				break;
+			}
+
+			let Ok(mut it) = fs::read_dir(path).await else {
+				continue;
+			};
+
+			while let Ok(Some(entry)) = it.next_entry().await {
+				let Ok(meta) = entry.metadata().await else {
+					continue;
+				};
+
+				if meta.is_dir() {
+					if Self::dev(&meta) == dev {
+						stack.push(entry.path());
+					}
+				} else if Self::first_link(&mut seen, &meta) {
+					total += Self::len(&meta);
+				}
+			}
+
+			if let Some(parent) = parent.as_ref().filter(|_| last.elapsed() >= SIZE_PARTIAL_INTERVAL) {
+				last = Instant::now();
+				emit!(Files(FilesOp::Sizing(parent.clone(), BTreeMap::from([(target.clone(), total)]))));
+			}
+		}
+		total
+	}
+
+	#[cfg(unix)]
+	fn first_link(seen: &mut HashSet<(u64, u64)>, meta: &Metadata) -> bool {
+		use std::os::unix::fs::MetadataExt;
+		meta.nlink() <= 1 || seen.insert((meta.dev(), meta.ino()))
+	}
+
+	#[cfg(unix)]
+	#[inline]
+	fn len(meta: &Metadata) -> u64 {
+		use std::os::unix::fs::MetadataExt;
+		if MANAGER.size_allocated {
+			meta.blocks() * 512
+		} else {
+			meta.len()
+		}
+	}
+
+	#[cfg(not(unix))]
+	#[inline]
+	fn len(meta: &Metadata) -> u64 { meta.len() }
+
+	#[cfg(unix)]
+	#[inline]
+	fn dev(meta: &Metadata) -> u64 { std::os::unix::fs::MetadataExt::dev(meta) }
+
+	#[cfg(not(unix))]
+	#[inline]
+	fn dev(_: &Metadata) -> u64 { 0 }
+
+	#[cfg(not(unix))]
+	#[inline]
+	fn first_link(_: &mut HashSet<(u64, u64)>, _: &Metadata) -> bool { true }
+
+	pub(crate) fn image(&self, id: usize, targets: Vec<(Url, Option<SystemTime>)>) -> Result<()> {
+		for (target, mtime) in targets {
 			self.sch.send(TaskOp::New(id, 0))?;
-			self.tx.send_blocking(PrecacheOp::Image(PrecacheOpImage { id, target }))?;
+			self.tx.send_blocking(PrecacheOp::Image(PrecacheOpImage { id, target, mtime }))?;
 		}
 		self.succ(id)
 	}
 
-	pub(crate) fn video(&self, id: usize, targets: Vec<Url>) -> Result<()> {
-		for target in targets {
+	pub(crate) fn video(&self, id: usize, targets: Vec<(Url, Option<SystemTime>)>) -> Result<()> {
+		for (target, mtime) in targets {
 			self.sch.send(TaskOp::New(id, 0))?;
-			self.tx.send_blocking(PrecacheOp::Video(PrecacheOpVideo { id, target }))?;
+			self.tx.send_blocking(PrecacheOp::Video(PrecacheOpVideo { id, target, mtime }))?;
 		}
 		self.succ(id)
 	}
 
-	pub(crate) fn pdf(&self, id: usize, targets: Vec<Url>) -> Result<()> {
-		for target in targets {
+	pub(crate) fn pdf(&self, id: usize, targets: Vec<(Url, Option<SystemTime>)>) -> Result<()> {
+		for (target, mtime) in targets {
 			self.sch.send(TaskOp::New(id, 0))?;
-			self.tx.send_blocking(PrecacheOp::Pdf(PrecacheOpPDF { id, target }))?;
+			self.tx.send_blocking(PrecacheOp::Pdf(PrecacheOpPDF { id, target, mtime }))?;
 		}
 		self.succ(id)
 	}
 }
 
 impl Precache {
+	// Files outside the folder come without their modification time
+	async fn mtime(target: &Url, known: Option<SystemTime>) -> Option<SystemTime> {
+		match known {
+			Some(m) => Some(m),
+			None => fs::metadata(target).await.ok()?.modified().ok(),
+		}
+	}
+
 	#[inline]
 	fn succ(&self, id: usize) -> Result<()> { Ok(self.sch.send(TaskOp::Succ(id))?) }
 }
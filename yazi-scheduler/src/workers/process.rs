@@ -1,4 +1,4 @@
-use std::{ffi::OsString, mem};
+use std::{ffi::OsString, mem, path::PathBuf};
 
 use anyhow::Result;
 use tokio::{io::{AsyncBufReadExt, BufReader}, select, sync::{mpsc, oneshot}};
@@ -16,6 +16,8 @@ pub(crate) struct ProcessOpOpen {
 	pub args:   Vec<OsString>,
 	pub block:  bool,
 	pub orphan: bool,
+	pub env:    Vec<(String, String)>,
+	pub cwd:    Option<PathBuf>,
 	pub cancel: oneshot::Sender<()>,
 }
 
@@ -26,6 +28,8 @@ impl From<&mut ProcessOpOpen> for ShellOpt {
 			args:   mem::take(&mut value.args),
 			piped:  false,
 			orphan: value.orphan,
+			env:    mem::take(&mut value.env),
+			cwd:    value.cwd.take(),
 		}
 	}
 }
@@ -0,0 +1,185 @@
+use std::{borrow::Cow, path::Path};
+
+use tokio::{fs::File, io::{self, AsyncReadExt}};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Charset {
+	Utf8,
+	Utf16Le,
+	Utf16Be,
+	Windows1252,
+	ShiftJis,
+	Binary,
+}
+
+#[rustfmt::skip]
+const WINDOWS_1252: [char; 32] = [
+	'€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+	'\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+impl Charset {
+	pub fn detect(b: &[u8]) -> Self {
+		if b.starts_with(b"\xff\xfe") {
+			return Self::Utf16Le;
+		} else if b.starts_with(b"\xfe\xff") {
+			return Self::Utf16Be;
+		}
+
+		if b.iter().filter(|&&c| c == 0).count() * 20 > b.len() {
+			return Self::Binary;
+		}
+
+		if std::str::from_utf8(b).is_ok() {
+			Self::Utf8
+		} else if cfg!(unix) && Self::is_shift_jis(b) {
+			Self::ShiftJis
+		} else {
+			Self::Windows1252
+		}
+	}
+
+	// Every non-ASCII byte has to be part of a double-byte character, or a
+	// half-width katakana, and they have to come in runs rather than alone, as
+	// the accented letters of Windows-1252 text mostly do
+	fn is_shift_jis(b: &[u8]) -> bool {
+		let (mut pairs, mut runs, mut last) = (0, 0, usize::MAX);
+		let mut i = 0;
+		while i < b.len() {
+			match b[i] {
+				0x00..=0x7f | 0xa1..=0xdf => i += 1,
+				0x81..=0x9f | 0xe0..=0xfc => {
+					match b.get(i + 1) {
+						Some(0x40..=0x7e | 0x80..=0xfc) => {}
+						// The prefix may end in the middle of a character
+						None => break,
+						Some(_) => return false,
+					}
+					pairs += 1;
+					runs += (last != i) as usize;
+					i += 2;
+					last = i;
+				}
+				_ => return false,
+			}
+		}
+		pairs > 0 && runs * 2 <= pairs
+	}
+
+	pub async fn sniff(path: &Path) -> io::Result<Self> {
+		let mut buf = Vec::with_capacity(4096);
+		File::open(path).await?.take(4096).read_to_end(&mut buf).await?;
+
+		// The prefix may end in the middle of a multi-byte character
+		if buf.len() == 4096 {
+			if let Some(i) = buf.iter().rev().take(4).position(|&c| c & 0xc0 != 0x80) {
+				buf.truncate(buf.len() - i - 1);
+			}
+		}
+		Ok(Self::detect(&buf))
+	}
+
+	pub fn decode(self, b: &[u8]) -> Cow<str> {
+		match self {
+			Self::Utf8 | Self::Binary => {
+				let s = String::from_utf8_lossy(b);
+				match s.strip_prefix('\u{feff}') {
+					Some(t) => Cow::Owned(t.to_owned()),
+					None => s,
+				}
+			}
+			Self::Utf16Le | Self::Utf16Be => {
+				let units = b.chunks_exact(2).map(|c| {
+					if self == Self::Utf16Le {
+						u16::from_le_bytes([c[0], c[1]])
+					} else {
+						u16::from_be_bytes([c[0], c[1]])
+					}
+				});
+				let s: String = char::decode_utf16(units).map(|c| c.unwrap_or('\u{fffd}')).collect();
+				Cow::Owned(s.strip_prefix('\u{feff}').map(ToOwned::to_owned).unwrap_or(s))
+			}
+			Self::Windows1252 => Cow::Owned(
+				b.iter()
+					.map(|&c| match c {
+						0x80..=0x9f => WINDOWS_1252[c as usize - 0x80],
+						_ => c as char,
+					})
+					.collect(),
+			),
+			Self::ShiftJis => match Self::iconv(b"SHIFT_JIS\0", b) {
+				Some(s) => Cow::Owned(s),
+				None => Self::Windows1252.decode(b),
+			},
+		}
+	}
+
+	// Decodes with the system's iconv, instead of bundling the JIS X 0208 tables
+	#[cfg(unix)]
+	fn iconv(from: &[u8], b: &[u8]) -> Option<String> {
+		let cd = unsafe { libc::iconv_open(b"UTF-8\0".as_ptr().cast(), from.as_ptr().cast()) };
+		if cd as isize == -1 {
+			return None;
+		}
+
+		let (mut out, mut buf) = (Vec::with_capacity(b.len() * 3 / 2), [0u8; 4096]);
+		let (mut input, mut left) = (b.as_ptr() as *mut libc::c_char, b.len());
+		while left > 0 {
+			let (mut output, mut room) = (buf.as_mut_ptr() as *mut libc::c_char, buf.len());
+			let n = unsafe { libc::iconv(cd, &mut input, &mut left, &mut output, &mut room) };
+			out.extend_from_slice(&buf[..buf.len() - room]);
+
+			// Invalid or incomplete sequences are replaced one byte at a time
+			if n == usize::MAX && io::Error::last_os_error().raw_os_error() != Some(libc::E2BIG) {
+				out.extend_from_slice("\u{fffd}".as_bytes());
+				input = unsafe { input.add(1) };
+				left -= 1;
+			}
+		}
+
+		unsafe { libc::iconv_close(cd) };
+		String::from_utf8(out).ok()
+	}
+
+	#[cfg(windows)]
+	fn iconv(_: &[u8], _: &[u8]) -> Option<String> { None }
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Utf8 => "UTF-8",
+			Self::Utf16Le => "UTF-16LE",
+			Self::Utf16Be => "UTF-16BE",
+			Self::Windows1252 => "Windows-1252",
+			Self::ShiftJis => "Shift_JIS",
+			Self::Binary => "binary",
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_detect() {
+		assert_eq!(Charset::detect("héllo".as_bytes()), Charset::Utf8);
+		assert_eq!(Charset::detect("你好".as_bytes()), Charset::Utf8);
+		assert_eq!(Charset::detect(b"caf\xe9"), Charset::Windows1252);
+		assert_eq!(Charset::detect(b"\xff\xfeh\0i\0"), Charset::Utf16Le);
+		assert_eq!(Charset::detect(b"\x93\xfa\x96{\x8c\xea"), Charset::ShiftJis);
+		assert_eq!(Charset::detect(b"r\xe9sum\xe9 \x93caf\xe9\x94"), Charset::Windows1252);
+		assert_eq!(Charset::detect(b"\x7fELF\x02\x01\x01\0\0\0\0\0"), Charset::Binary);
+	}
+
+	#[test]
+	fn test_decode() {
+		assert_eq!(Charset::Windows1252.decode(b"\x93caf\xe9\x94"), "“café”");
+		assert_eq!(Charset::Utf16Be.decode(b"\xfe\xff\0h\0i"), "hi");
+		assert_eq!(Charset::Utf8.decode(b"\xef\xbb\xbfabc"), "abc");
+		#[cfg(unix)]
+		{
+			assert_eq!(Charset::ShiftJis.decode(b"\x93\xfa\x96{\x8c\xea, \xb1"), "日本語, ｱ");
+			assert_eq!(Charset::ShiftJis.decode(b"a\xffb"), "a\u{fffd}b");
+		}
+	}
+}
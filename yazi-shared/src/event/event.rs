@@ -88,12 +88,13 @@ pub struct PreviewLock {
 pub enum PreviewData {
 	Folder,
 	Text(String),
-	Image,
+	// With the EXIF text shown beneath it, if any
+	Image(Option<String>),
 }
 
 impl PreviewLock {
 	#[inline]
-	pub fn is_image(&self) -> bool { matches!(self.data, PreviewData::Image) }
+	pub fn is_image(&self) -> bool { matches!(self.data, PreviewData::Image(_)) }
 
 	#[inline]
 	pub fn is_folder(&self) -> bool { matches!(self.data, PreviewData::Folder) }
@@ -1,20 +1,26 @@
-use std::{fs::Metadata, time::SystemTime};
+use std::{fs::Metadata, path::Path, time::SystemTime};
 
 use bitflags::bitflags;
 
 bitflags! {
 	#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-	pub struct ChaKind: u8 {
-		const DIR           = 0b00000001;
+	pub struct ChaKind: u16 {
+		const DIR           = 0b00000000_00000001;
 
-		const HIDDEN        = 0b00000010;
-		const LINK          = 0b00000100;
-		const BAD_LINK      = 0b00001000;
+		const HIDDEN        = 0b00000000_00000010;
+		const LINK          = 0b00000000_00000100;
+		const BAD_LINK      = 0b00000000_00001000;
+		const LOOP_LINK     = 0b00000100_00000000;
 
-		const BLOCK_DEVICE  = 0b00010000;
-		const CHAR_DEVICE   = 0b00100000;
-		const FIFO          = 0b01000000;
-		const SOCKET        = 0b10000000;
+		const BLOCK_DEVICE  = 0b00000000_00010000;
+		const CHAR_DEVICE   = 0b00000000_00100000;
+		const FIFO          = 0b00000000_01000000;
+		const SOCKET        = 0b00000000_10000000;
+
+		// Windows directory junctions, and other reparse points whose target
+		// can't be read, or that aren't links at all
+		const JUNCTION      = 0b00000001_00000000;
+		const REPARSE       = 0b00000010_00000000;
 	}
 }
 
@@ -27,6 +33,16 @@ pub struct Cha {
 	pub modified:    Option<SystemTime>,
 	#[cfg(unix)]
 	pub permissions: u32,
+	#[cfg(unix)]
+	pub uid:         u32,
+	#[cfg(unix)]
+	pub gid:         u32,
+	#[cfg(unix)]
+	pub nlink:       u64,
+	#[cfg(unix)]
+	pub dev:         u64,
+	#[cfg(unix)]
+	pub blocks:      u64,
 }
 
 impl From<Metadata> for Cha {
@@ -53,6 +69,19 @@ impl From<Metadata> for Cha {
 			}
 		}
 
+		#[cfg(windows)]
+		{
+			use std::os::windows::fs::MetadataExt;
+			// FILE_ATTRIBUTE_HIDDEN
+			if m.file_attributes() & 0x2 != 0 {
+				ck |= ChaKind::HIDDEN;
+			}
+			// FILE_ATTRIBUTE_REPARSE_POINT, on something that isn't a symlink or junction
+			if m.file_attributes() & 0x400 != 0 && !m.is_symlink() {
+				ck |= ChaKind::REPARSE;
+			}
+		}
+
 		Self {
 			kind:     ck,
 			len:      m.len(),
@@ -66,6 +95,16 @@ impl From<Metadata> for Cha {
 				use std::os::unix::prelude::PermissionsExt;
 				m.permissions().mode()
 			},
+			#[cfg(unix)]
+			uid:                      std::os::unix::fs::MetadataExt::uid(&m),
+			#[cfg(unix)]
+			gid:                      std::os::unix::fs::MetadataExt::gid(&m),
+			#[cfg(unix)]
+			nlink:                    std::os::unix::fs::MetadataExt::nlink(&m),
+			#[cfg(unix)]
+			dev:                      std::os::unix::fs::MetadataExt::dev(&m),
+			#[cfg(unix)]
+			blocks:                   std::os::unix::fs::MetadataExt::blocks(&m),
 		}
 	}
 }
@@ -76,9 +115,125 @@ impl Cha {
 		self.kind |= kind;
 		self
 	}
+
+	#[inline]
+	pub fn with_created(mut self, created: Option<SystemTime>) -> Self {
+		self.created = created.or(self.created);
+		self
+	}
+
+	// `Metadata` has no birth time on Linux, so ask `statx` for it directly.
+	// Filesystems that don't record it leave `STATX_BTIME` unset in the mask.
+	#[cfg(all(target_os = "linux", target_env = "gnu"))]
+	pub fn birth(path: &Path) -> Option<SystemTime> {
+		use std::{ffi::CString, os::unix::ffi::OsStrExt, time::Duration};
+
+		let p = CString::new(path.as_os_str().as_bytes()).ok()?;
+		let mut st = std::mem::MaybeUninit::<libc::statx>::uninit();
+		let ret =
+			unsafe { libc::statx(libc::AT_FDCWD, p.as_ptr(), 0, libc::STATX_BTIME, st.as_mut_ptr()) };
+		if ret != 0 {
+			return None;
+		}
+
+		let st = unsafe { st.assume_init() };
+		if st.stx_mask & libc::STATX_BTIME == 0 {
+			return None;
+		}
+
+		let d = Duration::new(st.stx_btime.tv_sec.unsigned_abs(), st.stx_btime.tv_nsec);
+		if st.stx_btime.tv_sec >= 0 {
+			SystemTime::UNIX_EPOCH.checked_add(d)
+		} else {
+			SystemTime::UNIX_EPOCH.checked_sub(d)
+		}
+	}
+
+	#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+	#[inline]
+	pub fn birth(_: &Path) -> Option<SystemTime> { None }
+
+	// The reparse tag of the link itself, which tells a junction from a symlink
+	#[cfg(windows)]
+	pub fn reparse_tag(path: &Path) -> Option<u32> {
+		use std::{ffi::c_void, os::windows::{fs::OpenOptionsExt, io::AsRawHandle}};
+
+		#[repr(C)]
+		#[derive(Default)]
+		struct TagInfo {
+			attributes: u32,
+			tag:        u32,
+		}
+
+		#[link(name = "kernel32")]
+		extern "system" {
+			fn GetFileInformationByHandleEx(
+				file: *mut c_void,
+				class: i32,
+				info: *mut c_void,
+				size: u32,
+			) -> i32;
+		}
+
+		// FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS
+		let f = std::fs::OpenOptions::new()
+			.access_mode(0)
+			.custom_flags(0x0020_0000 | 0x0200_0000)
+			.open(path)
+			.ok()?;
+
+		// FileAttributeTagInfo
+		let mut info = TagInfo::default();
+		let ok = unsafe {
+			GetFileInformationByHandleEx(
+				f.as_raw_handle(),
+				9,
+				&mut info as *mut _ as *mut c_void,
+				std::mem::size_of::<TagInfo>() as u32,
+			)
+		};
+		(ok != 0).then_some(info.tag)
+	}
 }
 
 impl Cha {
+	#[cfg(unix)]
+	#[inline]
+	pub fn owner(self) -> String { super::user_name(self.uid) }
+
+	#[cfg(unix)]
+	#[inline]
+	pub fn group(self) -> String { super::group_name(self.gid) }
+
+	// Windows only reports link counts through an opened handle, not worth it here
+	#[inline]
+	pub fn nlink(self) -> u64 {
+		#[cfg(unix)]
+		return self.nlink;
+		#[cfg(windows)]
+		return 1;
+	}
+
+	// The device of the filesystem it lives on, always 0 on Windows, where
+	// volume roots are told by their path instead
+	#[inline]
+	pub fn dev(self) -> u64 {
+		#[cfg(unix)]
+		return self.dev;
+		#[cfg(windows)]
+		return 0;
+	}
+
+	// Bytes actually allocated on disk, `st_blocks` is always in 512-byte units;
+	// Windows has no cheap way to tell, so it's the apparent size there
+	#[inline]
+	pub fn allocated(self) -> u64 {
+		#[cfg(unix)]
+		return self.blocks * 512;
+		#[cfg(windows)]
+		return self.len;
+	}
+
 	#[inline]
 	pub fn is_dir(self) -> bool { self.kind.contains(ChaKind::DIR) }
 
@@ -91,6 +246,15 @@ impl Cha {
 	#[inline]
 	pub fn is_bad_link(self) -> bool { self.kind.contains(ChaKind::BAD_LINK) }
 
+	#[inline]
+	pub fn is_loop_link(self) -> bool { self.kind.contains(ChaKind::LOOP_LINK) }
+
+	#[inline]
+	pub fn is_junction(self) -> bool { self.kind.contains(ChaKind::JUNCTION) }
+
+	#[inline]
+	pub fn is_reparse(self) -> bool { self.kind.contains(ChaKind::REPARSE) }
+
 	#[cfg(unix)]
 	#[inline]
 	pub fn is_block_device(self) -> bool { self.kind.contains(ChaKind::BLOCK_DEVICE) }
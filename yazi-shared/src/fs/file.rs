@@ -1,9 +1,33 @@
 use std::{borrow::Cow, collections::BTreeMap, ffi::OsStr, fs::Metadata, ops::Deref};
 
 use anyhow::Result;
-use tokio::fs;
+use futures::future::join_all;
+use tokio::{fs, sync::Semaphore, task::spawn_blocking};
 
-use crate::fs::{Cha, ChaKind, Url};
+use crate::fs::{file_flags, xattr, xattrs, Cha, ChaKind, Url, Xattr};
+
+const STAT_CONCURRENCY: usize = 32;
+
+// Whether `Cha::birth` can tell anything
+const HAS_BIRTH: bool = cfg!(all(target_os = "linux", target_env = "gnu"));
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkStatus {
+	Resolved,
+	Broken,
+	Loop,
+}
+
+impl LinkStatus {
+	#[inline]
+	pub fn as_str(self) -> &'static str {
+		match self {
+			Self::Resolved => "resolved",
+			Self::Broken => "broken",
+			Self::Loop => "loop",
+		}
+	}
+}
 
 #[derive(Clone, Debug, Default)]
 pub struct File {
@@ -20,26 +44,101 @@ impl Deref for File {
 }
 
 impl File {
-	#[inline]
 	pub async fn from(url: Url) -> Result<Self> {
 		let meta = fs::symlink_metadata(&url).await?;
-		Ok(Self::from_meta(url, meta).await)
+		let mut file = Self::from_meta(url, meta).await;
+		if HAS_BIRTH {
+			let url = file.url.clone();
+			let created = spawn_blocking(move || Cha::birth(&url)).await.ok().flatten();
+			file.cha = file.cha.with_created(created);
+		}
+		Ok(file)
 	}
 
-	pub async fn from_meta(url: Url, mut meta: Metadata) -> Self {
-		let mut cm = ChaKind::empty();
+	// Stat a batch concurrently, leaving out the ones that fail
+	pub async fn from_many(urls: Vec<Url>) -> Vec<Self> {
+		let semaphore = Semaphore::new(STAT_CONCURRENCY);
+		let files = join_all(urls.into_iter().map(|url| {
+			let semaphore = &semaphore;
+			async move {
+				let _permit = semaphore.acquire().await;
+				let meta = fs::symlink_metadata(&url).await.ok()?;
+				Some(Self::from_meta(url, meta).await)
+			}
+		}))
+		.await;
+		Self::with_births(files.into_iter().flatten().collect()).await
+	}
 
-		let (is_link, mut link_to) = (meta.is_symlink(), None);
-		if is_link {
-			cm |= ChaKind::LINK;
-			meta = fs::metadata(&url).await.unwrap_or(meta);
-			link_to = fs::read_link(&url).await.map(Url::from).ok();
+	// Birth times take a `statx` of their own, which blocks, so they're read for
+	// a whole batch at once on the blocking pool
+	pub async fn with_births(files: Vec<Self>) -> Vec<Self> {
+		if !HAS_BIRTH || files.is_empty() {
+			return files;
 		}
 
-		if is_link && meta.is_symlink() {
+		spawn_blocking(move || {
+			files
+				.into_iter()
+				.map(|mut f| {
+					f.cha = f.cha.with_created(Cha::birth(&f.url));
+					f
+				})
+				.collect()
+		})
+		.await
+		.unwrap_or_default()
+	}
+
+	pub async fn from_meta(url: Url, meta: Metadata) -> Self {
+		if !meta.is_symlink() {
+			return Self::from_meta_sync(url, meta).unwrap();
+		}
+
+		let mut cm = ChaKind::LINK;
+		let link_to = match fs::read_link(&url).await {
+			Ok(p) => Some(Url::from(p)),
+			// Some reparse points can only be read with elevated permissions
+			Err(e) if cfg!(windows) && e.kind() == std::io::ErrorKind::PermissionDenied => {
+				cm |= ChaKind::REPARSE;
+				None
+			}
+			Err(_) => None,
+		};
+
+		// IO_REPARSE_TAG_MOUNT_POINT
+		#[cfg(windows)]
+		if Cha::reparse_tag(&url) == Some(0xa000_0003) {
+			cm |= ChaKind::JUNCTION;
+		}
+
+		let target = match fs::metadata(&url).await {
+			Ok(m) => m,
+			#[cfg(unix)]
+			Err(e) if e.raw_os_error() == Some(libc::ELOOP) => {
+				cm |= ChaKind::LOOP_LINK;
+				meta
+			}
+			Err(_) => meta,
+		};
+		if target.is_symlink() {
 			cm |= ChaKind::BAD_LINK;
 		}
+		Self::build(url, target, cm, link_to)
+	}
+
+	// Builds the file without awaiting, for callers that already have its
+	// metadata. Symlinks need their target's metadata, so `None` is returned
+	// and they should go through `from_meta` instead. Neither reads the birth
+	// time, see `with_births`.
+	pub fn from_meta_sync(url: Url, meta: Metadata) -> Option<Self> {
+		if meta.is_symlink() {
+			return None;
+		}
+		Some(Self::build(url, meta, ChaKind::empty(), None))
+	}
 
+	fn build(url: Url, meta: Metadata, mut cm: ChaKind, link_to: Option<Url>) -> Self {
 		if url.is_hidden() {
 			cm |= ChaKind::HIDDEN;
 		}
@@ -83,4 +182,84 @@ impl File {
 	// --- Link to / Is link
 	#[inline]
 	pub fn link_to(&self) -> Option<&Url> { self.link_to.as_ref() }
+
+	// The target of a symlink and whether it resolves, from what was collected
+	// when the file was stat'ed
+	pub fn symlink_info(&self) -> Option<(&Url, LinkStatus)> {
+		if !self.is_link() {
+			return None;
+		}
+
+		let status = if self.is_loop_link() {
+			LinkStatus::Loop
+		} else if self.is_bad_link() {
+			LinkStatus::Broken
+		} else {
+			LinkStatus::Resolved
+		};
+		Some((self.link_to.as_ref()?, status))
+	}
+
+	// Directories always have `.` and each child's `..` linking to them
+	#[inline]
+	pub fn is_hardlinked(&self) -> bool { !self.is_dir() && self.nlink() > 1 }
+
+	// Less than half of the apparent size is backed by disk, e.g. VM images
+	#[inline]
+	pub fn is_sparse(&self) -> bool { !self.is_dir() && self.allocated() < self.len / 2 }
+
+	// A directory on another filesystem than its parent, whose device is
+	// `parent_dev`
+	#[inline]
+	pub fn is_mount_point(&self, parent_dev: u64) -> bool {
+		#[cfg(unix)]
+		return self.is_dir() && self.dev() != parent_dev;
+		#[cfg(windows)]
+		return {
+			_ = parent_dev;
+			self.is_dir() && self.url.parent().is_none()
+		};
+	}
+
+	// --- Extended attributes, read on demand
+	#[inline]
+	pub fn xattrs(&self) -> Vec<Xattr> { xattrs(&self.url) }
+
+	#[inline]
+	pub fn xattr(&self, name: &str) -> Option<Vec<u8>> { xattr(&self.url, name) }
+
+	// Only files and directories have any, opening the others may block or have
+	// side effects
+	#[inline]
+	pub fn flags(&self) -> Vec<&'static str> {
+		if self.is_block_device() || self.is_char_device() || self.is_fifo() || self.is_socket() {
+			return vec![];
+		}
+		file_flags(&self.url)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn link(kind: ChaKind) -> File {
+		let mut f = File::from_dummy(Url::from("/a/link"));
+		f.cha.kind |= ChaKind::LINK | kind;
+		f.link_to = Some(Url::from("../target"));
+		f
+	}
+
+	#[test]
+	fn test_symlink_info() {
+		assert!(File::from_dummy(Url::from("/a/file")).symlink_info().is_none());
+
+		let info = |f: &File| f.symlink_info().map(|(u, s)| (u.to_string_lossy().into_owned(), s));
+		assert_eq!(info(&link(ChaKind::empty())), Some(("../target".to_owned(), LinkStatus::Resolved)));
+		assert_eq!(info(&link(ChaKind::BAD_LINK)), Some(("../target".to_owned(), LinkStatus::Broken)));
+		assert_eq!(
+			info(&link(ChaKind::BAD_LINK | ChaKind::LOOP_LINK)),
+			Some(("../target".to_owned(), LinkStatus::Loop))
+		);
+	}
 }
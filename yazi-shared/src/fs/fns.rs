@@ -1,7 +1,8 @@
-use std::{collections::VecDeque, path::{Path, PathBuf}};
+use std::{collections::VecDeque, fmt::Write, fs::Metadata, path::{Path, PathBuf}};
 
 use anyhow::Result;
-use tokio::{fs, io, select, sync::{mpsc, oneshot}, time};
+use sha2::{Digest, Sha256};
+use tokio::{fs, io::{self, AsyncReadExt, AsyncWriteExt}, select, sync::{mpsc, oneshot}, time};
 
 pub async fn calculate_size(path: &Path) -> u64 {
 	let mut total = 0;
@@ -91,6 +92,124 @@ pub fn copy_with_progress(from: &Path, to: &Path) -> mpsc::Receiver<Result<u64,
 	rx
 }
 
+// Same as `copy_with_progress`, but hashes the source with SHA-256 as it
+// streams through, then reads the destination back from the disk rather than
+// the page cache, and fails with `InvalidData` if the two differ.
+pub fn copy_with_checksum(from: &Path, to: &Path) -> mpsc::Receiver<Result<u64, io::Error>> {
+	let (tx, rx) = mpsc::channel(1);
+
+	tokio::spawn({
+		let (from, to) = (from.to_path_buf(), to.to_path_buf());
+
+		async move {
+			_ = match _copy_with_checksum(&from, &to, &tx).await {
+				Ok(()) => tx.send(Ok(0)).await,
+				Err(e) => tx.send(Err(e)).await,
+			};
+		}
+	});
+
+	rx
+}
+
+async fn _copy_with_checksum(
+	from: &Path,
+	to: &Path,
+	tx: &mpsc::Sender<Result<u64, io::Error>>,
+) -> io::Result<()> {
+	let mut src = fs::File::open(from).await?;
+	let permissions = src.metadata().await?.permissions();
+
+	let mut dest = fs::File::create(to).await?;
+	let (mut buf, mut hasher) = (vec![0; 1 << 20], Sha256::new());
+	loop {
+		let n = src.read(&mut buf).await?;
+		if n == 0 {
+			break;
+		}
+
+		hasher.update(&buf[..n]);
+		dest.write_all(&buf[..n]).await?;
+		if tx.send(Ok(n as u64)).await.is_err() {
+			return Ok(());
+		}
+	}
+
+	dest.sync_all().await?;
+	drop(dest);
+	fs::set_permissions(to, permissions).await?;
+
+	let (expected, actual) = (hasher.finalize().into(), checksum(to).await?);
+	if expected != actual {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			format!("checksum mismatch, expected {} but got {}", hex(&expected), hex(&actual)),
+		));
+	}
+	Ok(())
+}
+
+fn hex(hash: &[u8; 32]) -> String {
+	hash.iter().fold(String::with_capacity(64), |mut s, b| {
+		_ = write!(s, "{b:02x}");
+		s
+	})
+}
+
+// The SHA-256 of a file. Its cached pages are dropped first where supported,
+// and the file is expected to have been synced, so that what's read is what's
+// on the disk
+pub async fn checksum(path: &Path) -> io::Result<[u8; 32]> {
+	let mut f = fs::File::open(path).await?;
+	#[cfg(target_os = "linux")]
+	{
+		use std::os::fd::AsRawFd;
+		unsafe { libc::posix_fadvise(f.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+	}
+
+	let (mut buf, mut hasher) = (vec![0; 1 << 20], Sha256::new());
+	loop {
+		match f.read(&mut buf).await? {
+			0 => return Ok(hasher.finalize().into()),
+			n => hasher.update(&buf[..n]),
+		}
+	}
+}
+
+// Replicate the access and modification times of `meta` onto `to`
+#[cfg(unix)]
+pub fn set_times(to: &Path, meta: &Metadata) -> io::Result<()> {
+	use std::{ffi::CString, os::unix::{ffi::OsStrExt, fs::MetadataExt}};
+
+	let path = CString::new(to.as_os_str().as_bytes())?;
+	let times = [
+		libc::timespec { tv_sec: meta.atime() as _, tv_nsec: meta.atime_nsec() as _ },
+		libc::timespec { tv_sec: meta.mtime() as _, tv_nsec: meta.mtime_nsec() as _ },
+	];
+
+	if unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), 0) } != 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(())
+}
+
+// Replicate the owner and group of `meta` onto `to`, if they differ
+#[cfg(unix)]
+pub fn set_owner(to: &Path, meta: &Metadata) -> io::Result<()> {
+	use std::{ffi::CString, os::unix::{ffi::OsStrExt, fs::MetadataExt}};
+
+	let current = std::fs::symlink_metadata(to)?;
+	if current.uid() == meta.uid() && current.gid() == meta.gid() {
+		return Ok(());
+	}
+
+	let path = CString::new(to.as_os_str().as_bytes())?;
+	if unsafe { libc::lchown(path.as_ptr(), meta.uid(), meta.gid()) } != 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(())
+}
+
 // Convert a file mode to a string representation
 #[cfg(unix)]
 #[allow(clippy::collapsible_else_if)]
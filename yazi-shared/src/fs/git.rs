@@ -0,0 +1,23 @@
+// Where a file stands in the git working tree it's in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GitStatus {
+	Untracked,
+	Modified,
+	Staged,
+	Ignored,
+	Conflicted,
+}
+
+impl GitStatus {
+	// Shown next to the icon, after the codes of `git status --short`
+	#[inline]
+	pub fn marker(self) -> &'static str {
+		match self {
+			Self::Untracked => "?",
+			Self::Modified => "M",
+			Self::Staged => "A",
+			Self::Ignored => "!",
+			Self::Conflicted => "U",
+		}
+	}
+}
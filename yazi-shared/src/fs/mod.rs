@@ -1,13 +1,23 @@
 mod cha;
 mod file;
 mod fns;
+mod git;
 mod op;
 mod path;
+mod temp;
 mod url;
+#[cfg(unix)]
+mod users;
+mod xattr;
 
 pub use cha::*;
 pub use file::*;
 pub use fns::*;
+pub use git::*;
 pub use op::*;
 pub use path::*;
+pub use temp::*;
 pub use url::*;
+#[cfg(unix)]
+pub use users::*;
+pub use xattr::*;
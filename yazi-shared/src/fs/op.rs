@@ -1,6 +1,6 @@
 use std::{collections::{BTreeMap, BTreeSet}, sync::atomic::{AtomicU64, Ordering}};
 
-use super::File;
+use super::{File, GitStatus};
 use crate::{emit, fs::Url};
 
 pub static FILES_TICKET: AtomicU64 = AtomicU64::new(0);
@@ -10,6 +10,8 @@ pub enum FilesOp {
 	Full(Url, Vec<File>),
 	Part(Url, u64, Vec<File>),
 	Size(Url, BTreeMap<Url, u64>),
+	Sizing(Url, BTreeMap<Url, u64>),
+	Git(Url, BTreeMap<Url, GitStatus>),
 	IOErr(Url),
 
 	Creating(Url, BTreeMap<Url, File>),
@@ -24,6 +26,8 @@ impl FilesOp {
 			Self::Full(url, _) => url,
 			Self::Part(url, ..) => url,
 			Self::Size(url, _) => url,
+			Self::Sizing(url, _) => url,
+			Self::Git(url, _) => url,
 			Self::IOErr(url) => url,
 
 			Self::Creating(url, _) => url,
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, env, ffi::OsString, path::{Component, Path, PathBuf, MAIN_SEPARATOR}};
+use std::{borrow::Cow, collections::HashMap, env, ffi::OsString, path::{Component, Path, PathBuf, MAIN_SEPARATOR}};
 
 use tokio::fs;
 
@@ -52,7 +52,10 @@ pub fn ends_with_slash(p: &Path) -> bool {
 	if let [.., last] = b { *last == MAIN_SEPARATOR as u8 } else { false }
 }
 
-pub async fn unique_path(mut p: Url) -> Url {
+#[inline]
+pub async fn unique_path(p: Url) -> Url { unique_path_with(p, |i| format!("_{i}")).await }
+
+pub async fn unique_path_with(mut p: Url, suffix: impl Fn(usize) -> String) -> Url {
 	let Some(stem) = p.file_stem().map(|s| s.to_owned()) else {
 		return p;
 	};
@@ -73,7 +76,7 @@ pub async fn unique_path(mut p: Url) -> Url {
 
 		let mut name = OsString::with_capacity(stem.len() + ext.len() + 5);
 		name.push(&stem);
-		name.push(format!("_{i}"));
+		name.push(suffix(i));
 		if !ext.is_empty() {
 			name.push(&ext);
 		}
@@ -128,11 +131,240 @@ pub fn path_relative_to<'a>(path: &'a Path, root: &Path) -> Cow<'a, Path> {
 	Cow::from(buf)
 }
 
+// Why `name` can't be a file name, or `None` if it can; `nested` allows path
+// separators, for names that create intermediate directories
+pub fn name_error(name: &str, nested: bool) -> Option<&'static str> {
+	if name.is_empty() {
+		return Some("Name cannot be empty");
+	}
+	if name == "." || name == ".." {
+		return Some("Name cannot be `.` or `..`");
+	}
+
+	let sep = |c: char| c == '/' || cfg!(windows) && c == '\\';
+	if !nested && name.contains(sep) {
+		return Some("Name cannot contain a path separator");
+	}
+
+	#[cfg(unix)]
+	let illegal = |c: char| c == '\0';
+	#[cfg(windows)]
+	let illegal = |c: char| c.is_control() || "<>:\"|?*".contains(c);
+	if name.contains(illegal) {
+		return Some("Name contains illegal characters");
+	}
+	None
+}
+
+// Whether `dest` is `src` itself or somewhere inside it, following symlinks on
+// both sides, so that nothing gets copied or moved into its own subtree
+pub fn path_within(dest: &Path, src: &Path) -> bool {
+	let (Ok(dest), Ok(src)) = (std::fs::canonicalize(dest), std::fs::canonicalize(src)) else {
+		return false;
+	};
+	src.is_dir() && dest.starts_with(src)
+}
+
+// Whether `a` and `b` name the same file, folding case if `insensitive`
+pub fn path_eq(a: &Path, b: &Path, insensitive: bool) -> bool {
+	if a == b {
+		return true;
+	}
+	if !insensitive {
+		return false;
+	}
+
+	let (mut a, mut b) = (a.components(), b.components());
+	loop {
+		match (a.next(), b.next()) {
+			(None, None) => return true,
+			(Some(x), Some(y)) if x == y => {}
+			(Some(x), Some(y)) => {
+				let (x, y) = (x.as_os_str().to_string_lossy(), y.as_os_str().to_string_lossy());
+				if !x.chars().flat_map(char::to_lowercase).eq(y.chars().flat_map(char::to_lowercase)) {
+					return false;
+				}
+			}
+			_ => return false,
+		}
+	}
+}
+
+// Whether the filesystem `path` lives on folds case, by looking up the nearest
+// existing ancestor with a letter in its name under its case swapped
+pub fn case_insensitive(path: &Path) -> bool {
+	for p in path.ancestors() {
+		let Some(name) = p.file_name().map(|n| n.to_string_lossy()) else {
+			continue;
+		};
+		if !name.chars().any(char::is_alphabetic) {
+			continue;
+		}
+		let Ok(meta) = std::fs::symlink_metadata(p) else {
+			continue;
+		};
+
+		let swapped: String = name
+			.chars()
+			.flat_map(|c| {
+				if c.is_uppercase() {
+					c.to_lowercase().collect::<Vec<_>>()
+				} else {
+					c.to_uppercase().collect()
+				}
+			})
+			.collect();
+		let Ok(other) = std::fs::symlink_metadata(p.with_file_name(swapped)) else {
+			return false;
+		};
+
+		#[cfg(unix)]
+		return {
+			use std::os::unix::fs::MetadataExt;
+			meta.dev() == other.dev() && meta.ino() == other.ino()
+		};
+		#[cfg(windows)]
+		return meta.file_type() == other.file_type();
+	}
+	cfg!(any(windows, target_os = "macos"))
+}
+
+// Whether `a` and `b` are the same file, even if they differ only in case on
+// a filesystem that doesn't tell them apart
+#[inline]
+pub fn same_file(a: &Path, b: &Path) -> bool {
+	path_eq(a, b, true) && (a == b || case_insensitive(a))
+}
+
+// Splits `paths` into those naming distinct entries and those naming one of
+// them again, through a symlinked parent or in a different case. Entries are
+// told apart by `lstat`, so a symlink and its target are two of them, and so
+// are hard links to the same file under different names.
+pub fn unique_files<T: AsRef<Path>>(paths: impl IntoIterator<Item = T>) -> (Vec<T>, Vec<T>) {
+	#[cfg(unix)]
+	fn key(p: &Path) -> Option<(u64, u64, u64, u64)> {
+		use std::os::unix::fs::MetadataExt;
+		let (m, d) = (std::fs::symlink_metadata(p).ok()?, std::fs::metadata(p.parent()?).ok()?);
+		Some((m.dev(), m.ino(), d.dev(), d.ino()))
+	}
+	#[cfg(windows)]
+	fn key(p: &Path) -> Option<PathBuf> {
+		std::fs::symlink_metadata(p).ok()?;
+		Some(std::fs::canonicalize(p.parent()?).ok()?.join(p.file_name()?.to_ascii_lowercase()))
+	}
+
+	// Hard links in the same directory share the key, but not the name
+	fn same_entry(a: &Path, b: &Path) -> bool {
+		let (Some(x), Some(y)) = (a.file_name(), b.file_name()) else {
+			return false;
+		};
+		x == y || path_eq(Path::new(x), Path::new(y), true) && case_insensitive(b)
+	}
+
+	let (mut kept, mut dupes) = (Vec::<T>::new(), vec![]);
+	let mut seen: HashMap<_, Vec<usize>> = HashMap::new();
+	for p in paths {
+		let Some(key) = key(p.as_ref()) else {
+			kept.push(p);
+			continue;
+		};
+
+		let indices = seen.entry(key).or_default();
+		if indices.iter().any(|&i| same_entry(kept[i].as_ref(), p.as_ref())) {
+			dupes.push(p);
+		} else {
+			indices.push(kept.len());
+			kept.push(p);
+		}
+	}
+	(kept, dupes)
+}
+
 #[cfg(test)]
 mod tests {
 	use std::{borrow::Cow, path::Path};
 
-	use super::path_relative_to;
+	use super::{case_insensitive, name_error, path_eq, path_relative_to, path_within, unique_files};
+	use crate::fs::TempDir;
+
+	#[test]
+	fn test_name_error() {
+		assert!(name_error("foo.txt", false).is_none());
+		assert!(name_error("", false).is_some());
+		assert!(name_error("..", true).is_some());
+		assert!(name_error("foo/bar", false).is_some());
+		assert!(name_error("foo/bar", true).is_none());
+		assert!(name_error("foo\0", true).is_some());
+	}
+
+	#[test]
+	fn test_path_eq() {
+		let (a, b) = (Path::new("/tmp/Foo.txt"), Path::new("/tmp/foo.TXT"));
+		assert!(path_eq(a, a, false));
+		assert!(!path_eq(a, b, false));
+		assert!(path_eq(a, b, true));
+		assert!(path_eq(Path::new("/tmp/Ä"), Path::new("/tmp/ä"), true));
+		assert!(!path_eq(a, Path::new("/tmp/foo.txt/bar"), true));
+		assert!(!path_eq(a, Path::new("/tmp/foo.tx"), true));
+	}
+
+	#[cfg(target_os = "linux")]
+	#[test]
+	fn test_case_insensitive() {
+		let root = TempDir::new("case").unwrap();
+		std::fs::create_dir_all(root.join("Foo")).unwrap();
+		assert!(!case_insensitive(&root.join("Foo")));
+		assert!(!case_insensitive(&root.join("Foo/missing")));
+
+		// A differently-cased sibling is a separate file, not the same one
+		std::fs::create_dir_all(root.join("fOO")).unwrap();
+		assert!(!case_insensitive(&root.join("Foo")));
+	}
+
+	#[test]
+	fn test_path_within() {
+		let root = TempDir::new("within").unwrap();
+		std::fs::create_dir_all(root.join("a/b")).unwrap();
+		std::fs::create_dir_all(root.join("ab")).unwrap();
+		std::fs::write(root.join("f"), "").unwrap();
+
+		assert!(path_within(&root.join("a"), &root.join("a")));
+		assert!(path_within(&root.join("a/b"), &root.join("a")));
+		assert!(path_within(&root.join("a/b/../b"), &root.join("a")));
+		assert!(!path_within(&root.join("ab"), &root.join("a")));
+		assert!(!path_within(&root, &root.join("a")));
+		assert!(!path_within(&root.join("f"), &root.join("f")));
+		assert!(!path_within(&root.join("missing"), &root.join("a")));
+
+		#[cfg(unix)]
+		{
+			std::os::unix::fs::symlink(root.join("a/b"), root.join("link")).unwrap();
+			assert!(path_within(&root.join("link"), &root.join("a")));
+		}
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_unique_files() {
+		let root = TempDir::new("unique").unwrap();
+		std::fs::create_dir_all(root.join("dir")).unwrap();
+		std::fs::write(root.join("dir/target"), "").unwrap();
+		std::fs::hard_link(root.join("dir/target"), root.join("dir/hard")).unwrap();
+		std::os::unix::fs::symlink(root.join("dir/target"), root.join("dir/link")).unwrap();
+		std::os::unix::fs::symlink(root.join("dir"), root.join("alias")).unwrap();
+
+		// Symlinks and hard links are entries of their own
+		let paths = vec![root.join("dir/link"), root.join("dir/hard"), root.join("dir/target")];
+		let (kept, dupes) = unique_files(paths.clone());
+		assert_eq!(kept, paths);
+		assert!(dupes.is_empty());
+
+		// The same entry through a symlinked parent isn't
+		let paths = vec![root.join("dir/target"), root.join("missing"), root.join("alias/target")];
+		let (kept, dupes) = unique_files(paths);
+		assert_eq!(kept, [root.join("dir/target"), root.join("missing")]);
+		assert_eq!(dupes, [root.join("alias/target")]);
+	}
 
 	#[cfg(unix)]
 	#[test]
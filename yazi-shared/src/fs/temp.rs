@@ -0,0 +1,31 @@
+use std::{io, ops::Deref, path::{Path, PathBuf}, sync::atomic::{AtomicUsize, Ordering}};
+
+static SEQ: AtomicUsize = AtomicUsize::new(0);
+
+// A directory under the system's temporary directory, removed with everything
+// in it once dropped, even if that's while unwinding from a panic
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+	pub fn new(prefix: &str) -> io::Result<Self> {
+		let seq = SEQ.fetch_add(1, Ordering::Relaxed);
+		let path = std::env::temp_dir().join(format!("yazi-{prefix}-{}-{seq}", std::process::id()));
+
+		std::fs::create_dir_all(&path)?;
+		Ok(Self(path))
+	}
+}
+
+impl Deref for TempDir {
+	type Target = Path;
+
+	fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+impl AsRef<Path> for TempDir {
+	fn as_ref(&self) -> &Path { &self.0 }
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) { std::fs::remove_dir_all(&self.0).ok(); }
+}
@@ -0,0 +1,48 @@
+use std::{collections::HashMap, ffi::CStr};
+
+use parking_lot::Mutex;
+
+static USERS: Mutex<Option<HashMap<u32, String>>> = Mutex::new(None);
+static GROUPS: Mutex<Option<HashMap<u32, String>>> = Mutex::new(None);
+
+// Resolve a uid to its user name, or the uid itself if there's no such user
+pub fn user_name(uid: u32) -> String {
+	let mut users = USERS.lock();
+	let users = users.get_or_insert_with(Default::default);
+	users
+		.entry(uid)
+		.or_insert_with(|| unsafe {
+			let mut buf = vec![0; 16 << 10];
+			let mut pwd: libc::passwd = std::mem::zeroed();
+			let mut result = std::ptr::null_mut();
+			if libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) == 0
+				&& !result.is_null()
+			{
+				CStr::from_ptr(pwd.pw_name).to_string_lossy().into_owned()
+			} else {
+				uid.to_string()
+			}
+		})
+		.clone()
+}
+
+// Resolve a gid to its group name, or the gid itself if there's no such group
+pub fn group_name(gid: u32) -> String {
+	let mut groups = GROUPS.lock();
+	let groups = groups.get_or_insert_with(Default::default);
+	groups
+		.entry(gid)
+		.or_insert_with(|| unsafe {
+			let mut buf = vec![0; 16 << 10];
+			let mut grp: libc::group = std::mem::zeroed();
+			let mut result = std::ptr::null_mut();
+			if libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut result) == 0
+				&& !result.is_null()
+			{
+				CStr::from_ptr(grp.gr_name).to_string_lossy().into_owned()
+			} else {
+				gid.to_string()
+			}
+		})
+		.clone()
+}
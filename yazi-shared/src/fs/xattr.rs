@@ -0,0 +1,176 @@
+use std::{fmt::Write, path::Path};
+
+#[derive(Clone, Debug)]
+pub struct Xattr {
+	pub name:  String,
+	pub value: Vec<u8>,
+}
+
+impl Xattr {
+	pub fn value_lossy(&self) -> String {
+		match std::str::from_utf8(&self.value) {
+			Ok(s) if !s.chars().any(|c| c.is_control()) => s.to_owned(),
+			_ => self.value.iter().fold(String::new(), |mut s, b| {
+				_ = write!(s, "{b:02x}");
+				s
+			}),
+		}
+	}
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod sys {
+	use std::ffi::CStr;
+
+	pub(super) unsafe fn list(p: &CStr, buf: *mut libc::c_char, size: usize) -> isize {
+		#[cfg(target_os = "linux")]
+		return libc::listxattr(p.as_ptr(), buf, size);
+		#[cfg(target_os = "macos")]
+		return libc::listxattr(p.as_ptr(), buf, size, 0);
+	}
+
+	pub(super) unsafe fn get(p: &CStr, name: &CStr, buf: *mut libc::c_void, size: usize) -> isize {
+		#[cfg(target_os = "linux")]
+		return libc::getxattr(p.as_ptr(), name.as_ptr(), buf, size);
+		#[cfg(target_os = "macos")]
+		return libc::getxattr(p.as_ptr(), name.as_ptr(), buf, size, 0, 0);
+	}
+
+	pub(super) fn value(p: &CStr, name: &CStr) -> Option<Vec<u8>> {
+		let len = unsafe { get(p, name, std::ptr::null_mut(), 0) };
+		if len < 0 {
+			return None;
+		}
+
+		let mut value = vec![0u8; len as usize];
+		if len > 0 {
+			let len = unsafe { get(p, name, value.as_mut_ptr() as _, value.len()) };
+			value.truncate(len.max(0) as usize);
+		}
+		Some(value)
+	}
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub fn xattrs(path: &Path) -> Vec<Xattr> {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	let Ok(p) = CString::new(path.as_os_str().as_bytes()) else {
+		return vec![];
+	};
+
+	let len = unsafe { sys::list(&p, std::ptr::null_mut(), 0) };
+	if len <= 0 {
+		return vec![];
+	}
+
+	let mut names = vec![0u8; len as usize];
+	let len = unsafe { sys::list(&p, names.as_mut_ptr() as _, names.len()) };
+	if len <= 0 {
+		return vec![];
+	}
+	names.truncate(len as usize);
+
+	let mut result = vec![];
+	for name in names.split(|&b| b == 0).filter(|n| !n.is_empty()) {
+		let Ok(name) = CString::new(name) else {
+			continue;
+		};
+
+		let value = sys::value(&p, &name).unwrap_or_default();
+		result.push(Xattr { name: name.to_string_lossy().into_owned(), value });
+	}
+	result
+}
+
+// Read a single attribute, without listing the others
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub fn xattr(path: &Path, name: &str) -> Option<Vec<u8>> {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	let p = CString::new(path.as_os_str().as_bytes()).ok()?;
+	sys::value(&p, &CString::new(name).ok()?)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn xattrs(_: &Path) -> Vec<Xattr> { vec![] }
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn xattr(_: &Path, _: &str) -> Option<Vec<u8>> { None }
+
+#[cfg(target_os = "linux")]
+pub fn file_flags(path: &Path) -> Vec<&'static str> {
+	use std::os::{fd::AsRawFd, unix::fs::OpenOptionsExt};
+
+	// _IOR('f', 1, long)
+	const FS_IOC_GETFLAGS: libc::c_ulong = (2 << 30)
+		| ((std::mem::size_of::<libc::c_long>() as libc::c_ulong) << 16)
+		| ((b'f' as libc::c_ulong) << 8)
+		| 1;
+
+	// Opening devices may have side effects, and FIFOs block until a writer
+	// comes, in case one took the place of the file meanwhile
+	if !std::fs::metadata(path).is_ok_and(|m| m.is_file() || m.is_dir()) {
+		return vec![];
+	}
+	let f = std::fs::OpenOptions::new()
+		.read(true)
+		.custom_flags(libc::O_NONBLOCK | libc::O_NOCTTY)
+		.open(path);
+	let Ok(f) = f else {
+		return vec![];
+	};
+
+	let mut flags: libc::c_long = 0;
+	if unsafe { libc::ioctl(f.as_raw_fd(), FS_IOC_GETFLAGS as _, &mut flags) } != 0 {
+		return vec![];
+	}
+
+	[(0x10, "immutable"), (0x20, "append-only"), (0x40, "no-dump"), (0x80, "no-atime")]
+		.into_iter()
+		.filter(|&(bit, _)| flags & bit != 0)
+		.map(|(_, name)| name)
+		.collect()
+}
+
+#[cfg(target_os = "macos")]
+pub fn file_flags(path: &Path) -> Vec<&'static str> {
+	use std::os::macos::fs::MetadataExt;
+
+	let Ok(meta) = std::fs::metadata(path) else {
+		return vec![];
+	};
+
+	let flags = meta.st_flags();
+	[
+		(0x00002, "immutable"),
+		(0x00004, "append-only"),
+		(0x00001, "no-dump"),
+		(0x08000, "hidden"),
+		(0x20000, "system-immutable"),
+		(0x40000, "system-append-only"),
+	]
+	.into_iter()
+	.filter(|&(bit, _)| flags & bit != 0)
+	.map(|(_, name)| name)
+	.collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn file_flags(_: &Path) -> Vec<&'static str> { vec![] }
+
+pub fn format_xattrs(path: &Path) -> String {
+	let mut lines = vec![];
+
+	let flags = file_flags(path);
+	if !flags.is_empty() {
+		lines.push(format!("Flags: {}", flags.join(", ")));
+	}
+
+	let xattrs = xattrs(path);
+	if !xattrs.is_empty() {
+		lines.push("Extended attributes:".to_owned());
+		lines.extend(xattrs.iter().map(|x| format!("  {}: {}", x.name, x.value_lossy())));
+	}
+	lines.join("\n")
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::option_map_unit_fn)]
 
 mod chars;
+mod charset;
 mod debounce;
 mod defer;
 mod env;
@@ -11,11 +12,13 @@ mod layer;
 mod mime;
 mod natsort;
 mod ro_cell;
+mod size;
 pub mod term;
 mod throttle;
 mod time;
 
 pub use chars::*;
+pub use charset::*;
 pub use debounce::*;
 pub use defer::*;
 pub use env::*;
@@ -24,5 +27,6 @@ pub use layer::*;
 pub use mime::*;
 pub use natsort::*;
 pub use ro_cell::*;
+pub use size::*;
 pub use throttle::*;
 pub use time::*;
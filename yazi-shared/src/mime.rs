@@ -1,53 +1,134 @@
+use std::str::FromStr;
+
 pub const MIME_DIR: &str = "inode/directory";
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum MimeKind {
 	Empty,
 
 	Archive,
+	Compressed,
 
 	Image,
+	Svg,
 	Video,
+	Audio,
+	Font,
 
 	JSON,
 	PDF,
 	Text,
+	Markdown,
+	Notebook,
+	Table,
+	Document,
 
+	Binary,
 	Others,
 }
 
 impl MimeKind {
 	pub fn new(s: &str) -> Self {
-		if s.starts_with("text/")
+		if s == "text/markdown" || s == "text/x-markdown" {
+			Self::Markdown
+		} else if s == "text/csv" || s == "text/tab-separated-values" {
+			Self::Table
+		} else if s.starts_with("text/")
 			|| s.ends_with("/xml")
 			|| s.ends_with("/javascript")
 			|| s.ends_with("/x-wine-extension-ini")
 		{
 			Self::Text
-		} else if s.starts_with("image/") && s != "image/vnd.djvu" {
+		} else if s == "image/svg+xml" {
+			Self::Svg
+		} else if s == "image/vnd.djvu" {
+			Self::Document
+		} else if s.starts_with("image/") {
 			Self::Image
 		} else if s.starts_with("video/") {
 			Self::Video
+		} else if s.starts_with("audio/") {
+			Self::Audio
+		} else if s.starts_with("font/")
+			|| s == "application/font-sfnt"
+			|| s == "application/x-font-ttf"
+			|| s == "application/vnd.ms-opentype"
+		{
+			Self::Font
 		} else if s == "inode/x-empty" {
 			Self::Empty
 		} else if s == "application/json" {
 			Self::JSON
 		} else if s == "application/pdf" {
 			Self::PDF
-		} else if s == "application/zip"
-			|| s == "application/gzip"
-			|| s == "application/x-tar"
+		} else if s == "application/gzip"
 			|| s == "application/x-bzip"
 			|| s == "application/x-bzip2"
+			|| s == "application/x-xz"
+			|| s == "application/zstd"
+		{
+			Self::Compressed
+		} else if s == "application/zip"
+			|| s == "application/x-tar"
 			|| s == "application/x-7z-compressed"
 			|| s == "application/x-rar"
 		{
 			Self::Archive
+		} else if s == "application/msword"
+			|| s == "application/rtf"
+			|| s == "application/epub+zip"
+			|| s == "application/x-mobipocket-ebook"
+			|| s == "application/vnd.amazon.ebook"
+			|| s == "application/x-fictionbook+xml"
+			|| s.starts_with("application/vnd.ms-excel")
+			|| s.starts_with("application/vnd.ms-powerpoint")
+			|| s.starts_with("application/vnd.openxmlformats-officedocument.")
+			|| s.starts_with("application/vnd.oasis.opendocument.")
+		{
+			Self::Document
+		} else if s == "application/octet-stream"
+			|| s == "application/x-executable"
+			|| s == "application/x-pie-executable"
+			|| s == "application/x-sharedlib"
+			|| s == "application/x-object"
+			|| s == "application/x-coredump"
+			|| s == "application/x-mach-binary"
+			|| s == "application/x-dosexec"
+			|| s == "application/vnd.microsoft.portable-executable"
+			|| s == "application/wasm"
+			|| s == "application/java-vm"
+		{
+			Self::Binary
 		} else {
 			Self::Others
 		}
 	}
 
+	// A guess from the extension alone, for files whose mime type isn't known yet
+	pub fn from_ext(ext: &str) -> Option<Self> {
+		Some(match ext.to_ascii_lowercase().as_str() {
+			"7z" | "rar" | "tar" | "zip" => Self::Archive,
+			"bz2" | "gz" | "xz" | "zst" => Self::Compressed,
+			"avif" | "bmp" | "gif" | "heic" | "jpeg" | "jpg" | "png" | "tif" | "tiff" | "webp" => {
+				Self::Image
+			}
+			"svg" => Self::Svg,
+			"avi" | "m4v" | "mkv" | "mov" | "mp4" | "webm" | "wmv" => Self::Video,
+			"aac" | "flac" | "m4a" | "mp3" | "ogg" | "opus" | "wav" => Self::Audio,
+			"otf" | "ttf" | "woff" | "woff2" => Self::Font,
+			"json" => Self::JSON,
+			"pdf" => Self::PDF,
+			"txt" | "log" | "rs" | "py" | "js" | "ts" | "c" | "h" | "sh" | "toml" | "yaml" | "yml" => {
+				Self::Text
+			}
+			"md" | "markdown" => Self::Markdown,
+			"ipynb" => Self::Notebook,
+			"csv" | "tsv" => Self::Table,
+			"doc" | "docx" | "epub" | "odt" | "rtf" | "xls" | "xlsx" | "ppt" | "pptx" => Self::Document,
+			_ => return None,
+		})
+	}
+
 	pub fn valid(s: &str) -> bool {
 		let parts = s.split('/').collect::<Vec<_>>();
 		if parts.len() != 2 {
@@ -60,6 +141,180 @@ impl MimeKind {
 	}
 
 	pub fn show_as_image(&self) -> bool {
-		matches!(self, MimeKind::Image | MimeKind::Video | MimeKind::PDF)
+		matches!(
+			self,
+			MimeKind::Image | MimeKind::Svg | MimeKind::Video | MimeKind::Font | MimeKind::PDF
+		)
+	}
+}
+
+impl FromStr for MimeKind {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"empty" => Self::Empty,
+			"archive" => Self::Archive,
+			"compressed" => Self::Compressed,
+			"image" => Self::Image,
+			"svg" => Self::Svg,
+			"video" => Self::Video,
+			"audio" => Self::Audio,
+			"font" => Self::Font,
+			"json" => Self::JSON,
+			"pdf" => Self::PDF,
+			"text" => Self::Text,
+			"markdown" => Self::Markdown,
+			"notebook" => Self::Notebook,
+			"table" => Self::Table,
+			"document" => Self::Document,
+			"binary" => Self::Binary,
+			"others" => Self::Others,
+			_ => anyhow::bail!("unknown mime kind: {s}"),
+		})
+	}
+}
+
+// Guess the mime type from the leading bytes of a file, only the first
+// `MIME_MAGIC_LEN` bytes are needed
+pub const MIME_MAGIC_LEN: usize = 512;
+
+pub fn mime_from_magic(b: &[u8]) -> Option<&'static str> {
+	#[rustfmt::skip]
+	const MAGIC: &[(usize, &[u8], &str)] = &[
+		(0, b"\x89PNG\r\n\x1a\n",       "image/png"),
+		(0, b"\xff\xd8\xff",            "image/jpeg"),
+		(0, b"GIF87a",                  "image/gif"),
+		(0, b"GIF89a",                  "image/gif"),
+		(0, b"BM",                      "image/bmp"),
+		(4, b"ftypavif",                "image/avif"),
+		(4, b"ftypavis",                "image/avif"),
+		(4, b"ftypheic",                "image/heic"),
+		(4, b"ftypheix",                "image/heic"),
+		(4, b"ftyphevc",                "image/heic"),
+		(4, b"ftyphevx",                "image/heic"),
+		(4, b"ftypmif1",                "image/heif"),
+		(4, b"ftypmsf1",                "image/heif"),
+		(4, b"ftypisom",                "video/mp4"),
+		(4, b"ftypiso2",                "video/mp4"),
+		(4, b"ftypmp41",                "video/mp4"),
+		(4, b"ftypmp42",                "video/mp4"),
+		(4, b"ftypavc1",                "video/mp4"),
+		(4, b"ftypdash",                "video/mp4"),
+		(4, b"ftypM4V ",                "video/x-m4v"),
+		(4, b"ftypqt  ",                "video/quicktime"),
+		(4, b"ftyp3gp4",                "video/3gpp"),
+		(4, b"ftyp3gp5",                "video/3gpp"),
+		(4, b"ftyp3g2a",                "video/3gpp2"),
+		(4, b"ftypM4A ",                "audio/mp4"),
+		(0, b"\x1a\x45\xdf\xa3",        "video/x-matroska"),
+		(0, b"%PDF-",                   "application/pdf"),
+		(0, b"PK\x03\x04",              "application/zip"),
+		(0, b"\x1f\x8b",                "application/gzip"),
+		(0, b"BZh",                     "application/x-bzip2"),
+		(0, b"\xfd7zXZ\x00",            "application/x-xz"),
+		(0, b"\x28\xb5\x2f\xfd",        "application/zstd"),
+		(0, b"7z\xbc\xaf\x27\x1c",      "application/x-7z-compressed"),
+		(0, b"Rar!\x1a\x07",            "application/x-rar"),
+		(257, b"ustar",                 "application/x-tar"),
+		(0, b"\x7fELF",                 "application/x-executable"),
+		(0, b"OggS",                    "audio/ogg"),
+		(0, b"fLaC",                    "audio/flac"),
+		(0, b"ID3",                     "audio/mpeg"),
+		(0, b"ttcf",                    "font/collection"),
+		(0, b"wOFF",                    "font/woff"),
+		(0, b"wOF2",                    "font/woff2"),
+	];
+
+	if let Some(font) = sfnt_from_magic(b) {
+		return Some(font);
+	}
+	if b.len() >= 12 && b.starts_with(b"RIFF") {
+		return match &b[8..12] {
+			b"WEBP" => Some("image/webp"),
+			b"AVI " => Some("video/x-msvideo"),
+			b"WAVE" => Some("audio/x-wav"),
+			_ => None,
+		};
+	}
+
+	MAGIC
+		.iter()
+		.find(|(off, magic, _)| b.get(*off..).is_some_and(|b| b.starts_with(magic)))
+		.map(|m| m.2)
+}
+
+// `\0\x01\0\0` alone shows up in plenty of other files, so the table count
+// and the binary search fields derived from it have to agree too, and the
+// first table has to have a printable tag
+fn sfnt_from_magic(b: &[u8]) -> Option<&'static str> {
+	let mime = match b.get(..4)? {
+		b"\0\x01\0\0" | b"true" => "font/ttf",
+		b"OTTO" => "font/otf",
+		_ => return None,
+	};
+
+	let u16_at = |i: usize| Some(u16::from_be_bytes([*b.get(i)?, *b.get(i + 1)?]));
+	let tables = u16_at(4)?;
+	if tables == 0 || tables > 256 {
+		return None;
+	}
+
+	let log2 = 15 - tables.leading_zeros() as u16;
+	if (u16_at(6)?, u16_at(8)?, u16_at(10)?) != (16 << log2, log2, tables * 16 - (16 << log2)) {
+		return None;
+	}
+	b.get(12..16)?.iter().all(|c| (0x20..0x7f).contains(c)).then_some(mime)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_magic() {
+		assert_eq!(mime_from_magic(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+		assert_eq!(mime_from_magic(b"\0\0\0\x20ftypheic"), Some("image/heic"));
+		assert_eq!(mime_from_magic(b"\0\0\0\x1cftypmif1"), Some("image/heif"));
+		assert_eq!(mime_from_magic(b"\0\0\0\x20ftypisom"), Some("video/mp4"));
+		assert_eq!(mime_from_magic(b"\0\0\0\x20ftypcrx "), None);
+		assert_eq!(mime_from_magic(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+		assert_eq!(mime_from_magic(b"OTTO\0\x0a\0\x80\0\x03\0\x20CFF "), Some("font/otf"));
+		assert_eq!(mime_from_magic(b"\0\x01\0\0\0\x11\x01\0\0\x04\0\x10GDEF"), Some("font/ttf"));
+		assert_eq!(mime_from_magic(b"\0\x01\0\0\0\x11\x01\0\0\x04\0\x10\0\0\0\0"), None);
+		assert_eq!(mime_from_magic(b"\0\x01\0\0\x12\x34\0\0\0\0\0\0GDEF"), None);
+		assert_eq!(mime_from_magic(b"\0\x01\0\0"), None);
+		assert_eq!(mime_from_magic(b"hello"), None);
+
+		let mut tar = vec![0; MIME_MAGIC_LEN];
+		tar[257..262].copy_from_slice(b"ustar");
+		assert_eq!(mime_from_magic(&tar), Some("application/x-tar"));
+	}
+
+	#[test]
+	fn test_from_ext() {
+		assert_eq!(MimeKind::from_ext("JPG"), Some(MimeKind::Image));
+		assert_eq!(MimeKind::from_ext("mkv"), Some(MimeKind::Video));
+		assert_eq!(MimeKind::from_ext("unknown"), None);
+		assert_eq!("video".parse::<MimeKind>().ok(), Some(MimeKind::Video));
+		assert!("videos".parse::<MimeKind>().is_err());
+	}
+
+	#[test]
+	fn test_new() {
+		assert_eq!(MimeKind::new("audio/mpeg"), MimeKind::Audio);
+		assert_eq!(MimeKind::new("audio/x-flac"), MimeKind::Audio);
+		assert_eq!(MimeKind::new("font/woff2"), MimeKind::Font);
+		assert_eq!(MimeKind::new("application/vnd.ms-opentype"), MimeKind::Font);
+		assert_eq!(
+			MimeKind::new("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+			MimeKind::Document
+		);
+		assert_eq!(MimeKind::new("application/vnd.oasis.opendocument.spreadsheet"), MimeKind::Document);
+		assert_eq!(MimeKind::new("application/epub+zip"), MimeKind::Document);
+		assert_eq!(MimeKind::new("image/vnd.djvu"), MimeKind::Document);
+		assert_eq!(MimeKind::new("application/x-pie-executable"), MimeKind::Binary);
+		assert_eq!(MimeKind::new("application/octet-stream"), MimeKind::Binary);
+		assert_eq!(MimeKind::new("application/x-unknown-thing"), MimeKind::Others);
 	}
 }
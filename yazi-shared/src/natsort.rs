@@ -175,9 +175,16 @@ mod tests {
 			"x8-y8",
 		];
 
+		let images = vec!["img1.png", "img2.png", "img10.png", "IMG11.png", "img12.png"];
+
 		cmp(&dates);
 		cmp(&fractions);
 		cmp(&words);
+		cmp(&images);
+
+		assert_eq!(natsort("img10", "img9", false), Ordering::Greater);
+		assert_eq!(natsort("IMG2", "img10", false), Ordering::Less);
+		assert_eq!(natsort("img2", "IMG10", false), Ordering::Greater);
 	}
 
 	// #[test]
@@ -0,0 +1,10 @@
+pub fn readable_size(size: u64) -> String {
+	const UNITS: [&str; 7] = ["B", "KB", "MB", "GB", "TB", "PB", "EB"];
+
+	let (mut size, mut i) = (size as f64, 0);
+	while size > 1024.0 && i < UNITS.len() - 1 {
+		size /= 1024.0;
+		i += 1;
+	}
+	format!("{:.1} {}", size, UNITS[i])
+}
@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::Term;
+
+static LIGHT: AtomicBool = AtomicBool::new(false);
+
+impl Term {
+	// Asks the terminal for its background color with `OSC 11`, followed by a
+	// `DA1` that every terminal answers, so we don't wait on those that ignore
+	// the former. Must be called in raw mode, before the event stream starts.
+	#[cfg(unix)]
+	pub(super) fn query_background() {
+		use std::{io::{stdout, Write}, time::{Duration, Instant}};
+
+		if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+			return;
+		}
+
+		let mut out = stdout();
+		if write!(out, "\x1b]11;?\x1b\\\x1b[c").and_then(|_| out.flush()).is_err() {
+			return;
+		}
+
+		let (mut buf, start) = (Vec::with_capacity(64), Instant::now());
+		while !Self::has_da1(&buf) {
+			let left = Duration::from_millis(500).saturating_sub(start.elapsed());
+			if left.is_zero() {
+				break;
+			}
+
+			let mut fd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+			if unsafe { libc::poll(&mut fd, 1, left.as_millis() as _) } <= 0 {
+				break;
+			}
+
+			let mut b = [0; 64];
+			let n = unsafe { libc::read(libc::STDIN_FILENO, b.as_mut_ptr() as _, b.len()) };
+			if n <= 0 {
+				break;
+			}
+			buf.extend_from_slice(&b[..n as usize]);
+		}
+
+		if let Some(light) = Self::parse_background(&buf) {
+			LIGHT.store(light, Ordering::Relaxed);
+		}
+	}
+
+	#[cfg(not(unix))]
+	pub(super) fn query_background() {}
+
+	#[inline]
+	pub fn light_background() -> bool { LIGHT.load(Ordering::Relaxed) }
+
+	fn has_da1(buf: &[u8]) -> bool {
+		buf.windows(3).position(|w| w == b"\x1b[?").is_some_and(|i| buf[i..].contains(&b'c'))
+	}
+
+	// Parses `OSC 11 ; rgb:RRRR/GGGG/BBBB ST`, where each component has 1 to 4 hex
+	// digits
+	fn parse_background(buf: &[u8]) -> Option<bool> {
+		let s = String::from_utf8_lossy(buf);
+		let s = &s[s.find("]11;rgb:")? + 8..];
+		let s = &s[..s.find(['\x1b', '\x07'])?];
+
+		let mut rgb = [0f64; 3];
+		let mut parts = s.split('/');
+		for c in &mut rgb {
+			let p = parts.next().filter(|p| (1..=4).contains(&p.len()))?;
+			*c = u16::from_str_radix(p, 16).ok()? as f64 / ((1u32 << (p.len() * 4)) - 1) as f64;
+		}
+
+		Some(0.299 * rgb[0] + 0.587 * rgb[1] + 0.114 * rgb[2] > 0.5)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_background() {
+		assert_eq!(Term::parse_background(b"\x1b]11;rgb:ffff/ffff/ffff\x1b\\\x1b[?62c"), Some(true));
+		assert_eq!(Term::parse_background(b"\x1b]11;rgb:28/2c/34\x07"), Some(false));
+		assert_eq!(Term::parse_background(b"\x1b]11;rgb:fdf6/f6e3/e3e3\x1b\\"), Some(true));
+		assert_eq!(Term::parse_background(b"\x1b[?62;22c"), None);
+
+		assert!(Term::has_da1(b"\x1b]11;rgb:0/0/0\x07\x1b[?1;2c"));
+		assert!(!Term::has_da1(b"\x1b]11;rgb:0/0/0\x07"));
+	}
+}
@@ -1,5 +1,6 @@
 #![allow(clippy::module_inception)]
 
+mod background;
 mod cursor;
 mod term;
 
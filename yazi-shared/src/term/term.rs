@@ -15,6 +15,7 @@ impl Term {
 
 		enable_raw_mode()?;
 		execute!(stdout(), EnterAlternateScreen, EnableBracketedPaste, EnableFocusChange)?;
+		Self::query_background();
 
 		term.csi_u = matches!(supports_keyboard_enhancement(), Ok(true));
 		if term.csi_u {